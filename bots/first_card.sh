@@ -0,0 +1,33 @@
+#!/bin/sh
+# Smallest possible bot: always picks the first cards in its hand.
+# Run with: boba_bot simulate "sh bots/first_card.sh" "sh bots/first_card.sh"
+hand=""
+while read -r command rest; do
+    case "$command" in
+        boba)
+            echo "name First Card"
+            echo "ready"
+            ;;
+        hand)
+            hand="$rest"
+            ;;
+        go)
+            set -- $rest
+            picks=$1
+            cards=""
+            for entry in $hand; do
+                card=${entry%%:*}
+                count=${entry##*:}
+                while [ "$count" -gt 0 ] && [ "$picks" -gt 0 ]; do
+                    cards="$cards $card"
+                    count=$((count - 1))
+                    picks=$((picks - 1))
+                done
+            done
+            echo "pick$cards"
+            ;;
+        quit)
+            exit 0
+            ;;
+    esac
+done
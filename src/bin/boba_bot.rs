@@ -0,0 +1,119 @@
+//! Runs external bots, see `boba_go::bot::protocol` for what they speak.
+//!
+//! Usage:
+//! `boba_bot simulate [--games N] [--seed S] [--rounds R] [--timeout MS] <bot>...`
+//! plays N games with one seat per bot and prints the scores.
+//! `boba_bot join <address> [--password PW] [--timeout MS] <bot>` seats a bot
+//! in a hosted room and plays one game.
+//!
+//! Quote bot commands that take arguments: `"python3 my_bot.py"`.
+
+use std::time::Duration;
+
+use libp2p::identity::Keypair;
+
+use boba_go::bot::{play_game, play_hosted_game, ExternalBot, DEFAULT_MOVE_TIMEOUT};
+use boba_go::engine::GameSettings;
+use boba_go::network::swarm::build_swarm;
+
+const USAGE: &str = "usage: boba_bot simulate [--games N] [--seed S] [--rounds R] [--timeout MS] <bot>...\n       boba_bot join <address> [--password PW] [--timeout MS] <bot>";
+
+struct Options {
+    games: usize,
+    seed: Option<u64>,
+    rounds: Option<usize>,
+    timeout: Duration,
+    password: Option<String>,
+    positional: Vec<String>,
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        games: 1,
+        seed: None,
+        rounds: None,
+        timeout: DEFAULT_MOVE_TIMEOUT,
+        password: None,
+        positional: Vec::new(),
+    };
+    let mut args = args;
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            options.positional.push(arg);
+            continue;
+        }
+        let value = args.next().ok_or(format!("missing value for {arg}"))?;
+        let number = |value: &str| value.parse::<u64>().map_err(|_| format!("{arg} needs a number"));
+        match arg.as_str() {
+            "--games" => options.games = number(&value)? as usize,
+            "--seed" => options.seed = Some(number(&value)?),
+            "--rounds" => options.rounds = Some(number(&value)? as usize),
+            "--timeout" => options.timeout = Duration::from_millis(number(&value)?),
+            "--password" => options.password = Some(value),
+            _ => return Err(format!("unknown argument: {arg}")),
+        }
+    }
+    Ok(options)
+}
+
+fn simulate(options: Options) -> Result<(), String> {
+    if options.positional.len() < 2 {
+        return Err("a game needs at least two bots".to_string());
+    }
+    let mut bots = options
+        .positional
+        .iter()
+        .map(|command| ExternalBot::spawn(command, options.timeout))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut settings = GameSettings::default();
+    if let Some(rounds) = options.rounds {
+        settings.round_count = rounds;
+    }
+    let mut wins = vec![0; bots.len()];
+    for game in 0..options.games {
+        settings.seed = options.seed.map(|seed| seed.wrapping_add(game as u64));
+        let scores = play_game(settings.to_config(Vec::new()), &mut bots).map_err(|e| e.to_string())?;
+        let best = scores.iter().copied().fold(f32::MIN, f32::max);
+        let line: Vec<String> = bots.iter().zip(&scores).map(|(bot, score)| format!("{} {score:.1}", bot.name())).collect();
+        println!("game {}: {}", game + 1, line.join(", "));
+        for (seat, score) in scores.iter().enumerate() {
+            if *score == best {
+                wins[seat] += 1;
+            }
+        }
+    }
+    for (seat, bot) in bots.iter().enumerate() {
+        println!("seat {} {}: {} wins", seat + 1, bot.name(), wins[seat]);
+    }
+    Ok(())
+}
+
+async fn join(mut options: Options) -> Result<(), String> {
+    let [address, command] = <[String; 2]>::try_from(std::mem::take(&mut options.positional))
+        .map_err(|_| "join takes an address and one bot".to_string())?;
+    let bot = ExternalBot::spawn(&command, options.timeout)?;
+    let swarm = build_swarm(Keypair::generate_ed25519()).await.map_err(|e| e.to_string())?;
+    let scores = play_hosted_game(bot, swarm, &address, options.password).await?;
+    for (seat, score) in scores {
+        println!("seat {}: {score:.1}", seat + 1);
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() {
+    let mut args = std::env::args().skip(1);
+    let mode = args.next();
+    let result = match (mode.as_deref(), parse_args(args)) {
+        (_, Err(e)) => Err(e),
+        (Some("simulate"), Ok(options)) => simulate(options),
+        (Some("join"), Ok(options)) => join(options).await,
+        _ => Err("pick simulate or join".to_string()),
+    };
+    if let Err(e) = result {
+        eprintln!("{e}");
+        eprintln!("{USAGE}");
+        std::process::exit(2);
+    }
+}
@@ -0,0 +1,118 @@
+//! Circuit relay for boba-go players behind NAT.
+//!
+//! Usage: `boba_relay [--port 4001] [--ws-port 4002] [--key-file relay.key] [--external /ip4/1.2.3.4/tcp/4001]`
+//!
+//! Hosts register with `BOBA_RELAY=<printed address> boba_tui`, and show
+//! the relayed address to their players.
+
+use libp2p::{
+    futures::StreamExt,
+    identify, identity, noise, ping, relay,
+    swarm::{NetworkBehaviour, SwarmEvent},
+    tcp, yamux, Multiaddr,
+};
+use std::error::Error;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use boba_go::network::identity::load_or_create_key;
+use boba_go::network::version::IDENTIFY_PROTOCOL;
+
+#[derive(NetworkBehaviour)]
+struct RelayBehaviour {
+    relay: relay::Behaviour,
+    identify: identify::Behaviour,
+    ping: ping::Behaviour,
+}
+
+struct Options {
+    port: u16,
+    // also accept WebSocket connections, for networks that only allow web traffic
+    ws_port: Option<u16>,
+    // saved key so the relay keeps the same address across restarts
+    key_file: Option<PathBuf>,
+    external: Vec<Multiaddr>,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options { port: 4001, ws_port: None, key_file: None, external: vec![] };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or(format!("missing value for {arg}"))?;
+        match arg.as_str() {
+            "--port" => options.port = value.parse().map_err(|_| format!("invalid port: {value}"))?,
+            "--ws-port" => options.ws_port = Some(value.parse().map_err(|_| format!("invalid port: {value}"))?),
+            "--key-file" => options.key_file = Some(PathBuf::from(value)),
+            "--external" => options.external.push(value.parse().map_err(|_| format!("invalid address: {value}"))?),
+            _ => return Err(format!("unknown argument: {arg}")),
+        }
+    }
+    Ok(options)
+}
+
+fn keypair(key_file: Option<&PathBuf>) -> Result<identity::Keypair, String> {
+    match key_file {
+        Some(path) => load_or_create_key(path),
+        None => Ok(identity::Keypair::generate_ed25519()),
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let options = parse_args().map_err(|e| {
+        eprintln!("{e}");
+        eprintln!("usage: boba_relay [--port 4001] [--ws-port 4002] [--key-file relay.key] [--external <multiaddr>]");
+        e
+    })?;
+
+    let mut swarm = libp2p::SwarmBuilder::with_existing_identity(keypair(options.key_file.as_ref())?)
+        .with_tokio()
+        .with_tcp(tcp::Config::default(), noise::Config::new, yamux::Config::default)?
+        .with_websocket(noise::Config::new, yamux::Config::default)
+        .await?
+        .with_behaviour(|key| RelayBehaviour {
+            relay: relay::Behaviour::new(key.public().to_peer_id(), relay::Config::default()),
+            identify: identify::Behaviour::new(identify::Config::new(IDENTIFY_PROTOCOL.to_string(), key.public())),
+            ping: ping::Behaviour::new(ping::Config::new()),
+        })?
+        .with_swarm_config(|config| config.with_idle_connection_timeout(Duration::from_secs(60)))
+        .build();
+
+    swarm.listen_on(format!("/ip4/0.0.0.0/tcp/{}", options.port).parse()?)?;
+    if let Some(ws_port) = options.ws_port {
+        swarm.listen_on(format!("/ip4/0.0.0.0/tcp/{ws_port}/ws").parse()?)?;
+    }
+    let has_external = !options.external.is_empty();
+    for address in options.external {
+        swarm.add_external_address(address);
+    }
+
+    let local_peer_id = *swarm.local_peer_id();
+    println!("boba relay running as {local_peer_id}");
+
+    loop {
+        match swarm.select_next_some().await {
+            SwarmEvent::NewListenAddr { address, .. } => {
+                // reservations only advertise addresses the relay considers reachable,
+                // without --external assume every listen address is
+                if !has_external {
+                    swarm.add_external_address(address.clone());
+                }
+                println!("Listening on {}", address.with_p2p(local_peer_id).unwrap_or_else(|a| a));
+            }
+            SwarmEvent::Behaviour(RelayBehaviourEvent::Relay(event)) => match event {
+                relay::Event::ReservationReqAccepted { src_peer_id, .. } => {
+                    println!("Host {src_peer_id} registered");
+                }
+                relay::Event::CircuitReqAccepted { src_peer_id, dst_peer_id } => {
+                    println!("Relaying {src_peer_id} -> {dst_peer_id}");
+                }
+                relay::Event::ReservationTimedOut { src_peer_id } => {
+                    println!("Reservation for {src_peer_id} expired");
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Headless host for boba-go tables, no local player and no terminal UI.
+//!
+//! Usage: `boba_server [--room NAME]... [--config server.json] [--relay <multiaddr>]`
+//!
+//! Every `--room` opens a table with default rules. The config file can set
+//! listen addresses, passwords, invite lists, game settings and a WebSocket
+//! gateway for web players per room:
+//!
+//! ```json
+//! { "rooms": [{ "name": "Lunch", "listen": ["/ip4/0.0.0.0/tcp/4100"], "password": "tapioca",
+//!               "gateway": "0.0.0.0:4180",
+//!               "settings": { "round_count": 3, "preset": "FruitFestival", "variants": [], "seed": null } }] }
+//! ```
+
+use libp2p::futures::future::join_all;
+
+use boba_go::network::server::{run_room, RoomConfig, ServerConfig};
+
+fn parse_args() -> Result<ServerConfig, String> {
+    let mut config = ServerConfig::default();
+    let mut rooms = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or(format!("missing value for {arg}"))?;
+        match arg.as_str() {
+            "--room" => rooms.push(RoomConfig::named(&value)),
+            "--config" => {
+                let json = std::fs::read_to_string(&value).map_err(|e| format!("could not read {value}: {e}"))?;
+                let file = ServerConfig::from_json(&json)?;
+                config.relay = file.relay.or(config.relay);
+                config.rooms.extend(file.rooms);
+            }
+            "--relay" => config.relay = Some(value),
+            _ => return Err(format!("unknown argument: {arg}")),
+        }
+    }
+    config.rooms.extend(rooms);
+    if config.rooms.is_empty() {
+        config.rooms.push(RoomConfig::default());
+    }
+    Ok(config)
+}
+
+#[tokio::main]
+async fn main() {
+    let config = match parse_args() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{e}");
+            eprintln!("usage: boba_server [--room NAME]... [--config server.json] [--relay <multiaddr>]");
+            std::process::exit(2);
+        }
+    };
+
+    let relay = match config.relay.as_deref().map(str::parse).transpose() {
+        Ok(relay) => relay,
+        Err(e) => {
+            eprintln!("invalid relay address: {e}");
+            std::process::exit(2);
+        }
+    };
+
+    let rooms = config.rooms.into_iter().map(|room| {
+        let relay = relay.clone();
+        async move {
+            let name = room.name.clone();
+            if let Err(e) = run_room(room, relay).await {
+                eprintln!("[{name}] stopped: {e}");
+            }
+        }
+    });
+    join_all(rooms).await;
+}
@@ -1,7 +1,29 @@
-use boba_go::tui::{run_start_page, run_local_game, run_host_game, run_join_game, StartAction};
+//! Terminal client for boba-go.
+//!
+//! Usage: `boba_tui [--profile NAME]`
+
+use boba_go::network::identity;
+use boba_go::tui::{run_start_page, run_local_game, run_host_game, run_join_game, run_correspondence_game, StartAction};
+
+// `--profile <name>` runs with a separate identity, e.g. a second player on the same machine
+fn parse_args() -> Result<(), String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--profile" => identity::set_profile(&args.next().ok_or("missing value for --profile")?)?,
+            _ => return Err(format!("unknown argument: {arg}")),
+        }
+    }
+    Ok(())
+}
 
 #[tokio::main]
 async fn main() {
+    if let Err(e) = parse_args() {
+        eprintln!("{e}");
+        eprintln!("usage: boba_tui [--profile NAME]");
+        std::process::exit(2);
+    }
     match run_start_page() {
         StartAction::NewLocalGame => {
             if let Err(e) = run_local_game() {
@@ -18,6 +40,11 @@ async fn main() {
                 eprintln!("Network error: {}", e);
             }
         }
+        StartAction::CorrespondenceGame => {
+            if let Err(e) = run_correspondence_game() {
+                eprintln!("Game error: {}", e);
+            }
+        }
         StartAction::HowToPlay => {
             println!("How to play - Coming soon!");
         }
@@ -0,0 +1,42 @@
+//! External bots: any executable that speaks the line protocol in `protocol`
+//! over stdin/stdout can take a seat in a local, hosted or simulated game.
+
+pub mod protocol;
+mod process;
+#[cfg(feature = "network")]
+pub mod remote;
+pub mod simulate;
+
+pub use process::{pick_count, ExternalBot, Submission, DEFAULT_MOVE_TIMEOUT};
+#[cfg(feature = "network")]
+pub use remote::play_hosted_game;
+pub use simulate::play_game;
+
+/// Bots for the local game, e.g. `2=./my_bot --fast;3=python3 bot.py`
+pub const BOTS_ENV: &str = "BOBA_BOTS";
+
+/// Parse `seat=command` pairs separated by `;`, seats counted from 1 the way
+/// players are named. Returns zero-based seats with their commands.
+pub fn parse_bot_seats(spec: &str) -> Result<Vec<(usize, String)>, String> {
+    let mut seats: Vec<(usize, String)> = Vec::new();
+    for entry in spec.split(';').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let (seat, command) = entry.split_once('=').ok_or(format!("Expected seat=command, got {entry}"))?;
+        let seat: usize = seat.trim().parse().map_err(|_| format!("Bad seat in {entry}"))?;
+        if seat == 0 {
+            return Err("Seats are counted from 1".to_string());
+        }
+        if seats.iter().any(|(taken, _)| *taken == seat - 1) {
+            return Err(format!("Seat {seat} has two bots"));
+        }
+        seats.push((seat - 1, command.trim().to_string()));
+    }
+    Ok(seats)
+}
+
+/// Bot seats from `BOBA_BOTS`, none if unset
+pub fn bot_seats_from_env() -> Result<Vec<(usize, String)>, String> {
+    match std::env::var(BOTS_ENV) {
+        Ok(spec) => parse_bot_seats(&spec),
+        Err(_) => Ok(Vec::new()),
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use crate::engine::{CardKind, PlayerPublic};
+use crate::log;
+use crate::engine::GameInterface;
+use super::protocol::{BotLine, EngineLine, BOT_PROTOCOL_VERSION};
+
+/// How long a bot gets per reply unless told otherwise
+pub const DEFAULT_MOVE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What a seat submitted for one turn: selected cards and what is left in hand
+pub type Submission = (HashMap<CardKind, usize>, HashMap<CardKind, usize>);
+
+/// An executable playing one seat over stdin/stdout, see `protocol`
+pub struct ExternalBot {
+    command: String,
+    name: String,
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    timeout: Duration,
+}
+
+impl ExternalBot {
+    /// Start `command`, split on whitespace into program and arguments, and
+    /// wait for it to answer the handshake
+    pub fn spawn(command: &str, timeout: Duration) -> Result<Self, String> {
+        let mut words = command.split_whitespace();
+        let program = words.next().ok_or("Empty bot command")?;
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Could not start {command}: {e}"))?;
+
+        let stdin = child.stdin.take().ok_or("Bot has no stdin")?;
+        let stdout = child.stdout.take().ok_or("Bot has no stdout")?;
+        let stderr = child.stderr.take().ok_or("Bot has no stderr")?;
+
+        // replies come in on a thread so waiting for them can time out
+        let (sender, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        // anything on stderr is for whoever debugs the bot
+        let label = command.to_string();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                log::bot(format!("[{label}] {line}"));
+            }
+        });
+
+        let mut bot = Self {
+            command: command.to_string(),
+            name: program.rsplit(['/', '\\']).next().unwrap_or(program).to_string(),
+            child,
+            stdin,
+            lines,
+            timeout,
+        };
+        bot.handshake()?;
+        Ok(bot)
+    }
+
+    fn handshake(&mut self) -> Result<(), String> {
+        self.send(&EngineLine::Hello { version: BOT_PROTOCOL_VERSION })?;
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            match self.read(deadline)? {
+                Some(Ok(BotLine::Ready)) => return Ok(()),
+                Some(Ok(BotLine::Name(name))) => self.name = name,
+                Some(_) => {}
+                None => return Err(format!("{} did not answer the handshake in time", self.command)),
+            }
+        }
+    }
+
+    /// Name the bot gave itself, or its program name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    fn send(&mut self, line: &EngineLine) -> Result<(), String> {
+        writeln!(self.stdin, "{line}")
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| format!("{} stopped listening: {e}", self.name))
+    }
+
+    // next reply before `deadline`, None on timeout, lines that don't parse come back as errors
+    fn read(&mut self, deadline: Instant) -> Result<Option<Result<BotLine, String>>, String> {
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            let line = match self.lines.recv_timeout(left) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => return Ok(None),
+                Err(RecvTimeoutError::Disconnected) => return Err(format!("{} exited", self.name)),
+            };
+            match line.parse::<BotLine>() {
+                Ok(BotLine::Info(text)) => log::bot(format!("[{}] {text}", self.name)),
+                reply => return Ok(Some(reply)),
+            }
+        }
+    }
+
+    /// Tell the bot who is playing, `seat` being its own
+    pub fn start_game(&mut self, seat: usize, players: &[PlayerPublic], rounds: usize) -> Result<(), String> {
+        self.send(&EngineLine::NewGame { seat, players: players.len(), rounds })?;
+        for player in players {
+            self.send(&EngineLine::Player { id: player.id, name: player.name.clone() })?;
+        }
+        Ok(())
+    }
+
+    /// Final scores by seat
+    pub fn end_game(&mut self, scores: &[(usize, f32)]) -> Result<(), String> {
+        for (id, points) in scores {
+            self.send(&EngineLine::Score { id: *id, points: *points })?;
+        }
+        self.send(&EngineLine::GameOver)
+    }
+
+    /// Pass on why the game did not take the last pick
+    pub fn reject(&mut self, reason: &str) -> Result<(), String> {
+        self.send(&EngineLine::Illegal(reason.to_string()))
+    }
+
+    fn send_state(&mut self, game: &impl GameInterface) -> Result<(), String> {
+        let status = game.get_game_status();
+        self.send(&EngineLine::State { round: status.round, turn: status.turn })?;
+        self.send(&EngineLine::Hand(game.get_hand()))?;
+        for player in game.get_players_public() {
+            self.send(&EngineLine::Table { id: player.id, cards: player.public_cards })?;
+            self.send(&EngineLine::Boosted { id: player.id, cards: player.boosted_fruit_teas })?;
+        }
+        Ok(())
+    }
+
+    /// Ask for this turn's pick and submit it through `game`. A timeout or a
+    /// pick the game refuses is answered with the first cards in hand
+    /// instead, only a bot that is gone is an error.
+    pub fn play_turn(&mut self, game: &mut impl GameInterface) -> Result<Submission, String> {
+        // whatever a bot says after timing out belongs to a turn that is over
+        while self.lines.try_recv().is_ok() {}
+        self.send_state(game)?;
+        let mut tray_active = false;
+        loop {
+            let picks = pick_count(&game.get_hand(), tray_active);
+            self.send(&EngineLine::Go { picks, millis: self.timeout.as_millis() as u64 })?;
+
+            let rejection = match self.read(Instant::now() + self.timeout)? {
+                None => {
+                    self.send(&EngineLine::Timeout)?;
+                    None
+                }
+                Some(Err(e)) => Some(e),
+                Some(Ok(BotLine::DrinkTray)) if tray_active => Some("A Drink Tray is already active this turn".to_string()),
+                Some(Ok(BotLine::DrinkTray)) => match game.activate_drink_tray() {
+                    Ok(()) => {
+                        tray_active = true;
+                        self.send(&EngineLine::Hand(game.get_hand()))?;
+                        continue;
+                    }
+                    Err(e) => Some(e),
+                },
+                Some(Ok(BotLine::Pick(cards))) if cards.len() != picks => Some(format!("Pick exactly {picks} cards")),
+                Some(Ok(BotLine::Pick(cards))) => {
+                    let (selected, remaining) = submission(&game.get_hand(), &cards);
+                    match game.submit_turn(selected.clone(), remaining.clone()) {
+                        Ok(()) => return Ok((selected, remaining)),
+                        Err(e) => Some(e),
+                    }
+                }
+                Some(Ok(BotLine::Ready | BotLine::Name(_) | BotLine::Info(_))) => Some("Expected a pick".to_string()),
+            };
+            if let Some(reason) = rejection {
+                self.reject(&reason)?;
+            }
+            return self.play_fallback(game, picks);
+        }
+    }
+
+    /// Submit the first cards in hand on the bot's behalf
+    pub fn play_fallback(&mut self, game: &mut impl GameInterface, picks: usize) -> Result<Submission, String> {
+        let hand = game.get_hand();
+        let (selected, remaining) = submission(&hand, &first_cards(&hand, picks));
+        log::bot(format!("[{}] Picking {selected:?} instead", self.name));
+        game.submit_turn(selected.clone(), remaining.clone())?;
+        Ok((selected, remaining))
+    }
+}
+
+impl Drop for ExternalBot {
+    fn drop(&mut self) {
+        self.send(&EngineLine::Quit).ok();
+        // give it a moment to leave on its own
+        let deadline = Instant::now() + Duration::from_millis(200);
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
+
+/// Cards to pick this turn: one, or two with a Drink Tray active, never more than the hand holds
+pub fn pick_count(hand: &HashMap<CardKind, usize>, tray_active: bool) -> usize {
+    let held: usize = hand.values().sum();
+    held.min(if tray_active { 2 } else { 1 })
+}
+
+// the picked cards out of the hand, cards that aren't there stay in the
+// selection so that validating the submission catches them
+fn submission(hand: &HashMap<CardKind, usize>, cards: &[CardKind]) -> Submission {
+    let mut selected = HashMap::new();
+    let mut remaining = hand.clone();
+    for card in cards {
+        *selected.entry(*card).or_insert(0) += 1;
+        if let Some(count) = remaining.get_mut(card) {
+            *count = count.saturating_sub(1);
+        }
+    }
+    remaining.retain(|_, count| *count > 0);
+    (selected, remaining)
+}
+
+fn first_cards(hand: &HashMap<CardKind, usize>, picks: usize) -> Vec<CardKind> {
+    let mut cards: Vec<(CardKind, usize)> = hand.iter().map(|(card, count)| (*card, *count)).collect();
+    cards.sort();
+    cards
+        .into_iter()
+        .flat_map(|(card, count)| std::iter::repeat_n(card, count))
+        .take(picks)
+        .collect()
+}
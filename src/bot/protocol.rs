@@ -0,0 +1,125 @@
+//! Line protocol between the game and an external bot, in the spirit of UCI.
+//! Every message is one line of space separated words, cards are written as
+//! `Kind:count` using the names from `CardKind`'s Debug output.
+//!
+//! The game starts with `boba 1`. The bot may answer `name <text>`, then
+//! has to answer `ready`. Each game opens with `newgame <seat> <players>
+//! <rounds>` and one `player <id> <name>` per seat. Each turn the game sends
+//! `state <round> <turn>`, `hand <cards>`, `table <id> <cards>` and
+//! `boosted <id> <cards>` for every player, then `go <picks> <millis>`.
+//! The bot answers with `pick <Kind> ...`, naming exactly `picks` cards, or
+//! with `tray` to activate a Drink Tray first, after which it gets its new
+//! `hand` and another `go`. Replies that break the rules get `illegal
+//! <reason>`, slow ones `timeout`, and the game picks for the bot instead.
+//! The game ends with `score <id> <points>` per player and `gameover`,
+//! and `quit` asks the bot to exit. Lines from the bot starting with `info`
+//! are logged and otherwise ignored.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::engine::CardKind;
+
+pub const BOT_PROTOCOL_VERSION: u32 = 1;
+
+/// What the game sends to a bot
+#[derive(Debug, Clone, PartialEq)]
+pub enum EngineLine {
+    Hello { version: u32 },
+    NewGame { seat: usize, players: usize, rounds: usize },
+    Player { id: usize, name: String },
+    State { round: usize, turn: usize },
+    Hand(HashMap<CardKind, usize>),
+    Table { id: usize, cards: HashMap<CardKind, usize> },
+    Boosted { id: usize, cards: HashMap<CardKind, usize> },
+    Go { picks: usize, millis: u64 },
+    Illegal(String),
+    Timeout,
+    Score { id: usize, points: f32 },
+    GameOver,
+    Quit,
+}
+
+/// What a bot sends back
+#[derive(Debug, Clone, PartialEq)]
+pub enum BotLine {
+    Ready,
+    Name(String),
+    Pick(Vec<CardKind>),
+    DrinkTray,
+    Info(String),
+}
+
+/// Card name as used on the wire
+pub fn card_token(card: CardKind) -> String {
+    format!("{card:?}")
+}
+
+pub fn parse_card(token: &str) -> Option<CardKind> {
+    CardKind::ALL.iter().copied().find(|card| card_token(*card) == token)
+}
+
+/// Cards as `Kind:count` words, sorted so the same cards always read the same
+pub fn format_cards(cards: &HashMap<CardKind, usize>) -> String {
+    let mut cards: Vec<(CardKind, usize)> = cards.iter().filter(|(_, count)| **count > 0).map(|(k, c)| (*k, *c)).collect();
+    cards.sort();
+    cards
+        .iter()
+        .map(|(card, count)| format!("{}:{}", card_token(*card), count))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A `Kind:count` list the other way around, for bots written against this crate
+pub fn parse_cards(words: &str) -> Result<HashMap<CardKind, usize>, String> {
+    let mut cards = HashMap::new();
+    for word in words.split_whitespace() {
+        let (name, count) = word.split_once(':').ok_or(format!("Expected Kind:count, got {word}"))?;
+        let card = parse_card(name).ok_or(format!("Unknown card {name}"))?;
+        let count: usize = count.parse().map_err(|_| format!("Bad count in {word}"))?;
+        *cards.entry(card).or_insert(0) += count;
+    }
+    Ok(cards)
+}
+
+impl fmt::Display for EngineLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineLine::Hello { version } => write!(f, "boba {version}"),
+            EngineLine::NewGame { seat, players, rounds } => write!(f, "newgame {seat} {players} {rounds}"),
+            EngineLine::Player { id, name } => write!(f, "player {id} {name}"),
+            EngineLine::State { round, turn } => write!(f, "state {round} {turn}"),
+            EngineLine::Hand(cards) => write!(f, "hand {}", format_cards(cards)),
+            EngineLine::Table { id, cards } => write!(f, "table {id} {}", format_cards(cards)),
+            EngineLine::Boosted { id, cards } => write!(f, "boosted {id} {}", format_cards(cards)),
+            EngineLine::Go { picks, millis } => write!(f, "go {picks} {millis}"),
+            EngineLine::Illegal(reason) => write!(f, "illegal {reason}"),
+            EngineLine::Timeout => write!(f, "timeout"),
+            EngineLine::Score { id, points } => write!(f, "score {id} {points}"),
+            EngineLine::GameOver => write!(f, "gameover"),
+            EngineLine::Quit => write!(f, "quit"),
+        }
+    }
+}
+
+impl FromStr for BotLine {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        let (word, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match word {
+            "ready" => Ok(BotLine::Ready),
+            "name" if !rest.trim().is_empty() => Ok(BotLine::Name(rest.trim().to_string())),
+            "pick" => rest
+                .split_whitespace()
+                .map(|token| parse_card(token).ok_or(format!("Unknown card {token}")))
+                .collect::<Result<Vec<_>, _>>()
+                .map(BotLine::Pick),
+            "tray" => Ok(BotLine::DrinkTray),
+            "info" => Ok(BotLine::Info(rest.to_string())),
+            _ => Err(format!("Unknown reply: {line}")),
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::{HashMap, VecDeque};
+
+use libp2p::Swarm;
+
+use crate::network::{
+    lobby_to_game_client, BobaGoBehaviour, Client, ClientEvent, GameClientEvent, GameClientState, GameRequestKind,
+    LobbyClientState,
+};
+use crate::engine::{CardKind, GameInterface, GameStatus, PlayerPublic};
+use super::process::{pick_count, Submission};
+use super::ExternalBot;
+
+/// Seat `bot` in the hosted room at `address`: join, ready up and play one
+/// game. Returns the final scores as (seat, score).
+///
+/// The bot picks on a blocking thread from a copy of our seat while this
+/// task keeps the connection to the host going, the pick is sent once the
+/// bot has made it.
+pub async fn play_hosted_game(
+    mut bot: ExternalBot,
+    swarm: Swarm<BobaGoBehaviour>,
+    address: &str,
+    password: Option<String>,
+) -> Result<Vec<(usize, f32)>, String> {
+    let mut lobby = Client::<LobbyClientState>::with_swarm(swarm, bot.name().to_string()).map_err(|e| e.to_string())?;
+    let has_password = password.is_some();
+    if let Some(password) = password {
+        lobby.submit_password(password);
+    }
+    lobby.join(address).map_err(|e| e.to_string())?;
+
+    let (all_hands, players_public, game_status) = loop {
+        match lobby.next_event().await {
+            // ready goes back to false whenever the host changes the rules
+            Some(ClientEvent::JoinedLobby { .. } | ClientEvent::SettingsUpdated { .. }) => lobby.set_ready(true),
+            Some(ClientEvent::PasswordRequired { reason }) => {
+                return Err(match (reason, has_password) {
+                    (Some(reason), _) => reason,
+                    (None, true) => "Wrong password".to_string(),
+                    (None, false) => "This room needs a password".to_string(),
+                });
+            }
+            Some(ClientEvent::JoinRejected { reason } | ClientEvent::Kicked { reason }) => return Err(reason),
+            Some(ClientEvent::HostlessStarting { .. }) => {
+                return Err("Bots can only play hosted games, not hostless ones".to_string());
+            }
+            Some(ClientEvent::GameStarting { all_hands, players_public, game_status }) => {
+                break (all_hands, players_public, game_status);
+            }
+            Some(ClientEvent::Disconnected) | None => return Err("Lost connection to the host".to_string()),
+            Some(_) => {}
+        }
+    };
+
+    let seat = lobby.game_seat().ok_or("No seat in this game")?;
+    let hand = all_hands.get(seat).cloned().unwrap_or_default();
+    let rounds = game_status.round_count;
+    let mut game = lobby_to_game_client(lobby, seat, hand, players_public, game_status);
+    bot.start_game(seat, &game.get_players_public(), rounds)?;
+
+    // turn we last picked for, so a pick goes out once per turn
+    let mut picked: Option<(usize, usize)> = None;
+    // the host already turned our pick down once this turn
+    let mut fell_back = false;
+    // our hand with this turn's Drink Tray back in it, the host only sends
+    // it along with the next turn
+    let mut tray_hand: Option<HashMap<CardKind, usize>> = None;
+    // events that came in while the bot was thinking
+    let mut pending = VecDeque::new();
+    loop {
+        let status = game.get_game_status();
+        let turn = (status.round, status.turn);
+        if !status.is_game_over && picked != Some(turn) && !game.state().turn_submitted && !game.get_hand().is_empty() {
+            let (returned, seat) = think(bot, &mut game, &mut pending).await?;
+            bot = returned;
+            let (selected, remaining) = seat.submission.ok_or("The bot did not pick")?;
+            tray_hand = None;
+            if seat.tray_active {
+                game.activate_drink_tray()?;
+                tray_hand = Some(seat.hand);
+            }
+            GameInterface::submit_turn(&mut game, selected, remaining)?;
+            picked = Some(turn);
+            fell_back = false;
+        }
+
+        let event = match pending.pop_front() {
+            Some(event) => Some(event),
+            None => game.next_event().await,
+        };
+        match event {
+            Some(GameClientEvent::RequestRejected { request, reason }) => {
+                bot.reject(&reason)?;
+                if request == GameRequestKind::ActivateDrinkTray {
+                    tray_hand = None;
+                }
+                if request == GameRequestKind::SubmitTurn {
+                    if fell_back {
+                        return Err(format!("The host refused every pick: {reason}"));
+                    }
+                    let mut seat = SeatCopy::of(&game);
+                    if let Some(hand) = tray_hand.clone() {
+                        seat.hand = hand;
+                        seat.tray_active = true;
+                    }
+                    let picks = pick_count(&seat.hand, seat.tray_active);
+                    let (selected, remaining) = bot.play_fallback(&mut seat, picks)?;
+                    GameInterface::submit_turn(&mut game, selected, remaining)?;
+                    fell_back = true;
+                }
+            }
+            Some(GameClientEvent::GameEnded { final_scores, .. }) => {
+                let scores: Vec<(usize, f32)> = final_scores.iter().map(|(seat, score, _, _)| (*seat, *score)).collect();
+                bot.end_game(&scores)?;
+                return Ok(scores);
+            }
+            Some(GameClientEvent::Disconnected) | None => return Err("Lost connection to the host".to_string()),
+            Some(_) => {}
+        }
+    }
+}
+
+// have the bot pick on a blocking thread, keeping the swarm polled meanwhile
+// and holding on to whatever it brings in for the caller
+async fn think(
+    mut bot: ExternalBot,
+    game: &mut Client<GameClientState>,
+    pending: &mut VecDeque<GameClientEvent>,
+) -> Result<(ExternalBot, SeatCopy), String> {
+    let mut seat = SeatCopy::of(game);
+    let mut thinking = tokio::task::spawn_blocking(move || {
+        let result = bot.play_turn(&mut seat);
+        (bot, seat, result)
+    });
+    loop {
+        tokio::select! {
+            done = &mut thinking => {
+                let (bot, seat, result) = done.map_err(|e| format!("Bot thread failed: {e}"))?;
+                result?;
+                return Ok((bot, seat));
+            }
+            Some(event) = game.next_event() => pending.push_back(event),
+        }
+    }
+}
+
+// our seat as it was when the turn started, the bot plays against this and
+// what it did is sent to the host afterwards
+struct SeatCopy {
+    player_id: usize,
+    hand: HashMap<CardKind, usize>,
+    status: GameStatus,
+    players: Vec<PlayerPublic>,
+    tray_active: bool,
+    submission: Option<Submission>,
+}
+
+impl SeatCopy {
+    fn of(game: &Client<GameClientState>) -> Self {
+        Self {
+            player_id: game.get_player_id(),
+            hand: game.get_hand(),
+            status: game.get_game_status(),
+            players: game.get_players_public(),
+            tray_active: false,
+            submission: None,
+        }
+    }
+}
+
+impl GameInterface for SeatCopy {
+    fn get_hand(&self) -> HashMap<CardKind, usize> {
+        self.hand.clone()
+    }
+
+    fn get_game_status(&self) -> GameStatus {
+        self.status.clone()
+    }
+
+    fn get_players_public(&self) -> Vec<PlayerPublic> {
+        self.players.clone()
+    }
+
+    fn submit_turn(&mut self, selected: HashMap<CardKind, usize>, remaining: HashMap<CardKind, usize>) -> Result<(), String> {
+        self.submission = Some((selected, remaining));
+        Ok(())
+    }
+
+    fn get_player_id(&self) -> usize {
+        self.player_id
+    }
+
+    // the tray goes back into the hand the way the host will do it
+    fn activate_drink_tray(&mut self) -> Result<(), String> {
+        let Some(player) = self.players.iter_mut().find(|player| player.id == self.player_id) else {
+            return Err("No seat in this game".to_string());
+        };
+        let Some(count) = player.public_cards.get_mut(&CardKind::DrinkTray) else {
+            return Err("No DrinkTray in public cards".to_string());
+        };
+        *count -= 1;
+        if *count == 0 {
+            player.public_cards.remove(&CardKind::DrinkTray);
+        }
+        *self.hand.entry(CardKind::DrinkTray).or_insert(0) += 1;
+        self.tray_active = true;
+        Ok(())
+    }
+}
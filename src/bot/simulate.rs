@@ -0,0 +1,32 @@
+use crate::engine::{Game, GameConfig, GameError, GamePlayerView};
+use super::ExternalBot;
+
+/// Play one game with a bot in every seat, named after the bots.
+/// Returns each seat's final score.
+pub fn play_game(mut config: GameConfig, bots: &mut [ExternalBot]) -> Result<Vec<f32>, GameError> {
+    config.player_names = bots.iter().map(|bot| bot.name().to_string()).collect();
+    let mut game = Game::new(config)?;
+
+    let players = game.get_players_public();
+    for (seat, bot) in bots.iter_mut().enumerate() {
+        bot.start_game(seat, &players, game.round_count).map_err(GameError::Other)?;
+    }
+
+    while !game.is_game_over() {
+        let mut submissions = Vec::new();
+        for (seat, bot) in bots.iter_mut().enumerate() {
+            let mut view = GamePlayerView::new(&mut game, seat);
+            submissions.push(Some(bot.play_turn(&mut view).map_err(GameError::Other)?));
+        }
+        game.process_turn(submissions)?;
+    }
+
+    let scores = (0..bots.len())
+        .map(|seat| game.calculate_player_score(seat).map(|(total, _)| total))
+        .collect::<Result<Vec<f32>, _>>()?;
+    let by_seat: Vec<(usize, f32)> = scores.iter().copied().enumerate().collect();
+    for bot in bots.iter_mut() {
+        bot.end_game(&by_seat).map_err(GameError::Other)?;
+    }
+    Ok(scores)
+}
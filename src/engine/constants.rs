@@ -20,3 +20,26 @@ pub fn cards_per_player(num_players: usize) -> Option<usize> {
     cards_per_player_map().get(&num_players).copied()
 }
 
+
+/// Most players a game can be dealt for
+pub const MAX_PLAYERS: usize = {
+    let mut max = 0;
+    let mut i = 0;
+    while i < CARDS_PER_PLAYER_DATA.len() {
+        if CARDS_PER_PLAYER_DATA[i].0 > max {
+            max = CARDS_PER_PLAYER_DATA[i].0;
+        }
+        i += 1;
+    }
+    max
+};
+
+/// Most players a game can be dealt for
+pub fn max_players() -> usize {
+    MAX_PLAYERS
+}
+
+/// Fewest players a game can be dealt for
+pub fn min_players() -> usize {
+    CARDS_PER_PLAYER_DATA.iter().map(|(players, _)| *players).min().unwrap_or(0)
+}
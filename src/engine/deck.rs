@@ -1,25 +1,27 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use rand::Rng;
 use rand_chacha::ChaCha8Rng;
 
 use crate::engine::models::CardKind;
 
+// BTreeMap so that draws only depend on the rng seed, not on hash ordering
 pub struct Deck {
-    cards: HashMap<CardKind, usize>,
+    cards: BTreeMap<CardKind, usize>,
     total: usize,
-    initial_distribution: HashMap<CardKind, usize>,
+    initial_distribution: BTreeMap<CardKind, usize>,
 }
 
 impl Deck {
     pub fn new() -> Self {
         Deck {
-            cards: HashMap::new(),
+            cards: BTreeMap::new(),
             total: 0,
-            initial_distribution: HashMap::new(),
+            initial_distribution: BTreeMap::new(),
         }
     }
 
     pub fn with_cards(cards: HashMap<CardKind, usize>) -> Self {
+        let cards: BTreeMap<CardKind, usize> = cards.into_iter().collect();
         let total = cards.values().sum();
         let initial_distribution = cards.clone();
         Deck { cards, total, initial_distribution }
@@ -31,7 +33,7 @@ impl Deck {
     }
 
     pub fn set_initial_distribution(&mut self, distribution: HashMap<CardKind, usize>) {
-        self.initial_distribution = distribution;
+        self.initial_distribution = distribution.into_iter().collect();
     }
 
     fn reshuffle(&mut self) {
@@ -64,6 +66,11 @@ impl Deck {
         None
     }
 
+    /// Remaining cards per kind, in a stable order
+    pub fn counts(&self) -> impl Iterator<Item = (CardKind, usize)> + '_ {
+        self.cards.iter().map(|(kind, count)| (*kind, *count))
+    }
+
     pub fn size(&self) -> usize {
         self.total
     }
@@ -0,0 +1,255 @@
+//! Gym-style environment for training agents on the game. Every player picks
+//! through `step`, the turn resolves once everyone has picked, and rewards
+//! are each player's change in score since the last resolved turn.
+//!
+//! Actions are discrete: one index per card kind to pick a card of that
+//! kind, plus one to activate a Drink Tray. A Drink Tray means two picks
+//! that turn, one `step` each.
+
+use std::collections::HashMap;
+
+use crate::engine::constants;
+use crate::engine::models::{CardKind, GameSettings};
+use crate::engine::state::{Game, GameError, PassDirection};
+
+/// Number of card kinds, the size of every per-card block in observations
+pub const CARD_KINDS: usize = CardKind::ALL.len();
+
+/// Most seats an observation has room for
+pub const MAX_PLAYERS: usize = constants::MAX_PLAYERS;
+
+/// Picking one card per kind, then activating a Drink Tray
+pub const ACTION_COUNT: usize = CARD_KINDS + 1;
+
+/// Own hand, then public and boosted cards for every seat starting with our
+/// own, then round, turn, round count, pass direction left and right, picks
+/// left this turn and whether a Drink Tray is active
+pub const OBSERVATION_SIZE: usize = CARD_KINDS + MAX_PLAYERS * 2 * CARD_KINDS + 7;
+
+pub type Observation = [f32; OBSERVATION_SIZE];
+pub type ActionMask = [bool; ACTION_COUNT];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Pick(CardKind),
+    ActivateDrinkTray,
+}
+
+impl Action {
+    pub fn index(self) -> usize {
+        match self {
+            Action::Pick(card) => card.index(),
+            Action::ActivateDrinkTray => CARD_KINDS,
+        }
+    }
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        match index {
+            CARD_KINDS => Some(Action::ActivateDrinkTray),
+            _ => CardKind::ALL.get(index).map(|card| Action::Pick(*card)),
+        }
+    }
+}
+
+/// What one `step` did
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    /// Score change per player, all zero until the turn resolves
+    pub rewards: Vec<f32>,
+    pub turn_resolved: bool,
+    pub done: bool,
+}
+
+// one player's progress through the current turn
+#[derive(Debug, Clone, Default)]
+struct SeatTurn {
+    picked: HashMap<CardKind, usize>,
+    tray_active: bool,
+    submission: Option<(HashMap<CardKind, usize>, HashMap<CardKind, usize>)>,
+}
+
+impl SeatTurn {
+    fn picked_count(&self) -> usize {
+        self.picked.values().sum()
+    }
+}
+
+pub struct Env {
+    settings: GameSettings,
+    players: usize,
+    game: Game,
+    seats: Vec<SeatTurn>,
+    scores: Vec<f32>,
+}
+
+impl Env {
+    /// A game for `players` seats with these settings, dealt with the settings' seed
+    pub fn new(players: usize, settings: GameSettings) -> Result<Self, GameError> {
+        if players > MAX_PLAYERS {
+            return Err(GameError::InvalidConfig);
+        }
+        let game = Self::deal(players, &settings)?;
+        Ok(Self {
+            settings,
+            players,
+            game,
+            seats: vec![SeatTurn::default(); players],
+            scores: vec![0.0; players],
+        })
+    }
+
+    fn deal(players: usize, settings: &GameSettings) -> Result<Game, GameError> {
+        let names = (0..players).map(|seat| format!("Player {}", seat + 1)).collect();
+        let mut game = Game::new(settings.to_config(names))?;
+        // nothing replays these games, copying every turn only slows self-play down
+        game.set_keep_history(false);
+        Ok(game)
+    }
+
+    /// Start over with a fresh deal from `seed`
+    pub fn reset(&mut self, seed: u64) -> Result<(), GameError> {
+        self.settings.seed = Some(seed);
+        self.game = Self::deal(self.players, &self.settings)?;
+        self.seats = vec![SeatTurn::default(); self.players];
+        self.scores = vec![0.0; self.players];
+        Ok(())
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn num_players(&self) -> usize {
+        self.players
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.game.is_game_over()
+    }
+
+    /// Scores as of the last resolved turn
+    pub fn scores(&self) -> &[f32] {
+        &self.scores
+    }
+
+    /// Whether `player` still has to act before the turn resolves
+    pub fn needs_action(&self, player: usize) -> bool {
+        !self.is_done() && self.seats.get(player).is_some_and(|seat| seat.submission.is_none())
+    }
+
+    fn picks_needed(&self, player: usize) -> usize {
+        let seat = &self.seats[player];
+        let held: usize = self.game.players[player].hand.values().sum();
+        held.min(if seat.tray_active { 2 } else { 1 })
+    }
+
+    // what is still in hand once this turn's picks are taken out
+    fn held(&self, player: usize, card: CardKind) -> usize {
+        let in_hand = self.game.players[player].hand.get(&card).copied().unwrap_or(0);
+        in_hand - self.seats[player].picked.get(&card).copied().unwrap_or(0)
+    }
+
+    /// Which actions `player` may take right now
+    pub fn legal_actions(&self, player: usize) -> ActionMask {
+        let mut mask = [false; ACTION_COUNT];
+        if !self.needs_action(player) {
+            return mask;
+        }
+        for card in CardKind::ALL {
+            mask[card.index()] = self.held(player, *card) > 0;
+        }
+        let seat = &self.seats[player];
+        // only before picking, the way the game UI allows it
+        mask[CARD_KINDS] = !seat.tray_active
+            && seat.picked.is_empty()
+            && self.game.players[player].public_cards.get(&CardKind::DrinkTray).is_some_and(|count| *count > 0);
+        mask
+    }
+
+    /// Fixed-size encoding of everything `player` can see, None for a seat
+    /// that isn't in this game
+    pub fn observation(&self, player: usize) -> Option<Observation> {
+        let seat = self.seats.get(player)?;
+        let mut observation = [0.0; OBSERVATION_SIZE];
+        for card in CardKind::ALL {
+            observation[card.index()] = self.held(player, *card) as f32;
+        }
+
+        for offset in 0..self.players {
+            let other = &self.game.players[(player + offset) % self.players];
+            let base = CARD_KINDS + offset * 2 * CARD_KINDS;
+            for (card, count) in &other.public_cards {
+                observation[base + card.index()] = *count as f32;
+            }
+            for (card, count) in &other.boosted_fruit_teas {
+                observation[base + CARD_KINDS + card.index()] = *count as f32;
+            }
+        }
+
+        let status = &mut observation[CARD_KINDS + MAX_PLAYERS * 2 * CARD_KINDS..];
+        let direction = self.game.get_current_pass_direction();
+        status[0] = self.game.round as f32;
+        status[1] = self.game.turn as f32;
+        status[2] = self.game.round_count as f32;
+        status[3] = (direction == PassDirection::Left) as u8 as f32;
+        status[4] = (direction == PassDirection::Right) as u8 as f32;
+        status[5] = if self.needs_action(player) { (self.picks_needed(player) - seat.picked_count()) as f32 } else { 0.0 };
+        status[6] = seat.tray_active as u8 as f32;
+        Some(observation)
+    }
+
+    /// Take one action for `player`. Illegal actions are errors and change nothing.
+    pub fn step(&mut self, player: usize, action: Action) -> Result<Step, GameError> {
+        if !self.legal_actions(player).get(action.index()).copied().unwrap_or(false) {
+            return Err(GameError::Other(format!("{action:?} is not legal for player {player} now")));
+        }
+
+        match action {
+            Action::ActivateDrinkTray => {
+                self.game.activate_drink_tray(player)?;
+                self.seats[player].tray_active = true;
+            }
+            Action::Pick(card) => {
+                *self.seats[player].picked.entry(card).or_insert(0) += 1;
+                if self.seats[player].picked_count() == self.picks_needed(player) {
+                    self.submit(player)?;
+                }
+            }
+        }
+
+        if self.seats.iter().any(|seat| seat.submission.is_none()) {
+            return Ok(Step { rewards: vec![0.0; self.players], turn_resolved: false, done: false });
+        }
+        self.resolve_turn()
+    }
+
+    fn submit(&mut self, player: usize) -> Result<(), GameError> {
+        let selected = std::mem::take(&mut self.seats[player].picked);
+        let mut remaining = self.game.players[player].hand.clone();
+        for (card, count) in &selected {
+            if let Some(held) = remaining.get_mut(card) {
+                *held -= count;
+            }
+        }
+        remaining.retain(|_, count| *count > 0);
+
+        self.game.validate_hand_submission(player, &selected, &remaining)?;
+        self.game.mark_player_selected(player)?;
+        self.seats[player].submission = Some((selected, remaining));
+        Ok(())
+    }
+
+    fn resolve_turn(&mut self) -> Result<Step, GameError> {
+        let submissions = self.seats.iter_mut().map(|seat| seat.submission.take()).collect();
+        self.game.process_turn(submissions)?;
+        self.seats = vec![SeatTurn::default(); self.players];
+
+        let mut rewards = Vec::with_capacity(self.players);
+        for player in 0..self.players {
+            let (score, _) = self.game.calculate_player_score(player)?;
+            rewards.push(score - self.scores[player]);
+            self.scores[player] = score;
+        }
+        Ok(Step { rewards, turn_resolved: true, done: self.game.is_game_over() })
+    }
+}
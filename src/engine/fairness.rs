@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::engine::models::{CardKind, GameConfig};
+use crate::engine::replay::{replay_game, TurnRecord};
+use crate::engine::state::Game;
+
+/// random bytes contributed by each participant towards the game seed
+pub type Entropy = [u8; 32];
+
+/// sha256 commitment published before the value it hides is revealed
+pub type Commitment = [u8; 32];
+
+pub fn random_entropy() -> Entropy {
+    rand::random()
+}
+
+/// Commitment to the host's own entropy, published in the lobby
+pub fn commit_entropy(entropy: &Entropy) -> Commitment {
+    let mut hasher = Sha256::new();
+    hasher.update(b"boba-go/entropy");
+    hasher.update(entropy);
+    hasher.finalize().into()
+}
+
+/// Combine the host entropy with every player's contribution into a game seed.
+/// Contributions are hashed in player order so the result does not depend on join timing.
+pub fn combine_seed(host_entropy: &Entropy, contributions: &[(usize, Entropy)]) -> u64 {
+    let mut sorted = contributions.to_vec();
+    sorted.sort_by_key(|(player_id, _)| *player_id);
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"boba-go/seed");
+    hasher.update(host_entropy);
+    for (player_id, entropy) in &sorted {
+        hasher.update((*player_id as u64).to_le_bytes());
+        hasher.update(entropy);
+    }
+    let digest = hasher.finalize();
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    u64::from_le_bytes(bytes)
+}
+
+/// Commitment to the combined seed, published before any cards are dealt
+pub fn commit_seed(seed: u64, host_entropy: &Entropy) -> Commitment {
+    let mut hasher = Sha256::new();
+    hasher.update(b"boba-go/seed-commitment");
+    hasher.update(seed.to_le_bytes());
+    hasher.update(host_entropy);
+    hasher.finalize().into()
+}
+
+/// Everything the host reveals at game end so players can re-run the game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FairnessReveal {
+    pub host_entropy: Entropy,
+    pub contributions: Vec<(usize, Entropy)>,
+    pub seed: u64,
+    pub player_names: Vec<String>,
+    pub round_count: usize,
+    pub card_distribution: Option<HashMap<CardKind, usize>>,
+    pub turns: Vec<TurnRecord>,
+}
+
+impl FairnessReveal {
+    pub fn from_game(game: &Game, host_entropy: Entropy, contributions: Vec<(usize, Entropy)>) -> Self {
+        let config = game.config();
+        Self {
+            host_entropy,
+            contributions,
+            seed: game.seed,
+            player_names: config.player_names.clone(),
+            round_count: config.round_count,
+            card_distribution: config.card_distribution.clone(),
+            turns: game.history().to_vec(),
+        }
+    }
+}
+
+/// What a single player saw during the game
+#[derive(Debug, Clone)]
+pub struct FairnessObservations {
+    pub player_id: usize,
+    pub own_entropy: Entropy,
+    pub entropy_commitment: Option<Commitment>,
+    pub seed_commitment: Option<Commitment>,
+    // first hand seen for each (round, turn)
+    pub hands: Vec<(usize, usize, HashMap<CardKind, usize>)>,
+}
+
+impl FairnessObservations {
+    pub fn new(player_id: usize, own_entropy: Entropy, entropy_commitment: Option<Commitment>) -> Self {
+        Self {
+            player_id,
+            own_entropy,
+            entropy_commitment,
+            seed_commitment: None,
+            hands: Vec::new(),
+        }
+    }
+
+    /// Remember the hand for a turn, later updates within the same turn are ignored
+    pub fn record_hand(&mut self, round: usize, turn: usize, hand: &HashMap<CardKind, usize>) {
+        if !self.hands.iter().any(|(r, t, _)| *r == round && *t == turn) {
+            self.hands.push((round, turn, hand.clone()));
+        }
+    }
+}
+
+/// Result of checking a reveal, empty mismatches means the deal was fair
+#[derive(Debug, Clone, Default)]
+pub struct FairnessReport {
+    pub mismatches: Vec<String>,
+}
+
+impl FairnessReport {
+    pub fn is_fair(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// Check the host's reveal against the commitments and the hands this player was dealt
+pub fn verify_reveal(reveal: &FairnessReveal, observed: &FairnessObservations) -> FairnessReport {
+    let mut mismatches = Vec::new();
+
+    match observed.entropy_commitment {
+        Some(commitment) if commit_entropy(&reveal.host_entropy) != commitment => {
+            mismatches.push("Host entropy does not match the lobby commitment".to_string());
+        }
+        None => mismatches.push("Host never committed to its entropy".to_string()),
+        _ => {}
+    }
+
+    let own_contribution = reveal.contributions
+        .iter()
+        .find(|(player_id, _)| *player_id == observed.player_id);
+    if own_contribution.map(|(_, entropy)| entropy) != Some(&observed.own_entropy) {
+        mismatches.push("Your entropy was not included in the seed".to_string());
+    }
+
+    if combine_seed(&reveal.host_entropy, &reveal.contributions) != reveal.seed {
+        mismatches.push("Seed is not derived from the revealed entropy".to_string());
+    }
+
+    match observed.seed_commitment {
+        Some(commitment) if commit_seed(reveal.seed, &reveal.host_entropy) != commitment => {
+            mismatches.push("Seed does not match the commitment made before dealing".to_string());
+        }
+        None => mismatches.push("Host never committed to the seed".to_string()),
+        _ => {}
+    }
+
+    let config = GameConfig {
+        player_names: reveal.player_names.clone(),
+        seed: Some(reveal.seed),
+        round_count: reveal.round_count,
+        card_distribution: reveal.card_distribution.clone(),
+    };
+
+    // hand at the start of each replayed turn, for this player only
+    let mut replayed_hands: Vec<(usize, usize, HashMap<CardKind, usize>)> = Vec::new();
+    let replay = replay_game(config, &reveal.turns, |game| {
+        if let Ok(hand) = game.get_player_hand(observed.player_id) {
+            replayed_hands.push((game.round, game.turn, hand.clone()));
+        }
+    });
+
+    if let Err(e) = replay {
+        mismatches.push(format!("Replay failed: {}", e));
+        return FairnessReport { mismatches };
+    }
+
+    for (index, (round, turn, expected)) in replayed_hands.iter().enumerate() {
+        let Some((_, _, seen)) = observed.hands.iter().find(|(r, t, _)| r == round && t == turn) else {
+            continue;
+        };
+
+        // the first update of a turn may already include our own Drink Tray activation
+        let activated = reveal.turns[index].drink_tray_activations.contains(&observed.player_id);
+        let mut with_tray = expected.clone();
+        *with_tray.entry(CardKind::DrinkTray).or_insert(0) += 1;
+
+        if seen != expected && !(activated && *seen == with_tray) {
+            mismatches.push(format!("Round {} turn {}: your hand differs from the replayed deal", round, turn));
+        }
+    }
+
+    FairnessReport { mismatches }
+}
@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+
+use crate::engine::models::{CardKind, PlayerPublic};
+use crate::engine::state::{GamePlayerView, GameStatus};
+
+/// One seat's view of a game, whatever runs it: a local `Game`, a network
+/// host or client, or a correspondence file. Frontends and bots only talk to this.
+pub trait GameInterface {
+    fn get_hand(&self) -> HashMap<CardKind, usize>;
+    fn get_game_status(&self) -> GameStatus;
+    fn get_players_public(&self) -> Vec<PlayerPublic>;
+    fn submit_turn(&mut self, selected: HashMap<CardKind, usize>, remaining: HashMap<CardKind, usize>) -> Result<(), String>;
+    fn get_player_id(&self) -> usize;
+    fn activate_drink_tray(&mut self) -> Result<(), String>;
+}
+
+impl<'a> GameInterface for GamePlayerView<'a> {
+    fn get_hand(&self) -> HashMap<CardKind, usize> {
+        self.game.get_player_hand(self.player_id).cloned().unwrap_or_default()
+    }
+
+    fn get_game_status(&self) -> GameStatus {
+        self.game.get_game_status()
+    }
+
+    fn get_players_public(&self) -> Vec<PlayerPublic> {
+        self.game.get_players_public()
+    }
+
+    fn submit_turn(&mut self, selected: HashMap<CardKind, usize>, remaining: HashMap<CardKind, usize>) -> Result<(), String> {
+        self.game.validate_hand_submission(self.player_id, &selected, &remaining)
+            .map_err(|e| format!("{:?}", e))?;
+        self.game.mark_player_selected(self.player_id)
+            .map_err(|e| format!("{:?}", e))?;
+
+        // Update the player's hand with the remaining cards
+        if let Some(player) = self.game.players.get_mut(self.player_id) {
+            player.hand = remaining;
+        }
+
+        Ok(())
+    }
+
+    fn get_player_id(&self) -> usize {
+        self.player_id
+    }
+
+    fn activate_drink_tray(&mut self) -> Result<(), String> {
+        self.game.activate_drink_tray(self.player_id)
+            .map_err(|_| "No DrinkTray in public cards".to_string())
+    }
+}
@@ -1,10 +1,16 @@
 pub mod constants;
 pub mod deck;
+pub mod env;
+pub mod fairness;
+pub mod interface;
 pub mod models;
+pub mod replay;
 pub mod scoring;
 pub mod state;
 
-pub use models::{Card, CardKind, GameConfig, OnDraftActionFn, Player, PlayerPublic};
+pub use models::{Card, CardKind, DistributionPreset, GameConfig, GameSettings, GameVariant, OnDraftActionFn, Player, PlayerPublic};
 pub use state::{Game, GameError, GameStatus, PassDirection, PlayerTurnState, GamePlayerView};
 pub use scoring::{ScoreBreakdown, CategoryScore, SetBonus};
 pub use deck::Deck;
+pub use interface::GameInterface;
+pub use replay::{TurnRecord, TurnSubmission, replay_game};
@@ -25,15 +25,24 @@ pub use tapioca_pearl::TapiocaPearl;
 pub use thai_tea::ThaiTea;
 
 use std::fmt;
+use sha2::{Digest, Sha256};
 
 macro_rules! define_card_kind {
     ($($variant:ident => $card:ty),* $(,)?) => {
-        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
         pub enum CardKind {
             $($variant,)*
         }
 
         impl CardKind {
+            /// Every card kind, in declaration order
+            pub const ALL: &'static [CardKind] = &[$(Self::$variant,)*];
+
+            /// Position in `ALL`, for anything laid out per card kind
+            pub fn index(&self) -> usize {
+                Self::ALL.iter().position(|kind| kind == self).unwrap_or(0)
+            }
+
             pub fn name(&self) -> &'static str {
                 match self {
                     $(Self::$variant => <$card>::NAME,)*
@@ -98,3 +107,33 @@ define_card_kind! {
     PassionFruitTea => PassionFruitTea,
     DrinkTray => DrinkTray,
 }
+
+/// Fingerprint of the card rules and default deck, used to make sure
+/// networked players agree on what every card does.
+pub fn card_set_hash() -> String {
+    let mut hasher = Sha256::new();
+    for kind in CardKind::ALL {
+        hasher.update(format!(
+            "{:?}|{}|{}|{}|{}|{}|{};",
+            kind,
+            kind.name(),
+            kind.description(),
+            kind.score(),
+            kind.playable(),
+            kind.is_fruit_tea(),
+            kind.on_draft().is_some(),
+        ));
+    }
+
+    let mut distribution: Vec<(CardKind, usize)> = crate::engine::GameConfig::default()
+        .card_distribution
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+    distribution.sort_by_key(|(kind, _)| CardKind::ALL.iter().position(|k| k == kind));
+    for (kind, count) in distribution {
+        hasher.update(format!("{:?}={};", kind, count));
+    }
+
+    hasher.finalize()[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -1,4 +1,8 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
 use super::cards::CardKind;
 
 #[derive(Debug, Clone)]
@@ -11,26 +15,189 @@ pub struct GameConfig {
 
 impl Default for GameConfig {
     fn default() -> Self {
-        use CardKind::*;
-        let mut default_distribution = HashMap::new();
-        default_distribution.insert(TapiocaPearl, 14);
-        default_distribution.insert(BrownSugarMilkTea, 14);
-        default_distribution.insert(ThaiTea, 12);
-        default_distribution.insert(MochiIceCream, 8);
-        default_distribution.insert(Matcha, 10);
-        default_distribution.insert(MysteryTea, 6);
-        default_distribution.insert(PoppingBubbles, 10);
-        default_distribution.insert(MangoTea, 10);
-        default_distribution.insert(LycheeTea, 10);
-        default_distribution.insert(PassionFruitTea, 10);
-        default_distribution.insert(DrinkTray, 10);
-        
         Self {
             player_names: Vec::new(),
             seed: None,
             round_count: 3,
-            card_distribution: Some(default_distribution),
+            card_distribution: Some(DistributionPreset::Standard.distribution()),
+        }
+    }
+}
+
+/// Named deck mixes the host can pick from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DistributionPreset {
+    #[default]
+    Standard,
+    FruitFestival,
+    MilkTeaShop,
+}
+
+impl DistributionPreset {
+    pub const ALL: &'static [DistributionPreset] = &[
+        DistributionPreset::Standard,
+        DistributionPreset::FruitFestival,
+        DistributionPreset::MilkTeaShop,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DistributionPreset::Standard => "Standard",
+            DistributionPreset::FruitFestival => "Fruit Festival",
+            DistributionPreset::MilkTeaShop => "Milk Tea Shop",
         }
     }
+
+    pub fn distribution(&self) -> HashMap<CardKind, usize> {
+        use CardKind::*;
+        let counts: [(CardKind, usize); 11] = match self {
+            DistributionPreset::Standard => [
+                (TapiocaPearl, 14),
+                (BrownSugarMilkTea, 14),
+                (ThaiTea, 12),
+                (MochiIceCream, 8),
+                (Matcha, 10),
+                (MysteryTea, 6),
+                (PoppingBubbles, 10),
+                (MangoTea, 10),
+                (LycheeTea, 10),
+                (PassionFruitTea, 10),
+                (DrinkTray, 10),
+            ],
+            DistributionPreset::FruitFestival => [
+                (TapiocaPearl, 10),
+                (BrownSugarMilkTea, 8),
+                (ThaiTea, 8),
+                (MochiIceCream, 6),
+                (Matcha, 8),
+                (MysteryTea, 6),
+                (PoppingBubbles, 14),
+                (MangoTea, 16),
+                (LycheeTea, 16),
+                (PassionFruitTea, 16),
+                (DrinkTray, 8),
+            ],
+            DistributionPreset::MilkTeaShop => [
+                (TapiocaPearl, 18),
+                (BrownSugarMilkTea, 18),
+                (ThaiTea, 16),
+                (MochiIceCream, 10),
+                (Matcha, 12),
+                (MysteryTea, 6),
+                (PoppingBubbles, 6),
+                (MangoTea, 6),
+                (LycheeTea, 6),
+                (PassionFruitTea, 6),
+                (DrinkTray, 10),
+            ],
+        };
+        counts.into_iter().collect()
+    }
+
+    /// The preset after this one, wrapping around
+    pub fn next(&self) -> DistributionPreset {
+        let index = Self::ALL.iter().position(|p| p == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+impl fmt::Display for DistributionPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Optional rule tweaks applied on top of the deck preset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum GameVariant {
+    /// Drink Trays are taken out of the deck
+    NoDrinkTrays,
+    /// Mystery Teas are taken out of the deck
+    NoMysteryTea,
+}
+
+impl GameVariant {
+    pub const ALL: &'static [GameVariant] = &[GameVariant::NoDrinkTrays, GameVariant::NoMysteryTea];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameVariant::NoDrinkTrays => "No Drink Trays",
+            GameVariant::NoMysteryTea => "No Mystery Tea",
+        }
+    }
+
+    fn apply(&self, distribution: &mut HashMap<CardKind, usize>) {
+        match self {
+            GameVariant::NoDrinkTrays => distribution.remove(&CardKind::DrinkTray),
+            GameVariant::NoMysteryTea => distribution.remove(&CardKind::MysteryTea),
+        };
+    }
 }
 
+/// Rules picked by the host before a game starts, turned into a
+/// `GameConfig` once the players are known
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameSettings {
+    pub round_count: usize,
+    pub preset: DistributionPreset,
+    pub variants: BTreeSet<GameVariant>,
+    pub seed: Option<u64>,
+}
+
+impl GameSettings {
+    pub const MIN_ROUNDS: usize = 1;
+    pub const MAX_ROUNDS: usize = 5;
+
+    pub fn has_variant(&self, variant: GameVariant) -> bool {
+        self.variants.contains(&variant)
+    }
+
+    pub fn toggle_variant(&mut self, variant: GameVariant) {
+        if !self.variants.remove(&variant) {
+            self.variants.insert(variant);
+        }
+    }
+
+    /// Deck counts after applying the preset and every variant
+    pub fn card_distribution(&self) -> HashMap<CardKind, usize> {
+        let mut distribution = self.preset.distribution();
+        for variant in &self.variants {
+            variant.apply(&mut distribution);
+        }
+        distribution
+    }
+
+    pub fn to_config(&self, player_names: Vec<String>) -> GameConfig {
+        GameConfig {
+            player_names,
+            seed: self.seed,
+            round_count: self.round_count.clamp(Self::MIN_ROUNDS, Self::MAX_ROUNDS),
+            card_distribution: Some(self.card_distribution()),
+        }
+    }
+
+    /// One line description for lobby screens
+    pub fn summary(&self) -> String {
+        let mut parts = vec![
+            format!("{} rounds", self.round_count),
+            self.preset.name().to_string(),
+        ];
+        parts.extend(self.variants.iter().map(|v| v.name().to_string()));
+        if let Some(seed) = self.seed {
+            parts.push(format!("seed {seed}"));
+        }
+        parts.join(", ")
+    }
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            round_count: GameConfig::default().round_count,
+            preset: DistributionPreset::default(),
+            variants: BTreeSet::new(),
+            seed: None,
+        }
+    }
+}
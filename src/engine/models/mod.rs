@@ -2,7 +2,7 @@ pub mod cards;
 pub mod config;
 pub mod player;
 
-pub use cards::{Card, CardKind, OnDraftActionFn};
-pub use config::GameConfig;
+pub use cards::{Card, CardKind, OnDraftActionFn, card_set_hash};
+pub use config::{DistributionPreset, GameConfig, GameSettings, GameVariant};
 pub use player::{Player, PlayerPublic};
 
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::models::{CardKind, GameConfig};
+use crate::engine::state::{Game, GameError};
+
+/// (selected cards, remaining hand) for one player, None if they did not submit
+pub type TurnSubmission = Option<(HashMap<CardKind, usize>, HashMap<CardKind, usize>)>;
+
+/// Everything that changed a game during one turn, in the order it happened.
+/// Together with the config this is enough to replay a whole game.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TurnRecord {
+    pub drink_tray_activations: Vec<usize>,
+    pub submissions: Vec<TurnSubmission>,
+}
+
+/// Re-run a game from its config and turn history.
+/// `on_turn_start` is called with the game state at the start of every recorded turn.
+pub fn replay_game<F: FnMut(&Game)>(
+    config: GameConfig,
+    turns: &[TurnRecord],
+    mut on_turn_start: F,
+) -> Result<Game, GameError> {
+    if config.seed.is_none() {
+        return Err(GameError::Other("Cannot replay a game without its seed".to_string()));
+    }
+
+    let mut game = Game::new(config)?;
+
+    for (index, turn) in turns.iter().enumerate() {
+        on_turn_start(&game);
+
+        for player_id in &turn.drink_tray_activations {
+            game.activate_drink_tray(*player_id).map_err(|_| {
+                GameError::Other(format!("Turn {}: player {} had no Drink Tray to activate", index + 1, player_id))
+            })?;
+        }
+
+        for (player_id, submission) in turn.submissions.iter().enumerate() {
+            if let Some((selected, remaining)) = submission {
+                game.validate_hand_submission(player_id, selected, remaining).map_err(|_| {
+                    GameError::Other(format!("Turn {}: player {} picked cards they did not hold or the wrong number of them", index + 1, player_id))
+                })?;
+            }
+            game.mark_player_selected(player_id)?;
+        }
+
+        game.process_turn(turn.submissions.clone())?;
+    }
+
+    Ok(game)
+}
@@ -6,6 +6,7 @@ use std::collections::HashMap;
 use crate::engine::constants;
 use crate::engine::deck::Deck;
 use crate::engine::models::{CardKind, GameConfig, Player, PlayerPublic};
+use crate::engine::replay::TurnRecord;
 use crate::engine::scoring::ScoreBreakdown;
 
 #[derive(Debug, Error)]
@@ -50,6 +51,10 @@ pub struct Game {
     pub turn: usize,
     pub player_turn_states: Vec<PlayerTurnState>,
     pub round_count: usize,
+    config: GameConfig,
+    history: Vec<TurnRecord>,
+    keep_history: bool,
+    pending_drink_trays: Vec<usize>,
 }
 
 impl Game {
@@ -74,6 +79,12 @@ impl Game {
         
         // Extract card distribution before moving config
         let card_distribution = config.card_distribution.clone();
+
+        // keep the resolved config around so the game can be replayed later
+        let recorded_config = GameConfig {
+            seed: Some(seed),
+            ..config.clone()
+        };
         
         let players: Vec<Player> = config.player_names
             .into_iter()
@@ -98,6 +109,10 @@ impl Game {
             turn: 1,
             player_turn_states,
             round_count: config.round_count,
+            config: recorded_config,
+            history: Vec::new(),
+            keep_history: true,
+            pending_drink_trays: Vec::new(),
         };
 
         game.build_deck(card_distribution);
@@ -176,6 +191,14 @@ impl Game {
             return Err(GameError::InvalidConfig);
         }
 
+        // one card a turn, two with a Drink Tray out, never more than the hand holds
+        let picks: usize = selected_cards.values().sum();
+        let allowed = if self.pending_drink_trays.contains(&player_id) { 2 } else { 1 };
+        let allowed = allowed.min(current_hand.values().sum());
+        if picks != allowed {
+            return Err(GameError::Other(format!("Pick exactly {allowed} cards, not {picks}")));
+        }
+
         Ok(())
     }
     
@@ -211,6 +234,11 @@ impl Game {
             return Err(GameError::InvalidConfig);
         }
 
+        let drink_tray_activations = std::mem::take(&mut self.pending_drink_trays);
+        if self.keep_history {
+            self.history.push(TurnRecord { drink_tray_activations, submissions: submissions.clone() });
+        }
+
         // move selected cards to public_cards and track which cards have on_draft
         let mut cards_with_on_draft: Vec<Option<CardKind>> = vec![None; self.players.len()];
         
@@ -218,8 +246,13 @@ impl Game {
             if let Some((selected_cards, remaining_hand)) = submission_opt {
                 let player = &mut self.players[player_id];
 
+                // Sorted so that Popping Bubbles pairing does not depend on hash order,
+                // replays have to end up with exactly the same public cards
+                let mut selected: Vec<(&CardKind, &usize)> = selected_cards.iter().collect();
+                selected.sort();
+
                 // Add selected cards to public_cards and handle Popping Bubbles pairing
-                for (kind, count) in selected_cards {
+                for (kind, count) in selected {
                     // If this is a fruit tea, check if there are available Popping Bubbles
                     if kind.is_fruit_tea() {
                         // Check current count of Popping Bubbles in public_cards (decreases as we pair them)
@@ -334,12 +367,77 @@ impl Game {
                 player.public_cards.remove(&CardKind::DrinkTray);
             }
             *player.hand.entry(CardKind::DrinkTray).or_insert(0) += 1;
+            self.pending_drink_trays.push(player_id);
             Ok(())
         } else {
             Err(GameError::InvalidConfig)
         }
     }
 
+    /// Undo a drink tray activation made earlier in the current turn
+    pub fn undo_drink_tray(&mut self, player_id: usize) -> Result<(), GameError> {
+        let position = self.pending_drink_trays
+            .iter()
+            .rposition(|id| *id == player_id)
+            .ok_or(GameError::InvalidConfig)?;
+
+        let player = &mut self.players[player_id];
+        match player.hand.get_mut(&CardKind::DrinkTray) {
+            Some(count) => {
+                *count -= 1;
+                if *count == 0 {
+                    player.hand.remove(&CardKind::DrinkTray);
+                }
+            }
+            None => return Err(GameError::InvalidConfig),
+        }
+        *player.public_cards.entry(CardKind::DrinkTray).or_insert(0) += 1;
+        self.pending_drink_trays.remove(position);
+        Ok(())
+    }
+
+    /// Hash of everything that affects future turns. Peers running their own copy
+    /// of the game compare these to detect divergence.
+    pub fn state_hash(&self) -> [u8; 32] {
+        use sha2::{Digest, Sha256};
+
+        let sorted = |cards: &HashMap<CardKind, usize>| {
+            let mut cards: Vec<(CardKind, usize)> = cards.iter().map(|(k, c)| (*k, *c)).collect();
+            cards.sort();
+            cards
+        };
+
+        let mut hasher = Sha256::new();
+        hasher.update(format!("{}|{}|{}|{};", self.seed, self.round, self.turn, self.rng.get_word_pos()));
+        for player in &self.players {
+            hasher.update(format!(
+                "{}:{:?}:{:?}:{:?};",
+                player.id,
+                sorted(&player.hand),
+                sorted(&player.public_cards),
+                sorted(&player.boosted_fruit_teas),
+            ));
+        }
+        hasher.update(format!("{:?}", self.deck.counts().collect::<Vec<_>>()));
+        hasher.finalize().into()
+    }
+
+    /// Config the game was created with, including the resolved seed
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    /// Every turn processed so far, in order, empty if history isn't kept
+    pub fn history(&self) -> &[TurnRecord] {
+        &self.history
+    }
+
+    /// Whether processed turns are recorded for `history`. On by default,
+    /// games that are never replayed can turn it off.
+    pub fn set_keep_history(&mut self, keep: bool) {
+        self.keep_history = keep;
+    }
+
     /// Get current player's hand
     pub fn get_player_hand(&self, player_id: usize) -> Result<&HashMap<CardKind, usize>, GameError> {
         if player_id >= self.players.len() {
@@ -460,54 +558,3 @@ impl<'a> GamePlayerView<'a> {
         Self { game, player_id }
     }
 }
-
-impl<'a> crate::tui::GameInterface for GamePlayerView<'a> {
-    fn get_hand(&self) -> HashMap<CardKind, usize> {
-        self.game.get_player_hand(self.player_id)
-            .map(|h| h.clone())
-            .unwrap_or_default()
-    }
-
-    fn get_game_status(&self) -> GameStatus {
-        self.game.get_game_status()
-    }
-
-    fn get_players_public(&self) -> Vec<PlayerPublic> {
-        self.game.get_players_public()
-    }
-
-    fn submit_turn(&mut self, selected: HashMap<CardKind, usize>, remaining: HashMap<CardKind, usize>) -> Result<(), String> {
-        self.game.validate_hand_submission(self.player_id, &selected, &remaining)
-            .map_err(|e| format!("{:?}", e))?;
-        self.game.mark_player_selected(self.player_id)
-            .map_err(|e| format!("{:?}", e))?;
-
-        // Update the player's hand with the remaining cards
-        if let Some(player) = self.game.players.get_mut(self.player_id) {
-            player.hand = remaining;
-        }
-
-        Ok(())
-    }
-
-    fn get_player_id(&self) -> usize {
-        self.player_id
-    }
-
-    fn activate_drink_tray(&mut self) -> Result<(), String> {
-        if let Some(player) = self.game.players.get_mut(self.player_id) {
-            if let Some(drink_tray_count) = player.public_cards.get_mut(&CardKind::DrinkTray) {
-                *drink_tray_count -= 1;
-                if *drink_tray_count == 0 {
-                    player.public_cards.remove(&CardKind::DrinkTray);
-                }
-                *player.hand.entry(CardKind::DrinkTray).or_insert(0) += 1;
-                Ok(())
-            } else {
-                Err("No DrinkTray in public cards".to_string())
-            }
-        } else {
-            Err("Invalid player id".to_string())
-        }
-    }
-}
@@ -1,5 +1,8 @@
 pub mod engine;
+#[cfg(feature = "tui")]
 pub mod tui;
+#[cfg(feature = "network")]
 pub mod network;
 pub mod log;
+pub mod bot;
 
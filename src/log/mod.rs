@@ -30,3 +30,20 @@ pub fn host(msg: impl AsRef<str>) {
 pub fn client(msg: impl AsRef<str>) {
     debug("client", msg);
 }
+
+pub fn peer(msg: impl AsRef<str>) {
+    debug("peer", msg);
+}
+
+pub fn gateway(msg: impl AsRef<str>) {
+    debug("gateway", msg);
+}
+
+pub fn bot(msg: impl AsRef<str>) {
+    debug("bot", msg);
+}
+
+/// Dedicated server output goes to stdout, tagged with the room
+pub fn server(room: &str, msg: impl AsRef<str>) {
+    println!("[{}] {}", room, msg.as_ref());
+}
@@ -1,6 +1,6 @@
 use libp2p::{
-    gossipsub, request_response,
-    swarm::NetworkBehaviour,
+    dcutr, gossipsub, identify, mdns, ping, relay, request_response,
+    swarm::{behaviour::toggle::Toggle, NetworkBehaviour},
     StreamProtocol,
 };
 use serde::{Deserialize, Serialize};
@@ -8,6 +8,8 @@ use std::io;
 
 use super::lobby::protocol::{ClientMessage, HostMessage};
 use super::game::protocol::{GameClientMessage, GameHostMessage};
+use super::latency::{PING_INTERVAL, PING_TIMEOUT};
+use super::version::{IDENTIFY_PROTOCOL, REQUEST_RESPONSE_PROTOCOL};
 
 // unified message enums for lobby + game
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,31 +28,61 @@ pub enum HostResponse {
 pub struct BobaGoBehaviour {
     pub request_response: request_response::cbor::Behaviour<ClientRequest, HostResponse>,
     pub gossipsub: gossipsub::Behaviour,
+    // LAN discovery for room codes, disabled if multicast isn't available
+    pub mdns: Toggle<mdns::tokio::Behaviour>,
+    // reach hosts behind NAT through a relay, then try to upgrade to a direct connection
+    pub relay_client: relay::client::Behaviour,
+    pub dcutr: dcutr::Behaviour,
+    pub identify: identify::Behaviour,
+    // round trips for the latency shown next to player names
+    pub ping: ping::Behaviour,
 }
 
 impl BobaGoBehaviour {
-    pub fn new(_peer_id: libp2p::PeerId) -> Result<Self, io::Error> {
+    pub fn new(
+        local_key: &libp2p::identity::Keypair,
+        relay_client: relay::client::Behaviour,
+        local_discovery: bool,
+    ) -> Result<Self, io::Error> {
         let request_response = request_response::cbor::Behaviour::new(
-            [(StreamProtocol::new("/boba-go/lobby/1.0.0"), request_response::ProtocolSupport::Full)],
+            [(StreamProtocol::new(REQUEST_RESPONSE_PROTOCOL), request_response::ProtocolSupport::Full)],
             request_response::Config::default(),
         );
 
-        // config: 1s heartbeat, strict validation
+        // config: 1s heartbeat, strict validation, and nothing is forwarded
+        // until the application has checked who published it
         let gossipsub_config = gossipsub::ConfigBuilder::default()
             .heartbeat_interval(std::time::Duration::from_secs(1))
             .validation_mode(gossipsub::ValidationMode::Strict)
+            .validate_messages()
             .build()
             .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
 
-        // create gossipsub with signed messages
+        // sign gossip with the transport key so message sources match connection peer ids
         let gossipsub: gossipsub::Behaviour = gossipsub::Behaviour::new(
-            gossipsub::MessageAuthenticity::Signed(libp2p::identity::Keypair::generate_ed25519()),
+            gossipsub::MessageAuthenticity::Signed(local_key.clone()),
             gossipsub_config,
         ).map_err(|e: &'static str| io::Error::new(io::ErrorKind::Other, e))?;
 
+        // without multicast we can still connect by address
+        let mdns = local_discovery
+            .then(|| mdns::tokio::Behaviour::new(mdns::Config::default(), local_key.public().to_peer_id()).ok())
+            .flatten();
+
+        let local_peer_id = local_key.public().to_peer_id();
+        let identify = identify::Behaviour::new(identify::Config::new(
+            IDENTIFY_PROTOCOL.to_string(),
+            local_key.public(),
+        ));
+
         Ok(Self {
             request_response,
             gossipsub,
+            mdns: Toggle::from(mdns),
+            relay_client,
+            dcutr: dcutr::Behaviour::new(local_peer_id),
+            identify,
+            ping: ping::Behaviour::new(ping::Config::new().with_interval(PING_INTERVAL).with_timeout(PING_TIMEOUT)),
         })
     }
 }
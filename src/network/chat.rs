@@ -0,0 +1,183 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{SystemTime, UNIX_EPOCH};
+use libp2p::{gossipsub::IdentTopic, PeerId, Swarm};
+use serde::{Deserialize, Serialize};
+
+use crate::network::behaviour::BobaGoBehaviour;
+use crate::network::wire;
+
+/// max characters in a single chat message
+pub const MAX_CHAT_LENGTH: usize = 200;
+
+/// number of messages kept in the chat history
+const CHAT_HISTORY_LIMIT: usize = 100;
+
+/// chat traffic published on the lobby/game gossipsub topic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ChatGossip {
+    Message(ChatMessage),
+    // only honoured when published by the host
+    MuteList { muted: Vec<usize> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub player_id: usize,
+    pub player_name: String,
+    pub timestamp: u64, // unix seconds
+    pub text: String,
+}
+
+impl ChatMessage {
+    /// Format the timestamp as HH:MM (UTC)
+    pub fn time_label(&self) -> String {
+        let minutes = (self.timestamp / 60) % (24 * 60);
+        format!("{:02}:{:02}", minutes / 60, minutes % 60)
+    }
+}
+
+/// Chat history and mute list, shared by lobby and game phases
+pub struct ChatLog {
+    local_player_id: Option<usize>,
+    local_player_name: String,
+    messages: VecDeque<ChatMessage>,
+    muted: HashSet<usize>,
+    // the player id and name each peer chats as, messages carry whatever the sender claims
+    senders: HashMap<PeerId, (usize, String)>,
+}
+
+impl ChatLog {
+    pub fn new(local_player_id: Option<usize>, local_player_name: String) -> Self {
+        Self {
+            local_player_id,
+            local_player_name,
+            messages: VecDeque::new(),
+            muted: HashSet::new(),
+            senders: HashMap::new(),
+        }
+    }
+
+    pub fn set_local_player_id(&mut self, player_id: usize) {
+        self.local_player_id = Some(player_id);
+    }
+
+    pub fn local_player_id(&self) -> Option<usize> {
+        self.local_player_id
+    }
+
+    /// Who is in the room, the id and name a peer's messages are shown under
+    pub fn set_senders(&mut self, senders: HashMap<PeerId, (usize, String)>) {
+        self.senders = senders;
+    }
+
+    pub fn sender(&self, peer: &PeerId) -> Option<(usize, &str)> {
+        self.senders.get(peer).map(|(id, name)| (*id, name.as_str()))
+    }
+
+    /// Switch to another numbering of the players, lobby ids to game seats and
+    /// back. Mutes follow the players that have a new id, the rest are dropped.
+    pub fn renumber(&mut self, local_player_id: Option<usize>, ids: &HashMap<usize, usize>) {
+        self.local_player_id = local_player_id;
+        self.muted = self.muted.iter().filter_map(|id| ids.get(id).copied()).collect();
+    }
+
+    pub fn messages(&self) -> impl Iterator<Item = &ChatMessage> {
+        self.messages.iter()
+    }
+
+    pub fn is_muted(&self, player_id: usize) -> bool {
+        self.muted.contains(&player_id)
+    }
+
+    pub fn muted_players(&self) -> Vec<usize> {
+        let mut muted: Vec<usize> = self.muted.iter().copied().collect();
+        muted.sort_unstable();
+        muted
+    }
+
+    /// Returns true if the mute state changed
+    pub fn set_muted(&mut self, player_id: usize, muted: bool) -> bool {
+        if muted {
+            self.muted.insert(player_id)
+        } else {
+            self.muted.remove(&player_id)
+        }
+    }
+
+    fn push(&mut self, message: ChatMessage) {
+        self.messages.push_back(message);
+        while self.messages.len() > CHAT_HISTORY_LIMIT {
+            self.messages.pop_front();
+        }
+    }
+
+    /// Build a message from the local player and record it
+    pub fn compose(&mut self, text: &str) -> Result<ChatMessage, String> {
+        let player_id = self.local_player_id.ok_or("Not in a lobby yet")?;
+        if self.is_muted(player_id) {
+            return Err("You have been muted by the host".to_string());
+        }
+
+        let text: String = text.trim().chars().take(MAX_CHAT_LENGTH).collect();
+        if text.is_empty() {
+            return Err("Message is empty".to_string());
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        let message = ChatMessage {
+            player_id,
+            player_name: self.local_player_name.clone(),
+            timestamp,
+            text,
+        };
+        self.push(message.clone());
+        Ok(message)
+    }
+
+    /// Apply incoming chat gossip, returns the message if it should be displayed.
+    /// Players are shown under the id and name we know their peer by, only
+    /// the host speaks for itself.
+    pub fn handle_gossip(&mut self, gossip: ChatGossip, source: Option<PeerId>, from_host: bool) -> Option<ChatMessage> {
+        match gossip {
+            ChatGossip::Message(mut message) => {
+                if !from_host {
+                    let (player_id, name) = source.and_then(|peer| self.sender(&peer))?;
+                    message.player_name = name.to_string();
+                    message.player_id = player_id;
+                }
+                if self.is_muted(message.player_id) {
+                    return None;
+                }
+                message.text = message.text.chars().take(MAX_CHAT_LENGTH).collect();
+                self.push(message.clone());
+                Some(message)
+            }
+            ChatGossip::MuteList { muted } => {
+                if from_host {
+                    self.muted = muted.into_iter().collect();
+                }
+                None
+            }
+        }
+    }
+}
+
+/// Try to parse a gossipsub payload as chat traffic
+pub fn parse_chat_gossip(data: &[u8]) -> Option<ChatGossip> {
+    wire::decode::<ChatGossip>(data)
+}
+
+/// Publish chat traffic on the given topic
+pub fn publish_chat_gossip(swarm: &mut Swarm<BobaGoBehaviour>, topic: &IdentTopic, gossip: &ChatGossip) -> Result<(), String> {
+    let data = wire::encode(gossip)?;
+    swarm
+        .behaviour_mut()
+        .gossipsub
+        .publish(topic.clone(), data)
+        .map(|_| ())
+        .map_err(|e| format!("Failed to send chat: {e:?}"))
+}
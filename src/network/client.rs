@@ -1,17 +1,27 @@
 use libp2p::{
     gossipsub::IdentTopic,
     swarm::Swarm,
-    Multiaddr,
+    ping, Multiaddr, PeerId,
 };
+use std::collections::HashMap;
 use std::error::Error;
 
 use crate::network::behaviour::BobaGoBehaviour;
+use crate::network::chat::{self, ChatGossip, ChatLog};
+use crate::network::latency::{LatencyTracker, PlayerLatency};
 
 /// Generic client with networking (works for any state type)
 pub struct Client<S> {
     pub(crate) swarm: Swarm<BobaGoBehaviour>,
     pub(crate) state: S,
     pub(crate) topic: IdentTopic,
+    pub(crate) chat: ChatLog,
+    // sequence number of the last host message we accepted
+    pub(crate) host_seq: Option<u64>,
+    // our own round trip to the host
+    pub(crate) latency: LatencyTracker,
+    // everyone's latency as last reported by the host, by player id
+    pub(crate) room_latency: HashMap<usize, PlayerLatency>,
 }
 
 // Generic impl - works for any state type
@@ -32,4 +42,34 @@ impl<S> Client<S> {
     pub fn state_mut(&mut self) -> &mut S {
         &mut self.state
     }
+
+    pub fn chat(&self) -> &ChatLog {
+        &self.chat
+    }
+
+    /// Our round trip to the host, None until the first ping
+    pub fn host_latency(&self, host: Option<PeerId>) -> Option<PlayerLatency> {
+        host.and_then(|host| self.latency.latency(&host))
+    }
+
+    // record a ping, and stop waiting for a host that no longer answers
+    pub(crate) fn handle_ping(&mut self, event: ping::Event, host: Option<PeerId>) {
+        if self.latency.record(&event) && Some(event.peer) == host {
+            crate::log::client(format!("Host {} stopped answering pings, disconnecting", event.peer));
+            self.swarm.disconnect_peer_id(event.peer).ok();
+        }
+    }
+
+    pub(crate) fn apply_latency_report(&mut self, players: Vec<(usize, PlayerLatency)>) {
+        self.room_latency = players.into_iter().collect();
+    }
+
+    /// Send a chat message to everyone in the room
+    pub fn send_chat(&mut self, text: &str) -> Result<(), String> {
+        let message = self.chat.compose(text)?;
+        if let Err(e) = chat::publish_chat_gossip(&mut self.swarm, &self.topic, &ChatGossip::Message(message)) {
+            crate::log::client(e);
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,386 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use libp2p::identity::{Keypair, PublicKey};
+use libp2p::PeerId;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::engine::{replay_game, CardKind, Game, GameSettings, GameStatus, PlayerPublic, PlayerTurnState, TurnRecord};
+use crate::network::{wire, Identity, PlayerLatencies};
+
+/// bump whenever the layout of a turn file changes
+pub const TURN_FILE_FORMAT: u32 = 2;
+
+/// extension turn files are saved with, other files in the folder are left alone
+pub const TURN_FILE_EXTENSION: &str = "boba";
+
+/// What a correspondence game is: the rules, with the seed fixed, and who sits where.
+/// Everything else follows from the picks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CorrespondenceHeader {
+    pub settings: GameSettings,
+    pub players: Vec<String>,
+    // peer id of each seat's identity, only picks signed by it count
+    pub identities: Vec<String>,
+}
+
+/// One player's pick for one turn. Cards are sorted so the signed bytes are
+/// the same on every machine.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pick {
+    pub seat: usize,
+    // turns resolved before this one
+    pub turn: usize,
+    pub drink_tray: bool,
+    pub selected: Vec<(CardKind, usize)>,
+    pub remaining: Vec<(CardKind, usize)>,
+}
+
+/// A pick with the key of whoever made it, which has to be the seat's
+/// identity from the header.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedPick {
+    pub pick: Pick,
+    // protobuf encoded
+    pub public_key: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+impl SignedPick {
+    /// Sign `pick` for the game with `game_id`
+    pub fn sign(game_id: &[u8; 32], pick: Pick, keypair: &Keypair) -> Result<Self, String> {
+        let signature = keypair.sign(&signed_bytes(game_id, &pick)?).map_err(|e| e.to_string())?;
+        Ok(Self { pick, public_key: keypair.public().encode_protobuf(), signature })
+    }
+
+    fn verify(&self, game_id: &[u8; 32], identity: &str) -> Result<(), String> {
+        let key = PublicKey::try_decode_protobuf(&self.public_key)
+            .map_err(|_| format!("Pick for seat {} has an invalid key", self.pick.seat))?;
+        if key.to_peer_id().to_string() != identity {
+            return Err(format!("Pick for seat {} on turn {} was made by someone else", self.pick.seat, self.pick.turn + 1));
+        }
+        if !key.verify(&signed_bytes(game_id, &self.pick)?, &self.signature) {
+            return Err(format!("Pick for seat {} on turn {} has a bad signature", self.pick.seat, self.pick.turn + 1));
+        }
+        Ok(())
+    }
+}
+
+// the game id is signed along with the pick, so picks can't be moved to another game
+fn signed_bytes(game_id: &[u8; 32], pick: &Pick) -> Result<Vec<u8>, String> {
+    wire::encode(&(game_id, pick))
+}
+
+fn sorted_cards(cards: &HashMap<CardKind, usize>) -> Vec<(CardKind, usize)> {
+    let mut cards: Vec<(CardKind, usize)> = cards.iter().filter(|(_, count)| **count > 0).map(|(k, c)| (*k, *c)).collect();
+    cards.sort();
+    cards
+}
+
+/// A game played by passing a file around. It holds every pick made so far,
+/// so anyone can check the signatures and replay the game from the seed.
+/// It is not secret: hands follow from the seed, and picks for the current
+/// turn can be read before it resolves.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TurnFile {
+    pub format: u32,
+    pub header: CorrespondenceHeader,
+    // every seat's pick for each resolved turn, by seat
+    pub turns: Vec<Vec<SignedPick>>,
+    // picks for the turn in progress
+    pub picks: Vec<SignedPick>,
+}
+
+impl TurnFile {
+    /// Start a game for these players and their identities in seating order,
+    /// a random seed is picked unless the settings fix one
+    pub fn new(mut settings: GameSettings, seats: Vec<(String, PeerId)>) -> Result<Self, String> {
+        for (i, (_, peer)) in seats.iter().enumerate() {
+            if seats[..i].iter().any(|(_, other)| other == peer) {
+                return Err(format!("{peer} has more than one seat"));
+            }
+        }
+        settings.seed.get_or_insert_with(rand::random);
+        let (players, identities) = seats.into_iter().map(|(name, peer)| (name, peer.to_string())).unzip();
+        let file = Self {
+            format: TURN_FILE_FORMAT,
+            header: CorrespondenceHeader { settings, players, identities },
+            turns: Vec::new(),
+            picks: Vec::new(),
+        };
+        file.replay()?;
+        Ok(file)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = fs::read_to_string(path).map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+        let file: Self = serde_json::from_str(&json).map_err(|e| format!("{} is not a turn file: {e}", path.display()))?;
+        file.verify()?;
+        Ok(file)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| format!("Could not write {}: {e}", path.display()))
+    }
+
+    /// Hash of the header, the same for every copy of the game
+    pub fn game_id(&self) -> [u8; 32] {
+        let header = wire::encode(&self.header).unwrap_or_default();
+        Sha256::digest(header).into()
+    }
+
+    /// Check the format, that every pick is signed by its seat's identity,
+    /// that every resolved turn replays and that pending picks fit their hands
+    pub fn verify(&self) -> Result<(), String> {
+        if self.format != TURN_FILE_FORMAT {
+            return Err(format!("Turn file format {} is not supported, this build reads {}", self.format, TURN_FILE_FORMAT));
+        }
+        let seats = self.header.players.len();
+        if self.header.identities.len() != seats {
+            return Err("Turn file does not name an identity for every seat".to_string());
+        }
+        let game_id = self.game_id();
+        let resolved = self.turns.iter().enumerate().map(|(turn, picks)| (turn, picks.as_slice()));
+        for (turn, picks) in resolved.chain(std::iter::once((self.turns.len(), self.picks.as_slice()))) {
+            if turn < self.turns.len() && picks.len() != seats {
+                return Err(format!("Turn {} was resolved without every pick", turn + 1));
+            }
+            for (i, signed) in picks.iter().enumerate() {
+                let pick = &signed.pick;
+                if pick.turn != turn || pick.seat >= seats || picks[..i].iter().any(|p| p.pick.seat == pick.seat) {
+                    return Err(format!("Turn {} has a misplaced pick", turn + 1));
+                }
+                signed.verify(&game_id, &self.header.identities[pick.seat])?;
+            }
+        }
+        // picks waiting on the rest of the turn have to be playable too, or
+        // their seats could never pick again
+        for signed in &self.picks {
+            self.check_pick(&signed.pick)?;
+        }
+        self.replay().map(|_| ())
+    }
+
+    /// The game at the start of the turn in progress
+    pub fn replay(&self) -> Result<Game, String> {
+        let config = self.header.settings.to_config(self.header.players.clone());
+        let turns: Vec<TurnRecord> = self.turns.iter().map(|picks| turn_record(picks, self.header.players.len())).collect();
+        replay_game(config, &turns, |_| {}).map_err(|e| e.to_string())
+    }
+
+    /// Seat the header gives to `identity`, None if it doesn't play in this game
+    pub fn seat_for(&self, identity: &Identity) -> Option<usize> {
+        let peer = identity.peer_id().to_string();
+        self.header.identities.iter().position(|id| *id == peer)
+    }
+
+    pub fn has_picked(&self, seat: usize) -> bool {
+        self.picks.iter().any(|p| p.pick.seat == seat)
+    }
+
+    /// Players we still need a pick from this turn
+    pub fn waiting_on(&self) -> Vec<&str> {
+        (0..self.header.players.len())
+            .filter(|seat| !self.has_picked(*seat))
+            .map(|seat| self.header.players[seat].as_str())
+            .collect()
+    }
+
+    /// Add a pick, resolving the turn once every seat has picked.
+    /// Returns true if the turn was resolved.
+    pub fn add_pick(&mut self, signed: SignedPick) -> Result<bool, String> {
+        let pick = &signed.pick;
+        if pick.turn != self.turns.len() {
+            return Err(format!("Pick is for turn {}, the game is on turn {}", pick.turn + 1, self.turns.len() + 1));
+        }
+        if self.has_picked(pick.seat) {
+            return Err(format!("{} already picked this turn", self.header.players.get(pick.seat).map_or("That seat", |s| s)));
+        }
+        let Some(identity) = self.header.identities.get(pick.seat) else {
+            return Err(format!("The game has no seat {}", pick.seat));
+        };
+        signed.verify(&self.game_id(), identity)?;
+        self.check_pick(pick)?;
+
+        let seat = pick.seat;
+        self.picks.push(signed);
+        if self.picks.len() < self.header.players.len() {
+            return Ok(false);
+        }
+
+        let mut picks = std::mem::take(&mut self.picks);
+        picks.sort_by_key(|p| p.pick.seat);
+        self.turns.push(picks);
+        // every pick was checked on its own, if the turn still doesn't replay
+        // the one that closed it goes and the others wait for a new one
+        if let Err(e) = self.replay() {
+            let mut picks = self.turns.pop().unwrap_or_default();
+            picks.retain(|p| p.pick.seat != seat);
+            self.picks = picks;
+            return Err(e);
+        }
+        Ok(true)
+    }
+
+    // the pick against the hand its seat holds at the start of the turn, with
+    // the Drink Tray taken out first if the pick used it
+    fn check_pick(&self, pick: &Pick) -> Result<(), String> {
+        let mut game = self.replay()?;
+        let name = self.header.players.get(pick.seat).map_or("That seat", |s| s);
+        if pick.drink_tray && game.activate_drink_tray(pick.seat).is_err() {
+            return Err(format!("{name} used a Drink Tray they don't have"));
+        }
+        let selected = pick.selected.iter().copied().collect();
+        let remaining = pick.remaining.iter().copied().collect();
+        game.validate_hand_submission(pick.seat, &selected, &remaining)
+            .map_err(|_| format!("{name} picked cards they don't hold or the wrong number of them"))
+    }
+
+    /// Take the picks from another copy of the same game, e.g. the files each
+    /// player sent back. Returns how many picks were new to us.
+    pub fn merge(&mut self, other: &TurnFile) -> Result<usize, String> {
+        if other.game_id() != self.game_id() {
+            return Err("That turn file is from another game".to_string());
+        }
+        other.verify()?;
+        let mut added = 0;
+        for signed in other.turns.iter().flatten().chain(&other.picks) {
+            if signed.pick.turn == self.turns.len() && !self.has_picked(signed.pick.seat) {
+                self.add_pick(signed.clone())?;
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    /// Merge every other turn file of this game found in `dir`, for picks
+    /// that came back as separate files. Files that aren't turn files, or
+    /// are from other games, are skipped. Returns how many picks were new.
+    pub fn collect_from(&mut self, dir: &Path) -> usize {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return 0;
+        };
+        let mut added = 0;
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().is_some_and(|ext| ext == TURN_FILE_EXTENSION)
+                && let Ok(other) = Self::load(&path)
+            {
+                added += self.merge(&other).unwrap_or(0);
+            }
+        }
+        added
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.replay().is_ok_and(|game| game.is_game_over())
+    }
+}
+
+fn turn_record(picks: &[SignedPick], players: usize) -> TurnRecord {
+    let mut submissions = vec![None; players];
+    let mut drink_tray_activations = Vec::new();
+    for signed in picks {
+        let pick = &signed.pick;
+        if pick.drink_tray {
+            drink_tray_activations.push(pick.seat);
+        }
+        if let Some(submission) = submissions.get_mut(pick.seat) {
+            *submission = Some((pick.selected.iter().copied().collect(), pick.remaining.iter().copied().collect()));
+        }
+    }
+    TurnRecord { drink_tray_activations, submissions }
+}
+
+/// One seat of a correspondence game, played through `GameInterface` like any
+/// other game. Submitting signs the pick and adds it to the file.
+pub struct CorrespondenceGame {
+    file: TurnFile,
+    game: Game,
+    seat: usize,
+    keypair: Keypair,
+    drink_tray: bool,
+}
+
+impl CorrespondenceGame {
+    pub fn new(file: TurnFile, seat: usize, identity: &Identity) -> Result<Self, String> {
+        if seat >= file.header.players.len() {
+            return Err(format!("The game has no seat {seat}"));
+        }
+        if file.seat_for(identity) != Some(seat) {
+            return Err(format!("{} is played by another identity", file.header.players[seat]));
+        }
+        let game = file.replay()?;
+        Ok(Self { file, game, seat, keypair: identity.keypair.clone(), drink_tray: false })
+    }
+
+    pub fn file(&self) -> &TurnFile {
+        &self.file
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Whether the file needs a pick from us before it moves on
+    pub fn can_pick(&self) -> bool {
+        !self.game.is_game_over() && !self.file.has_picked(self.seat)
+    }
+}
+
+impl crate::engine::GameInterface for CorrespondenceGame {
+    fn get_hand(&self) -> HashMap<CardKind, usize> {
+        self.game.get_player_hand(self.seat).cloned().unwrap_or_default()
+    }
+
+    fn get_game_status(&self) -> GameStatus {
+        let mut status = self.game.get_game_status();
+        for signed in &self.file.picks {
+            if let Some(state) = status.player_turn_states.get_mut(signed.pick.seat) {
+                *state = PlayerTurnState::Selected;
+            }
+        }
+        status
+    }
+
+    fn get_players_public(&self) -> Vec<PlayerPublic> {
+        self.game.get_players_public()
+    }
+
+    fn submit_turn(&mut self, selected: HashMap<CardKind, usize>, remaining: HashMap<CardKind, usize>) -> Result<(), String> {
+        if !self.can_pick() {
+            return Err("Already picked this turn".to_string());
+        }
+        self.game
+            .validate_hand_submission(self.seat, &selected, &remaining)
+            .map_err(|e| format!("{:?}", e))?;
+        let pick = Pick {
+            seat: self.seat,
+            turn: self.file.turns.len(),
+            drink_tray: self.drink_tray,
+            selected: sorted_cards(&selected),
+            remaining: sorted_cards(&remaining),
+        };
+        let signed = SignedPick::sign(&self.file.game_id(), pick, &self.keypair)?;
+        if self.file.add_pick(signed)? {
+            self.game = self.file.replay()?;
+        }
+        self.drink_tray = false;
+        Ok(())
+    }
+
+    fn get_player_id(&self) -> usize {
+        self.seat
+    }
+
+    fn activate_drink_tray(&mut self) -> Result<(), String> {
+        self.game
+            .activate_drink_tray(self.seat)
+            .map_err(|_| "No DrinkTray in public cards".to_string())?;
+        self.drink_tray = true;
+        Ok(())
+    }
+}
+
+impl PlayerLatencies for CorrespondenceGame {}
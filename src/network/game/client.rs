@@ -2,14 +2,19 @@ use std::collections::HashMap;
 use libp2p::{
     futures::StreamExt,
     gossipsub::IdentTopic,
+    request_response::OutboundRequestId,
     swarm::{Swarm, SwarmEvent},
     PeerId,
 };
 
-use crate::network::behaviour::{BobaGoBehaviour, BobaGoBehaviourEvent, ClientRequest};
-use crate::network::Client;
+use crate::network::behaviour::{BobaGoBehaviour, BobaGoBehaviourEvent, ClientRequest, HostResponse};
+use crate::network::{gossip, Client};
+use crate::network::chat::{self, ChatLog, ChatMessage};
+use crate::network::latency::{LatencyTracker, PlayerLatencies, PlayerLatency};
 use crate::engine::{models::{CardKind, PlayerPublic}, state::{GameStatus, PlayerTurnState}};
-use super::protocol::{GameClientMessage, GameHostMessage, GameEndReason};
+use crate::engine::fairness::{self, FairnessObservations, FairnessReport, FairnessReveal};
+use super::protocol::{GameClientMessage, GameHostMessage, GameEndReason, GameRequestKind};
+use super::delta::{CardsDelta, PlayerDelta};
 use crate::log;
 
 pub struct GameClientState {
@@ -20,6 +25,12 @@ pub struct GameClientState {
     pub selected_cards: HashMap<CardKind, usize>,
     pub turn_submitted: bool,
     pub host_peer_id: Option<PeerId>,
+    // requests waiting for an Accepted/Rejected reply
+    pub pending_requests: HashMap<OutboundRequestId, GameRequestKind>,
+    // hands we were dealt, checked against the host's reveal when fair dealing is on
+    pub fairness: Option<FairnessObservations>,
+    // version of the last update applied
+    pub state_version: u64,
 }
 
 impl GameClientState {
@@ -38,6 +49,9 @@ impl GameClientState {
             selected_cards: HashMap::new(),
             turn_submitted: false,
             host_peer_id,
+            pending_requests: HashMap::new(),
+            fairness: None,
+            state_version: 0,
         }
     }
 
@@ -93,6 +107,11 @@ impl GameClientState {
         self.turn_submitted = true;
     }
 
+    // host rejected the submission, allow picking again
+    pub fn roll_back_submission(&mut self) {
+        self.turn_submitted = false;
+    }
+
     pub fn reset_for_new_turn(&mut self) {
         self.selected_cards.clear();
         self.turn_submitted = false;
@@ -111,10 +130,68 @@ impl GameClientState {
         self.players_public = players;
     }
 
+    /// Take over a full snapshot of our view from the host. The submission
+    /// flag follows the host, in case it has our pick but we missed the ack.
+    pub fn apply_snapshot(
+        &mut self,
+        hand: HashMap<CardKind, usize>,
+        players_public: Vec<PlayerPublic>,
+        game_status: GameStatus,
+        state_version: u64,
+    ) {
+        if let Some(observations) = self.fairness.as_mut() {
+            observations.record_hand(game_status.round, game_status.turn, &hand);
+        }
+        self.update_hand(hand);
+        self.update_players_public(players_public);
+        self.update_game_status(game_status);
+        self.state_version = state_version;
+        self.turn_submitted = matches!(self.get_own_turn_state(), PlayerTurnState::Selected);
+    }
+
+    /// Apply the changes since a version we have, keeping the rest of our view
+    pub fn apply_delta(&mut self, hands: &[(usize, CardsDelta)], players: &[PlayerDelta], game_status: GameStatus, state_version: u64) {
+        let mut hand = self.hand.clone();
+        if let Some((_, delta)) = hands.iter().find(|(player_id, _)| *player_id == self.player_id) {
+            delta.apply(&mut hand);
+        }
+        let mut players_public = self.players_public.clone();
+        for delta in players {
+            delta.apply(&mut players_public);
+        }
+        self.apply_snapshot(hand, players_public, game_status, state_version);
+    }
+
+    /// Mark who has picked this turn, false if the progress is for another turn
+    pub fn apply_turn_progress(&mut self, round: usize, turn: usize, submitted: &[usize]) -> bool {
+        if round != self.game_status.round || turn != self.game_status.turn {
+            return false;
+        }
+        for (player_id, state) in self.game_status.player_turn_states.iter_mut().enumerate() {
+            *state = if submitted.contains(&player_id) {
+                PlayerTurnState::Selected
+            } else {
+                PlayerTurnState::NotSelected
+            };
+        }
+        true
+    }
+
     pub fn can_submit(&self) -> bool {
         !self.selected_cards.is_empty() && !self.turn_submitted
     }
 
+    /// Check the host's reveal against what we saw, None if fair dealing was off
+    pub fn verify_fairness(&self, reveal: Option<&FairnessReveal>) -> Option<FairnessReport> {
+        let observations = self.fairness.as_ref()?;
+        Some(match reveal {
+            Some(reveal) => fairness::verify_reveal(reveal, observations),
+            None => FairnessReport {
+                mismatches: vec!["Host did not reveal the seed".to_string()],
+            },
+        })
+    }
+
     pub fn get_own_turn_state(&self) -> PlayerTurnState {
         self.game_status.player_turn_states
             .get(self.player_id)
@@ -127,34 +204,122 @@ impl Client<GameClientState> {
     pub fn new(
         swarm: Swarm<BobaGoBehaviour>,
         topic: IdentTopic,
-        player_id: usize,
-        hand: HashMap<CardKind, usize>,
-        players_public: Vec<PlayerPublic>,
-        game_status: GameStatus,
-        host_peer_id: Option<PeerId>,
+        chat: ChatLog,
+        state: GameClientState,
     ) -> Self {
-        let state = GameClientState::new(player_id, hand, players_public, game_status, host_peer_id);
         Self {
             swarm,
             state,
             topic,
+            chat,
+            host_seq: None,
+            latency: LatencyTracker::new(),
+            room_latency: HashMap::new(),
         }
     }
 
+    // send a game request and remember it until the host acknowledges it
+    fn send_game_request(&mut self, host_peer: PeerId, message: GameClientMessage, kind: GameRequestKind) {
+        let request_id = self.swarm
+            .behaviour_mut()
+            .request_response
+            .send_request(&host_peer, ClientRequest::Game(message));
+        self.state.pending_requests.insert(request_id, kind);
+    }
+
     // submit turn to host
     pub fn submit_turn(&mut self, host_peer: PeerId) {
-        let message = ClientRequest::Game(GameClientMessage::SubmitTurn {
+        let message = GameClientMessage::SubmitTurn {
             selected_cards: self.state.selected_cards.clone(),
             remaining_hand: self.state.get_remaining_hand(),
-        });
-
-        self.swarm
-            .behaviour_mut()
-            .request_response
-            .send_request(&host_peer, message);
+            state_version: self.state.state_version,
+        };
+        self.send_game_request(host_peer, message, GameRequestKind::SubmitTurn);
 
         self.state.mark_turn_submitted();
-        log::client(format!("Submitted turn to host"));
+        log::client("Submitted turn to host");
+    }
+
+    /// Ask the host for the current state, the answer arrives as a `GameUpdated` event
+    pub fn request_sync(&mut self) {
+        let Some(host_peer) = self.state.host_peer_id else {
+            return;
+        };
+        log::client(format!("Requesting resync at version {}", self.state.state_version));
+        self.send_game_request(host_peer, GameClientMessage::RequestSync, GameRequestKind::Sync);
+    }
+
+    /// Latency of a seat as the host measured it, our own seat is our round trip to the host
+    pub fn player_latency(&self, player_id: usize) -> Option<PlayerLatency> {
+        if player_id == self.state.player_id {
+            return self.host_latency(self.state.host_peer_id);
+        }
+        self.room_latency.get(&player_id).copied()
+    }
+
+    // apply a rejected request to local state
+    fn handle_rejection(&mut self, request: GameRequestKind, reason: String) -> GameClientEvent {
+        log::client(format!("Host rejected {:?}: {}", request, reason));
+        if request == GameRequestKind::SubmitTurn {
+            self.state.roll_back_submission();
+        }
+        GameClientEvent::RequestRejected { request, reason }
+    }
+
+    fn game_ended(
+        &mut self,
+        final_scores: Vec<(usize, f32, String, crate::engine::ScoreBreakdown)>,
+        reason: GameEndReason,
+        reveal: Option<&FairnessReveal>,
+    ) -> GameClientEvent {
+        let fairness = self.state.verify_fairness(reveal);
+        if let Some(report) = &fairness {
+            log::client(format!("Fair dealing check: {:?}", report.mismatches));
+        }
+        GameClientEvent::GameEnded { final_scores, reason, fairness }
+    }
+
+    fn handle_request_response(
+        &mut self,
+        rr_event: libp2p::request_response::Event<ClientRequest, HostResponse>,
+    ) -> Option<GameClientEvent> {
+        use libp2p::request_response;
+
+        match rr_event {
+            request_response::Event::Message {
+                message: request_response::Message::Response { request_id, response },
+                ..
+            } => {
+                self.state.pending_requests.remove(&request_id);
+                match response {
+                    HostResponse::Game(GameHostMessage::Accepted { request }) => {
+                        log::client(format!("Host accepted {:?}", request));
+                        Some(GameClientEvent::RequestAccepted { request })
+                    }
+                    HostResponse::Game(GameHostMessage::Rejected { request, reason }) => {
+                        Some(self.handle_rejection(request, reason))
+                    }
+                    HostResponse::Game(GameHostMessage::SyncState { hand, players_public, game_status, state_version }) => {
+                        // an update may have overtaken the sync
+                        if state_version < self.state.state_version {
+                            return None;
+                        }
+                        log::client(format!("Resynced to version {state_version}"));
+                        self.state.apply_snapshot(hand, players_public, game_status.clone(), state_version);
+                        Some(GameClientEvent::GameUpdated { game_status })
+                    }
+                    HostResponse::Game(GameHostMessage::GameEnded { final_scores, reason, fairness }) => {
+                        Some(self.game_ended(final_scores, reason, fairness.as_ref()))
+                    }
+                    _ => None,
+                }
+            }
+            request_response::Event::OutboundFailure { request_id, error, .. } => {
+                let request = self.state.pending_requests.remove(&request_id)?;
+                Some(self.handle_rejection(request, format!("Could not reach host: {error}")))
+            }
+            _ => None,
+        }
     }
 
     // run event loop
@@ -162,34 +327,81 @@ impl Client<GameClientState> {
         loop {
             match self.swarm.select_next_some().await {
                 SwarmEvent::Behaviour(BobaGoBehaviourEvent::Gossipsub(gossipsub_event)) => {
-                    if let libp2p::gossipsub::Event::Message { message, .. } = gossipsub_event {
-                        if let Ok(json_str) = std::str::from_utf8(&message.data) {
-                            if let Ok(host_message) = serde_json::from_str::<GameHostMessage>(json_str) {
-                                match host_message {
-                                    GameHostMessage::GameUpdate { all_hands, players_public, game_status } => {
-                                        // extract own hand from all_hands
-                                        if let Some(your_hand) = all_hands.get(self.state.player_id) {
-                                            self.state.update_hand(your_hand.clone());
-                                        }
-                                        self.state.update_players_public(players_public);
-                                        self.state.update_game_status(game_status.clone());
-                                        return Some(GameClientEvent::GameUpdated { game_status });
+                    if let libp2p::gossipsub::Event::Message { message, message_id, propagation_source } = gossipsub_event {
+                        let acceptance = gossip::validate_client_gossip(&message, self.state.host_peer_id, &mut self.host_seq, &self.chat);
+                        if !gossip::report(&mut self.swarm, &message_id, &propagation_source, acceptance) {
+                            log::client(format!("Dropped gossip published by {:?} via {propagation_source}", message.source));
+                            continue;
+                        }
+                        if let Some(gossip) = chat::parse_chat_gossip(&message.data) {
+                            let from_host = message.source.is_some() && message.source == self.state.host_peer_id;
+                            if let Some(message) = self.chat.handle_gossip(gossip, message.source, from_host) {
+                                return Some(GameClientEvent::ChatReceived { message });
+                            }
+                            continue;
+                        }
+                        if let Some(host_message) = gossip::parse_host_gossip::<GameHostMessage>(&message.data) {
+                            match host_message {
+                                GameHostMessage::GameUpdate { mut all_hands, players_public, game_status, state_version } => {
+                                    // already have this one, or something newer from a resync
+                                    if state_version <= self.state.state_version {
+                                        continue;
+                                    }
+                                    let missed = state_version > self.state.state_version + 1;
+                                    // extract own hand from all_hands
+                                    let your_hand = if self.state.player_id < all_hands.len() {
+                                        all_hands.swap_remove(self.state.player_id)
+                                    } else {
+                                        self.state.hand.clone()
+                                    };
+                                    self.state.apply_snapshot(your_hand, players_public, game_status.clone(), state_version);
+                                    if missed {
+                                        log::client(format!("Missed updates before version {state_version}"));
+                                        self.request_sync();
+                                    }
+                                    return Some(GameClientEvent::GameUpdated { game_status });
+                                }
+                                GameHostMessage::GameDelta { base_version, state_version, hands, players, game_status } => {
+                                    if state_version <= self.state.state_version {
+                                        continue;
                                     }
-                                    GameHostMessage::GameEnded { final_scores, reason } => {
-                                        return Some(GameClientEvent::GameEnded { final_scores, reason });
+                                    // built on an update we never got, only a resync helps
+                                    if base_version > self.state.state_version {
+                                        log::client(format!("Missed version {base_version} needed for delta to {state_version}"));
+                                        self.request_sync();
+                                        continue;
                                     }
-                                    GameHostMessage::Error { message } => {
-                                        log::client(format!("Error from host: {}", message));
+                                    self.state.apply_delta(&hands, &players, game_status.clone(), state_version);
+                                    return Some(GameClientEvent::GameUpdated { game_status });
+                                }
+                                GameHostMessage::GameEnded { final_scores, reason, fairness } => {
+                                    return Some(self.game_ended(final_scores, reason, fairness.as_ref()));
+                                }
+                                GameHostMessage::TurnProgress { round, turn, submitted } => {
+                                    if self.state.apply_turn_progress(round, turn, &submitted) {
+                                        return Some(GameClientEvent::TurnProgress { submitted });
                                     }
                                 }
+                                GameHostMessage::Latency { players } => self.apply_latency_report(players),
+                                GameHostMessage::Accepted { .. }
+                                | GameHostMessage::Rejected { .. }
+                                | GameHostMessage::SyncState { .. } => {}
                             }
                         }
                     }
                 }
+                SwarmEvent::Behaviour(BobaGoBehaviourEvent::RequestResponse(rr_event)) => {
+                    if let Some(event) = self.handle_request_response(rr_event) {
+                        return Some(event);
+                    }
+                }
+                SwarmEvent::Behaviour(BobaGoBehaviourEvent::Ping(event)) => {
+                    self.handle_ping(event, self.state.host_peer_id);
+                }
                 SwarmEvent::ConnectionEstablished { peer_id, .. } => {
                     super::super::events::handle_client_connection_established(&mut self.swarm, peer_id);
                 }
-                SwarmEvent::ConnectionClosed { peer_id, .. } => {
+                SwarmEvent::ConnectionClosed { peer_id, num_established: 0, .. } => {
                     super::super::events::log_client_connection_closed(peer_id);
                     if Some(peer_id) == self.state.host_peer_id {
                         return Some(GameClientEvent::Disconnected);
@@ -204,11 +416,20 @@ impl Client<GameClientState> {
 #[derive(Debug)]
 pub enum GameClientEvent {
     GameUpdated { game_status: GameStatus },
-    GameEnded { final_scores: Vec<(usize, f32, String, crate::engine::ScoreBreakdown)>, reason: GameEndReason },
+    GameEnded {
+        final_scores: Vec<(usize, f32, String, crate::engine::ScoreBreakdown)>,
+        reason: GameEndReason,
+        fairness: Option<FairnessReport>,
+    },
+    // someone picked, the game status has the per-player turn states
+    TurnProgress { submitted: Vec<usize> },
+    RequestAccepted { request: GameRequestKind },
+    RequestRejected { request: GameRequestKind, reason: String },
+    ChatReceived { message: ChatMessage },
     Disconnected,
 }
 
-impl crate::tui::GameInterface for Client<GameClientState> {
+impl crate::engine::GameInterface for Client<GameClientState> {
     fn get_hand(&self) -> HashMap<CardKind, usize> {
         self.state.hand.clone()
     }
@@ -237,11 +458,7 @@ impl crate::tui::GameInterface for Client<GameClientState> {
 
     fn activate_drink_tray(&mut self) -> Result<(), String> {
         if let Some(host_peer) = self.state.host_peer_id {
-            let message = ClientRequest::Game(GameClientMessage::ActivateDrinkTray);
-            self.swarm
-                .behaviour_mut()
-                .request_response
-                .send_request(&host_peer, message);
+            self.send_game_request(host_peer, GameClientMessage::ActivateDrinkTray, GameRequestKind::ActivateDrinkTray);
             log::client("Sent DrinkTray activation request to host".to_string());
             Ok(())
         } else {
@@ -250,3 +467,8 @@ impl crate::tui::GameInterface for Client<GameClientState> {
     }
 }
 
+impl PlayerLatencies for Client<GameClientState> {
+    fn player_latency(&self, player_id: usize) -> Option<PlayerLatency> {
+        Client::<GameClientState>::player_latency(self, player_id)
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::engine::{models::{CardKind, PlayerPublic}, Game};
+
+/// New counts for the cards that changed, a count of zero means the card is gone
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CardsDelta(pub Vec<(CardKind, usize)>);
+
+impl CardsDelta {
+    /// Cards whose count in `new` differs from any of the `old` maps
+    pub fn since<'a>(old: impl IntoIterator<Item = &'a HashMap<CardKind, usize>>, new: &HashMap<CardKind, usize>) -> Self {
+        let count = |cards: &HashMap<CardKind, usize>, card: &CardKind| cards.get(card).copied().unwrap_or(0);
+        let mut changed = BTreeSet::new();
+        for old in old {
+            for card in old.keys().chain(new.keys()) {
+                if count(old, card) != count(new, card) {
+                    changed.insert(*card);
+                }
+            }
+        }
+        Self(changed.into_iter().map(|card| (card, count(new, &card))).collect())
+    }
+
+    pub fn apply(&self, cards: &mut HashMap<CardKind, usize>) {
+        for (card, count) in &self.0 {
+            if *count == 0 {
+                cards.remove(card);
+            } else {
+                cards.insert(*card, *count);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Changes to one player's cards on the table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerDelta {
+    pub id: usize,
+    pub public_cards: CardsDelta,
+    pub boosted_fruit_teas: CardsDelta,
+}
+
+impl PlayerDelta {
+    pub fn apply(&self, players: &mut [PlayerPublic]) {
+        if let Some(player) = players.iter_mut().find(|p| p.id == self.id) {
+            self.public_cards.apply(&mut player.public_cards);
+            self.boosted_fruit_teas.apply(&mut player.boosted_fruit_teas);
+        }
+    }
+}
+
+/// Hands and tables as they were at one state version, kept by the host
+/// until every client has acknowledged something newer
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub hands: Vec<HashMap<CardKind, usize>>,
+    pub players_public: Vec<PlayerPublic>,
+}
+
+impl Snapshot {
+    pub fn of(game: &Game) -> Self {
+        let hands = (0..game.num_players())
+            .filter_map(|player_id| game.get_player_hand(player_id).ok().cloned())
+            .collect();
+        Self { hands, players_public: game.get_players_public() }
+    }
+}
+
+/// Everything that changed between `since` and `new`. Cards that changed in
+/// any of the versions in between are included, so a client on any of them
+/// ends up with `new` after applying it.
+pub fn delta_since<'a>(
+    since: impl Iterator<Item = &'a Snapshot> + Clone,
+    new: &Snapshot,
+) -> (Vec<(usize, CardsDelta)>, Vec<PlayerDelta>) {
+    let hands = new
+        .hands
+        .iter()
+        .enumerate()
+        .map(|(seat, hand)| (seat, CardsDelta::since(since.clone().filter_map(|s| s.hands.get(seat)), hand)))
+        .filter(|(_, delta)| !delta.is_empty())
+        .collect();
+
+    let players = new
+        .players_public
+        .iter()
+        .map(|player| {
+            let old = since.clone().filter_map(|s| s.players_public.iter().find(|p| p.id == player.id));
+            PlayerDelta {
+                id: player.id,
+                public_cards: CardsDelta::since(old.clone().map(|p| &p.public_cards), &player.public_cards),
+                boosted_fruit_teas: CardsDelta::since(old.map(|p| &p.boosted_fruit_teas), &player.boosted_fruit_teas),
+            }
+        })
+        .filter(|delta| !delta.public_cards.is_empty() || !delta.boosted_fruit_teas.is_empty())
+        .collect();
+
+    (hands, players)
+}
+
+/// Snapshots by state version, from the oldest version a client may still be on
+pub type Snapshots = BTreeMap<u64, Snapshot>;
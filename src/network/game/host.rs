@@ -7,25 +7,29 @@ use libp2p::{
 use std::collections::HashMap;
 
 use crate::network::behaviour::{BobaGoBehaviour, BobaGoBehaviourEvent, ClientRequest, HostResponse};
-use crate::network::Host;
-use crate::engine::{Game, models::CardKind};
+use crate::network::{gossip, Host};
+use crate::network::chat::{self, ChatLog, ChatMessage};
+use crate::network::latency::{LatencyTracker, PlayerLatencies, PlayerLatency};
+use crate::engine::{models::CardKind, state::PlayerTurnState};
 use super::state::GameHostState;
-use super::protocol::{GameClientMessage, GameHostMessage, GameEndReason};
+use super::delta::{self, Snapshot};
+use super::protocol::{GameClientMessage, GameHostMessage, GameEndReason, GameRequestKind};
 use crate::log;
 
 impl Host<GameHostState> {
     pub fn new(
         swarm: Swarm<BobaGoBehaviour>,
         topic: IdentTopic,
-        game: Game,
-        peer_to_player_id: HashMap<PeerId, usize>,
-        player_id_to_peer: HashMap<usize, PeerId>,
+        chat: ChatLog,
+        state: GameHostState,
     ) -> Self {
-        let state = GameHostState::new(game, peer_to_player_id, player_id_to_peer);
         Self {
             swarm,
             state,
             topic,
+            chat,
+            gossip_seq: 0,
+            latency: LatencyTracker::new(),
         }
     }
     // process turn submission from a player
@@ -35,67 +39,79 @@ impl Host<GameHostState> {
         selected_cards: HashMap<CardKind, usize>,
         remaining_hand: HashMap<CardKind, usize>,
     ) -> (GameHostMessage, Option<GameHostEvent>) {
+        let reject = |reason: String| GameHostMessage::Rejected {
+            request: GameRequestKind::SubmitTurn,
+            reason,
+        };
+
         let player_id = match self.state.get_player_id(&peer) {
             Some(id) => id,
-            None => {
-                return (
-                    GameHostMessage::Error {
-                        message: "Player not found".to_string(),
-                    },
-                    None,
-                );
-            }
+            None => return (reject("Player not found".to_string()), None),
         };
 
         log::host(format!("Turn submission from player {player_id}"));
 
+        if matches!(self.state.game.get_player_turn_state(player_id), Ok(PlayerTurnState::Selected)) {
+            return (reject("Turn already submitted".to_string()), None);
+        }
+
         // validate submission
         if let Err(e) = self.state.game.validate_hand_submission(
             player_id,
             &selected_cards,
             &remaining_hand,
         ) {
-            return (
-                GameHostMessage::Error {
-                    message: format!("Invalid submission: {:?}", e),
-                },
-                None,
-            );
+            return (reject(format!("Invalid submission: {:?}", e)), None);
         }
 
         // mark player as selected
         if let Err(e) = self.state.game.mark_player_selected(player_id) {
-            return (
-                GameHostMessage::Error {
-                    message: format!("Failed to mark player: {:?}", e),
-                },
-                None,
-            );
+            return (reject(format!("Failed to mark player: {:?}", e)), None);
         }
 
         // store submission
         self.state.turn_submissions.insert(player_id, (selected_cards, remaining_hand));
+        self.broadcast_turn_progress();
+
+        let accepted = GameHostMessage::Accepted { request: GameRequestKind::SubmitTurn };
 
         // check if all players have submitted
         if self.state.game.all_players_selected() {
-            return (
-                GameHostMessage::Error {
-                    message: "Submission accepted".to_string(),
-                },
-                Some(GameHostEvent::AllPlayersSubmitted),
-            );
+            return (accepted, Some(GameHostEvent::AllPlayersSubmitted));
         }
 
-        (
-            GameHostMessage::Error {
-                message: "Submission accepted".to_string(),
-            },
-            Some(GameHostEvent::PlayerSubmitted { player_id }),
-        )
+        (accepted, Some(GameHostEvent::PlayerSubmitted { player_id }))
+    }
+
+    // move a player's drink tray back into their hand
+    fn process_drink_tray_activation(&mut self, peer: PeerId) -> GameHostMessage {
+        let reject = |reason: String| GameHostMessage::Rejected {
+            request: GameRequestKind::ActivateDrinkTray,
+            reason,
+        };
+
+        let player_id = match self.state.get_player_id(&peer) {
+            Some(id) => id,
+            None => return reject("Player not found".to_string()),
+        };
+
+        if matches!(self.state.game.get_player_turn_state(player_id), Ok(PlayerTurnState::Selected)) {
+            return reject("Turn already submitted".to_string());
+        }
+
+        if self.state.game.activate_drink_tray(player_id).is_err() {
+            return reject("No Drink Tray in your cards".to_string());
+        }
+
+        // broadcast update so client gets updated hand
+        self.broadcast_game_update();
+        GameHostMessage::Accepted { request: GameRequestKind::ActivateDrinkTray }
     }
 
     pub fn get_own_hand(&self) -> HashMap<CardKind, usize> {
-        self.state.game.get_player_hand(0).cloned().unwrap_or_default()
+        self.state.local_player_id
+            .and_then(|id| self.state.game.get_player_hand(id).ok().cloned())
+            .unwrap_or_default()
     }
 
     pub fn get_game_status(&self) -> crate::engine::state::GameStatus {
@@ -117,11 +133,13 @@ impl Host<GameHostState> {
     }
 
     pub fn submit_own_turn(&mut self, selected_cards: HashMap<CardKind, usize>, remaining_hand: HashMap<CardKind, usize>) -> Result<bool, String> {
-        self.state.game.validate_hand_submission(0, &selected_cards, &remaining_hand)
+        let player_id = self.state.local_player_id.ok_or("The host is not playing")?;
+        self.state.game.validate_hand_submission(player_id, &selected_cards, &remaining_hand)
             .map_err(|e| format!("{:?}", e))?;
-        self.state.game.mark_player_selected(0).map_err(|e| format!("{:?}", e))?;
-        self.state.turn_submissions.insert(0, (selected_cards, remaining_hand));
+        self.state.game.mark_player_selected(player_id).map_err(|e| format!("{:?}", e))?;
+        self.state.turn_submissions.insert(player_id, (selected_cards, remaining_hand));
         log::host("Host submitted turn".to_string());
+        self.broadcast_turn_progress();
 
         // Check if all players have now submitted
         let all_submitted = self.state.game.all_players_selected();
@@ -154,59 +172,115 @@ impl Host<GameHostState> {
     }
 
     pub fn broadcast_game_update(&mut self) {
-        let players_public = self.state.game.get_players_public();
+        let snapshot = Snapshot::of(&self.state.game);
         let game_status = self.state.game.get_game_status();
-
-        // collect all hands
-        let mut all_hands = Vec::new();
-        for player_id in 0..self.state.game.num_players() {
-            if let Ok(hand) = self.state.game.get_player_hand(player_id) {
-                all_hands.push(hand.clone());
+        let base = self.state.delta_base();
+        self.state.state_version += 1;
+        let state_version = self.state.state_version;
+
+        // everything until all players have acknowledged an update, only the changes after that
+        let message = match base {
+            Some(base_version) => {
+                let since = self.state.snapshots.range(base_version..).map(|(_, snapshot)| snapshot);
+                let (hands, players) = delta::delta_since(since, &snapshot);
+                GameHostMessage::GameDelta { base_version, state_version, hands, players, game_status }
             }
-        }
+            None => GameHostMessage::GameUpdate {
+                all_hands: snapshot.hands.clone(),
+                players_public: snapshot.players_public.clone(),
+                game_status,
+                state_version,
+            },
+        };
+        self.state.record_snapshot(state_version, snapshot);
 
-        let message = GameHostMessage::GameUpdate {
-            all_hands,
-            players_public,
-            game_status,
+        let kind = match base {
+            Some(base_version) => format!("delta from version {base_version}"),
+            None => "full update".to_string(),
         };
+        match self.publish_host_message(message) {
+            Ok(_) => log::host(format!("Published version {state_version} as {kind}")),
+            Err(e) => log::host(format!("Failed to publish version {state_version}: {e}")),
+        }
+    }
 
-        if let Ok(json) = serde_json::to_string(&message) {
-            log::host(format!("Publishing GameUpdate on topic: {:?}", self.topic));
-            log::host(format!("GameUpdate message: {}", json));
-            match self.swarm
-                .behaviour_mut()
-                .gossipsub
-                .publish(self.topic.clone(), json.as_bytes()) {
-                    Ok(msg_id) => log::host(format!("Published GameUpdate with id: {:?}", msg_id)),
-                    Err(e) => log::host(format!("Failed to publish GameUpdate: {:?}", e)),
-                }
+    /// Round trip to the player in a seat, None for our own seat and until the first ping
+    pub fn player_latency(&self, player_id: usize) -> Option<PlayerLatency> {
+        self.state.get_peer_id(player_id).and_then(|peer| self.latency.latency(peer))
+    }
+
+    // send everyone the latency of every seat we've measured
+    fn broadcast_latency(&mut self) {
+        let mut players: Vec<(usize, PlayerLatency)> = self
+            .state
+            .player_id_to_peer
+            .iter()
+            .filter_map(|(player_id, peer)| Some((*player_id, self.latency.latency(peer)?)))
+            .collect();
+        players.sort_by_key(|(player_id, _)| *player_id);
+        self.publish_host_message(GameHostMessage::Latency { players }).ok();
+    }
+
+    // let everyone see who we're still waiting on
+    fn broadcast_turn_progress(&mut self) {
+        let status = self.state.game.get_game_status();
+        let mut submitted: Vec<usize> = self.state.turn_submissions.keys().copied().collect();
+        submitted.sort_unstable();
+        let message = GameHostMessage::TurnProgress { round: status.round, turn: status.turn, submitted };
+        if let Err(e) = self.publish_host_message(message) {
+            log::host(format!("Failed to publish turn progress: {e}"));
         }
     }
 
-    fn broadcast_game_ended(&mut self, reason: GameEndReason) -> GameHostEvent {
+    fn final_scores(&self) -> Vec<(usize, f32, String, crate::engine::ScoreBreakdown)> {
         let mut final_scores = Vec::new();
-
         for player in self.state.game.get_players_public() {
             if let Ok((score, breakdown)) = self.state.game.calculate_player_score(player.id) {
                 final_scores.push((player.id, score, player.name, breakdown));
             }
         }
+        final_scores
+    }
 
-        let message = GameHostMessage::GameEnded {
-            final_scores: final_scores.clone(),
-            reason: reason.clone(),
-        };
+    fn game_ended_message(&self, reason: GameEndReason) -> GameHostMessage {
+        GameHostMessage::GameEnded {
+            final_scores: self.final_scores(),
+            reason,
+            fairness: self.state.fair_dealing.as_ref().map(|f| f.reveal(&self.state.game)),
+        }
+    }
 
-        if let Ok(json) = serde_json::to_string(&message) {
-            self.swarm
-                .behaviour_mut()
-                .gossipsub
-                .publish(self.topic.clone(), json.as_bytes())
-                .ok();
+    fn broadcast_game_ended(&mut self, reason: GameEndReason) -> GameHostEvent {
+        self.state.ended = Some(reason.clone());
+        let message = self.game_ended_message(reason.clone());
+        if let Err(e) = self.publish_host_message(message) {
+            log::host(format!("Failed to publish GameEnded: {e}"));
         }
 
-        GameHostEvent::GameEnded { final_scores, reason }
+        GameHostEvent::GameEnded { final_scores: self.final_scores(), reason }
+    }
+
+    // current state for a player who missed updates, or the result if it's over
+    fn process_sync_request(&mut self, peer: PeerId) -> GameHostMessage {
+        let Some(player_id) = self.state.get_player_id(&peer) else {
+            return GameHostMessage::Rejected {
+                request: GameRequestKind::Sync,
+                reason: "Player not found".to_string(),
+            };
+        };
+        log::host(format!("Resync requested by player {player_id}"));
+
+        if let Some(reason) = &self.state.ended {
+            return self.game_ended_message(reason.clone());
+        }
+        // what we're about to send is as good as an acknowledgement
+        self.state.acknowledge(player_id, self.state.state_version);
+        GameHostMessage::SyncState {
+            hand: self.state.game.get_player_hand(player_id).cloned().unwrap_or_default(),
+            players_public: self.state.game.get_players_public(),
+            game_status: self.state.game.get_game_status(),
+            state_version: self.state.state_version,
+        }
     }
 
     fn handle_request_response(
@@ -224,7 +298,10 @@ impl Host<GameHostState> {
                         ..
                     } => {
                         match game_msg {
-                            GameClientMessage::SubmitTurn { selected_cards, remaining_hand } => {
+                            GameClientMessage::SubmitTurn { selected_cards, remaining_hand, state_version } => {
+                                if let Some(player_id) = self.state.get_player_id(&peer) {
+                                    self.state.acknowledge(player_id, state_version);
+                                }
                                 let (response, event) = self.process_turn_submission(peer, selected_cards, remaining_hand);
                                 self.swarm
                                     .behaviour_mut()
@@ -234,48 +311,20 @@ impl Host<GameHostState> {
                                 return event;
                             }
                             GameClientMessage::ActivateDrinkTray => {
-                                let player_id = match self.state.get_player_id(&peer) {
-                                    Some(id) => id,
-                                    None => {
-                                        self.swarm
-                                            .behaviour_mut()
-                                            .request_response
-                                            .send_response(channel, HostResponse::Game(GameHostMessage::Error {
-                                                message: "Player not found".to_string(),
-                                            }))
-                                            .ok();
-                                        return None;
-                                    }
-                                };
-
-                                if let Some(player) = self.state.game.players.get_mut(player_id) {
-                                    if let Some(drink_tray_count) = player.public_cards.get_mut(&CardKind::DrinkTray) {
-                                        *drink_tray_count -= 1;
-                                        if *drink_tray_count == 0 {
-                                            player.public_cards.remove(&CardKind::DrinkTray);
-                                        }
-                                        *player.hand.entry(CardKind::DrinkTray).or_insert(0) += 1;
-
-                                        // broadcast update so client gets updated hand
-                                        self.broadcast_game_update();
-
-                                        self.swarm
-                                            .behaviour_mut()
-                                            .request_response
-                                            .send_response(channel, HostResponse::Game(GameHostMessage::Error {
-                                                message: "DrinkTray activated".to_string(),
-                                            }))
-                                            .ok();
-                                    } else {
-                                        self.swarm
-                                            .behaviour_mut()
-                                            .request_response
-                                            .send_response(channel, HostResponse::Game(GameHostMessage::Error {
-                                                message: "No DrinkTray in public cards".to_string(),
-                                            }))
-                                            .ok();
-                                    }
-                                }
+                                let response = self.process_drink_tray_activation(peer);
+                                self.swarm
+                                    .behaviour_mut()
+                                    .request_response
+                                    .send_response(channel, HostResponse::Game(response))
+                                    .ok();
+                            }
+                            GameClientMessage::RequestSync => {
+                                let response = self.process_sync_request(peer);
+                                self.swarm
+                                    .behaviour_mut()
+                                    .request_response
+                                    .send_response(channel, HostResponse::Game(response))
+                                    .ok();
                             }
                         }
                     }
@@ -289,6 +338,7 @@ impl Host<GameHostState> {
 
     fn handle_connection_closed(&mut self, peer_id: PeerId) -> Option<GameHostEvent> {
         super::super::events::log_host_connection_closed(peer_id);
+        self.latency.remove(&peer_id);
         if let Some(player_id) = self.state.remove_player(&peer_id) {
             let event = self.broadcast_game_ended(GameEndReason::PlayerDisconnected { player_id });
             return Some(event);
@@ -304,10 +354,30 @@ impl Host<GameHostState> {
                         return Some(event);
                     }
                 }
+                SwarmEvent::Behaviour(BobaGoBehaviourEvent::Gossipsub(
+                    libp2p::gossipsub::Event::Message { message, message_id, propagation_source }
+                )) => {
+                    let acceptance = gossip::validate_host_gossip(&message, *self.swarm.local_peer_id(), &self.chat);
+                    if !gossip::report(&mut self.swarm, &message_id, &propagation_source, acceptance) {
+                        log::host(format!("Dropped gossip published by {:?} via {propagation_source}", message.source));
+                        continue;
+                    }
+                    if let Some(gossip) = chat::parse_chat_gossip(&message.data)
+                        && let Some(message) = self.chat.handle_gossip(gossip, message.source, false)
+                    {
+                        return Some(GameHostEvent::ChatReceived { message });
+                    }
+                }
+                SwarmEvent::Behaviour(BobaGoBehaviourEvent::Ping(event)) => {
+                    let report_due = self.handle_ping(event);
+                    if report_due {
+                        self.broadcast_latency();
+                    }
+                }
                 SwarmEvent::ConnectionEstablished { peer_id, .. } => {
                     super::super::events::handle_host_connection_established(&mut self.swarm, peer_id);
                 }
-                SwarmEvent::ConnectionClosed { peer_id, .. } => {
+                SwarmEvent::ConnectionClosed { peer_id, num_established: 0, .. } => {
                     if let Some(event) = self.handle_connection_closed(peer_id) {
                         return Some(event);
                     }
@@ -324,9 +394,10 @@ pub enum GameHostEvent {
     AllPlayersSubmitted,
     PlayerDisconnected { peer_id: PeerId, player_id: usize },
     GameEnded { final_scores: Vec<(usize, f32, String, crate::engine::ScoreBreakdown)>, reason: GameEndReason },
+    ChatReceived { message: ChatMessage },
 }
 
-impl crate::tui::GameInterface for Host<GameHostState> {
+impl crate::engine::GameInterface for Host<GameHostState> {
     fn get_hand(&self) -> HashMap<CardKind, usize> {
         self.get_own_hand()
     }
@@ -344,23 +415,18 @@ impl crate::tui::GameInterface for Host<GameHostState> {
     }
 
     fn get_player_id(&self) -> usize {
-        0
+        self.state.local_player_id.unwrap_or(0)
     }
 
     fn activate_drink_tray(&mut self) -> Result<(), String> {
-        if let Some(player) = self.state.game.players.get_mut(0) {
-            if let Some(drink_tray_count) = player.public_cards.get_mut(&CardKind::DrinkTray) {
-                *drink_tray_count -= 1;
-                if *drink_tray_count == 0 {
-                    player.public_cards.remove(&CardKind::DrinkTray);
-                }
-                *player.hand.entry(CardKind::DrinkTray).or_insert(0) += 1;
-                Ok(())
-            } else {
-                Err("No DrinkTray in public cards".to_string())
-            }
-        } else {
-            Err("Invalid player id".to_string())
-        }
+        let player_id = self.state.local_player_id.ok_or("The host is not playing")?;
+        self.state.game.activate_drink_tray(player_id)
+            .map_err(|_| "No DrinkTray in public cards".to_string())
+    }
+}
+
+impl PlayerLatencies for Host<GameHostState> {
+    fn player_latency(&self, player_id: usize) -> Option<PlayerLatency> {
+        Host::<GameHostState>::player_latency(self, player_id)
     }
 }
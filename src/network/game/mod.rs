@@ -2,9 +2,11 @@ pub mod protocol;
 pub mod state;
 pub mod client;
 pub mod host;
+pub mod delta;
 
-pub use state::GameHostState;
+pub use state::{GameHostState, FairDealing};
 pub use client::{GameClientState, GameClientEvent};
 pub use host::GameHostEvent;
-pub use protocol::{GameClientMessage, GameHostMessage, GameEndReason};
+pub use delta::{CardsDelta, PlayerDelta};
+pub use protocol::{GameClientMessage, GameHostMessage, GameEndReason, GameRequestKind};
 
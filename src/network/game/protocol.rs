@@ -2,9 +2,12 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use crate::engine::{
+    fairness::FairnessReveal,
     models::{CardKind, PlayerPublic},
     state::GameStatus,
 };
+use crate::network::latency::PlayerLatency;
+use super::delta::{CardsDelta, PlayerDelta};
 
 // messages from client to host
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,9 +16,14 @@ pub enum GameClientMessage {
     SubmitTurn {
         selected_cards: HashMap<CardKind, usize>,
         remaining_hand: HashMap<CardKind, usize>,
+        // the last update we applied, so the host knows what to send deltas against
+        #[serde(default)]
+        state_version: u64,
     },
     // activate drink tray (move from public_cards to hand)
     ActivateDrinkTray,
+    // ask for the current state after missing updates
+    RequestSync,
 }
 
 // messages from host to client
@@ -26,19 +34,67 @@ pub enum GameHostMessage {
         all_hands: Vec<HashMap<CardKind, usize>>, // indexed by player_id
         players_public: Vec<PlayerPublic>,
         game_status: GameStatus,
+        // bumped with every update, a gap means we missed one
+        #[serde(default)]
+        state_version: u64,
+    },
+
+    // what changed since `base_version`, sent once every player has acknowledged
+    // it. Clients on any version from there on can apply it, older ones resync.
+    GameDelta {
+        base_version: u64,
+        state_version: u64,
+        hands: Vec<(usize, CardsDelta)>, // (player_id, changes), unchanged hands are left out
+        players: Vec<PlayerDelta>,
+        game_status: GameStatus,
+    },
+
+    // who has picked so far this turn, never what they picked
+    TurnProgress {
+        round: usize,
+        turn: usize,
+        submitted: Vec<usize>,
+    },
+
+    // round trips the host measured to each seat
+    Latency {
+        players: Vec<(usize, PlayerLatency)>,
+    },
+
+    // reply to RequestSync, everything a client needs to catch up
+    SyncState {
+        hand: HashMap<CardKind, usize>,
+        players_public: Vec<PlayerPublic>,
+        game_status: GameStatus,
+        state_version: u64,
     },
 
     GameEnded {
         final_scores: Vec<(usize, f32, String, crate::engine::ScoreBreakdown)>, // (player_id, score, name, breakdown)
         reason: GameEndReason,
+        // seed, entropy and turn history so clients can replay the deal
+        #[serde(default)]
+        fairness: Option<FairnessReveal>,
     },
 
-    // error response
-    Error {
-        message: String,
+    // request-response acknowledgements
+    Accepted {
+        request: GameRequestKind,
+    },
+    Rejected {
+        request: GameRequestKind,
+        reason: String,
     },
 }
 
+// which client request an acknowledgement refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameRequestKind {
+    SubmitTurn,
+    ActivateDrinkTray,
+    Sync,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum GameEndReason {
     Completed,
@@ -2,27 +2,101 @@ use std::collections::HashMap;
 use libp2p::PeerId;
 
 use crate::engine::Game;
+use crate::engine::fairness::{self, Commitment, Entropy, FairnessReveal};
 use crate::engine::models::CardKind;
+use super::protocol::GameEndReason;
+use super::delta::{Snapshot, Snapshots};
+
+/// Entropy collected in the lobby when fair dealing is enabled
+#[derive(Debug, Clone)]
+pub struct FairDealing {
+    pub host_entropy: Entropy,
+    pub contributions: Vec<(usize, Entropy)>,
+}
+
+impl FairDealing {
+    pub fn new(host_entropy: Entropy, contributions: Vec<(usize, Entropy)>) -> Self {
+        Self { host_entropy, contributions }
+    }
+
+    /// Seed for the game, derived from everyone's entropy
+    pub fn seed(&self) -> u64 {
+        fairness::combine_seed(&self.host_entropy, &self.contributions)
+    }
+
+    pub fn seed_commitment(&self) -> Commitment {
+        fairness::commit_seed(self.seed(), &self.host_entropy)
+    }
+
+    pub fn reveal(&self, game: &Game) -> FairnessReveal {
+        FairnessReveal::from_game(game, self.host_entropy, self.contributions.clone())
+    }
+}
 
 pub struct GameHostState {
     pub game: Game,
+    // None when a dedicated server runs the game without playing
+    pub local_player_id: Option<usize>,
     pub peer_to_player_id: HashMap<PeerId, usize>,
     pub player_id_to_peer: HashMap<usize, PeerId>,
     pub turn_submissions: HashMap<usize, (HashMap<CardKind, usize>, HashMap<CardKind, usize>)>,
+    pub fair_dealing: Option<FairDealing>,
+    // version of the last update sent to clients
+    pub state_version: u64,
+    // set once the game is over, so late resyncs get the final scores
+    pub ended: Option<GameEndReason>,
+    // latest version each player has told us they applied
+    pub acked: HashMap<usize, u64>,
+    // what we sent at each version still needed as a delta base
+    pub snapshots: Snapshots,
 }
 
 impl GameHostState {
     pub fn new(
         game: Game,
+        local_player_id: Option<usize>,
         peer_to_player_id: HashMap<PeerId, usize>,
         player_id_to_peer: HashMap<usize, PeerId>,
+        fair_dealing: Option<FairDealing>,
     ) -> Self {
         Self {
             game,
+            local_player_id,
             peer_to_player_id,
             player_id_to_peer,
             turn_submissions: HashMap::new(),
+            fair_dealing,
+            state_version: 0,
+            ended: None,
+            acked: HashMap::new(),
+            snapshots: Snapshots::new(),
+        }
+    }
+
+    /// Note that a player has applied `state_version`, acknowledgements only move forward
+    pub fn acknowledge(&mut self, player_id: usize, state_version: u64) {
+        let acked = self.acked.entry(player_id).or_default();
+        *acked = (*acked).max(state_version);
+    }
+
+    /// Oldest version a connected player is on, None while someone hasn't
+    /// acknowledged anything we still have a snapshot of
+    pub fn delta_base(&self) -> Option<u64> {
+        let base = self
+            .player_id_to_peer
+            .keys()
+            .map(|player_id| self.acked.get(player_id).copied())
+            .min()
+            .flatten()?;
+        self.snapshots.contains_key(&base).then_some(base)
+    }
+
+    /// Remember what went out at `state_version`, forgetting what nobody needs anymore
+    pub fn record_snapshot(&mut self, state_version: u64, snapshot: Snapshot) {
+        if let Some(base) = self.delta_base() {
+            self.snapshots = self.snapshots.split_off(&base);
         }
+        self.snapshots.insert(state_version, snapshot);
     }
 
     pub fn get_player_id(&self, peer: &PeerId) -> Option<usize> {
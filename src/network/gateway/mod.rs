@@ -0,0 +1,138 @@
+//! WebSocket gateway for players that can't run libp2p, like browsers or
+//! test scripts. Every connection gets its own lobby client dialing the
+//! host, the gateway translates between that client and JSON text frames
+//! (see `protocol`).
+
+pub mod protocol;
+mod session;
+
+pub use protocol::{GatewayEvent, GatewayRequest, GatewayScore};
+
+use libp2p::Multiaddr;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+use crate::engine::constants;
+use crate::log;
+use crate::network::swarm::is_relayed;
+
+/// Set to a socket address like `127.0.0.1:4180` to open a gateway for the TUI host
+pub const GATEWAY_ENV: &str = "BOBA_GATEWAY";
+
+/// Seats the room has left, kept up to date by whoever runs the host.
+/// The gateway only lets that many players at a time be on their way in.
+#[derive(Debug, Clone)]
+pub struct FreeSeats(Arc<AtomicUsize>);
+
+impl FreeSeats {
+    pub fn set(&self, seats: usize) {
+        self.0.store(seats, Ordering::Relaxed);
+    }
+
+    fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+// a connection that hasn't joined the lobby yet, counted against the free seats until dropped
+struct SeatClaim(Arc<AtomicUsize>);
+
+impl Drop for SeatClaim {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+pub struct Gateway {
+    listener: TcpListener,
+    // sessions dial over libp2p's memory transport, for tests
+    memory: bool,
+    free_seats: FreeSeats,
+    joining: Arc<AtomicUsize>,
+}
+
+impl Gateway {
+    pub async fn bind(address: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(address).await?;
+        Ok(Self {
+            listener,
+            memory: false,
+            free_seats: FreeSeats(Arc::new(AtomicUsize::new(constants::max_players()))),
+            joining: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
+    /// Handle for the host's loop to report how many seats are left
+    pub fn free_seats(&self) -> FreeSeats {
+        self.free_seats.clone()
+    }
+
+    // a place among the players on their way in, None when they would already fill the room
+    fn claim_seat(&self) -> Option<SeatClaim> {
+        let free = self.free_seats.get();
+        self.joining
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |joining| (joining < free).then_some(joining + 1))
+            .ok()
+            .map(|_| SeatClaim(self.joining.clone()))
+    }
+
+    /// Gateway address from `BOBA_GATEWAY`, if set
+    pub fn address_from_env() -> Option<String> {
+        std::env::var(GATEWAY_ENV).ok().filter(|address| !address.trim().is_empty())
+    }
+
+    /// Dial the host over the memory transport, only useful in tests
+    pub fn with_memory_transport(mut self) -> Self {
+        self.memory = true;
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accept connections forever, every player dials the host at `host`.
+    /// Connections beyond the free seats are turned away before they cost a swarm.
+    pub async fn run(self, host: Multiaddr) {
+        loop {
+            let (stream, peer) = match self.listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    log::gateway(format!("Accept failed: {e}"));
+                    continue;
+                }
+            };
+            let host = host.clone();
+            let memory = self.memory;
+            let seat = self.claim_seat();
+            tokio::spawn(async move {
+                let socket = match tokio_tungstenite::accept_async(stream).await {
+                    Ok(socket) => socket,
+                    Err(e) => return log::gateway(format!("WebSocket handshake with {peer} failed: {e}")),
+                };
+                match seat {
+                    Some(seat) => session::run(socket, host, memory, seat).await,
+                    None => {
+                        log::gateway(format!("Turning away {peer}, the room is full"));
+                        session::turn_away(socket, "The room is full").await;
+                    }
+                }
+            });
+        }
+    }
+
+    /// Run on its own task
+    pub fn spawn(self, host: Multiaddr) -> JoinHandle<()> {
+        tokio::spawn(self.run(host))
+    }
+}
+
+/// Whether the gateway can reach the host at a listen address, relayed
+/// addresses would send every player through the relay for nothing
+pub fn can_dial(address: &Multiaddr) -> bool {
+    !is_relayed(address)
+}
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::{CardKind, GameSettings, GameStatus, PlayerPublic};
+use crate::network::{ChatMessage, LobbyPlayer};
+
+/// What a gateway client sends, one JSON object per text frame, e.g.
+/// `{"type": "submit_turn", "selected": {"TapiocaPearl": 1}}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GatewayRequest {
+    // has to come first, everything else needs a seat
+    Join {
+        name: String,
+        #[serde(default)]
+        password: Option<String>,
+    },
+    // answer to `password_required`
+    Password { password: String },
+    SetReady { ready: bool },
+    // the rest of the hand is worked out by the gateway
+    SubmitTurn { selected: HashMap<CardKind, usize> },
+    ActivateDrinkTray,
+    Chat { text: String },
+}
+
+/// Final score of one player
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GatewayScore {
+    pub player_id: usize,
+    pub name: String,
+    pub score: f32,
+}
+
+/// What the gateway sends back, tagged the same way
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GatewayEvent {
+    Joined { player_id: usize, players: Vec<LobbyPlayer> },
+    Rejected { reason: String },
+    PasswordRequired { reason: Option<String> },
+    Lobby {
+        players: Vec<LobbyPlayer>,
+        countdown: Option<u64>,
+        settings: GameSettings,
+    },
+    // everything the player can see, sent whenever any of it may have changed
+    GameState {
+        player_id: usize,
+        hand: HashMap<CardKind, usize>,
+        status: GameStatus,
+        players: Vec<PlayerPublic>,
+    },
+    GameOver { scores: Vec<GatewayScore> },
+    Chat { message: ChatMessage },
+    // a request that couldn't be carried out, the session goes on
+    Error { message: String },
+    // the session is over, the socket is closed after this
+    Disconnected { reason: String },
+}
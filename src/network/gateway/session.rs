@@ -0,0 +1,338 @@
+use std::collections::HashMap;
+use libp2p::futures::{SinkExt, StreamExt};
+use libp2p::identity::Keypair;
+use libp2p::Multiaddr;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+use crate::engine::CardKind;
+use crate::log;
+use crate::network::swarm::{build_dial_swarm, build_memory_swarm, TransportKind};
+use crate::network::{
+    lobby_to_game_client, lobby_to_hostless_client, ChatMessage, Client, ClientEvent, GameClientEvent,
+    GameClientState, HostlessGameState, LobbyClientState, Peer, PeerEvent,
+};
+use crate::engine::GameInterface;
+use super::protocol::{GatewayEvent, GatewayRequest, GatewayScore};
+use super::SeatClaim;
+
+/// One WebSocket connection
+struct Socket {
+    ws: WebSocketStream<TcpStream>,
+}
+
+impl Socket {
+    // false once the other side is gone
+    async fn send(&mut self, event: &GatewayEvent) -> bool {
+        let text = match serde_json::to_string(event) {
+            Ok(text) => text,
+            Err(e) => {
+                log::gateway(format!("Could not encode {event:?}: {e}"));
+                return true;
+            }
+        };
+        self.ws.send(Message::text(text)).await.is_ok()
+    }
+
+    async fn error(&mut self, message: impl Into<String>) -> bool {
+        self.send(&GatewayEvent::Error { message: message.into() }).await
+    }
+
+    /// Say why the session ends and close the socket
+    async fn close(mut self, reason: impl Into<String>) {
+        self.send(&GatewayEvent::Disconnected { reason: reason.into() }).await;
+        self.ws.close(None).await.ok();
+    }
+
+    /// Next request, an error for frames that aren't one, None once the socket is closed
+    async fn next_request(&mut self) -> Option<Result<GatewayRequest, String>> {
+        loop {
+            match self.ws.next().await? {
+                Ok(Message::Text(text)) => {
+                    return Some(serde_json::from_str(&text).map_err(|e| format!("Invalid request: {e}")));
+                }
+                Ok(Message::Binary(_)) => return Some(Err("Requests are JSON text frames".to_string())),
+                Ok(Message::Close(_)) | Err(_) => return None,
+                // pings are answered by tungstenite
+                Ok(_) => {}
+            }
+        }
+    }
+}
+
+/// How a game went on, in the gateway's terms
+enum GameUpdate {
+    Changed,
+    Rejected(String),
+    Chat(ChatMessage),
+    Over(Vec<GatewayScore>),
+    Ended(String),
+}
+
+/// The two kinds of game a lobby client can end up in
+trait GatewayGame: GameInterface {
+    async fn next_update(&mut self) -> GameUpdate;
+    fn send_chat(&mut self, text: &str) -> Result<(), String>;
+}
+
+impl GatewayGame for Client<GameClientState> {
+    async fn next_update(&mut self) -> GameUpdate {
+        loop {
+            let Some(event) = self.next_event().await else {
+                return GameUpdate::Ended("Lost connection to the host".to_string());
+            };
+            return match event {
+                GameClientEvent::GameUpdated { .. } | GameClientEvent::TurnProgress { .. } => GameUpdate::Changed,
+                GameClientEvent::RequestAccepted { .. } => continue,
+                GameClientEvent::RequestRejected { reason, .. } => GameUpdate::Rejected(reason),
+                GameClientEvent::ChatReceived { message } => GameUpdate::Chat(message),
+                GameClientEvent::GameEnded { final_scores, .. } => GameUpdate::Over(
+                    final_scores
+                        .into_iter()
+                        .map(|(player_id, score, name, _)| GatewayScore { player_id, name, score })
+                        .collect(),
+                ),
+                GameClientEvent::Disconnected => GameUpdate::Ended("Lost connection to the host".to_string()),
+            };
+        }
+    }
+
+    fn send_chat(&mut self, text: &str) -> Result<(), String> {
+        Client::send_chat(self, text)
+    }
+}
+
+impl GatewayGame for Peer<HostlessGameState> {
+    async fn next_update(&mut self) -> GameUpdate {
+        let Some(event) = self.next_event().await else {
+            return GameUpdate::Ended("Lost connection to the other players".to_string());
+        };
+        match event {
+            PeerEvent::PlayerCommitted { .. } | PeerEvent::TurnResolved => GameUpdate::Changed,
+            PeerEvent::ChatReceived { message } => GameUpdate::Chat(message),
+            PeerEvent::GameOver => GameUpdate::Over(
+                self.get_score_breakdowns()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(player_id, (name, breakdown))| GatewayScore { player_id, name, score: breakdown.total_score })
+                    .collect(),
+            ),
+            PeerEvent::Aborted { reason } => GameUpdate::Ended(reason),
+        }
+    }
+
+    fn send_chat(&mut self, text: &str) -> Result<(), String> {
+        Peer::send_chat(self, text)
+    }
+}
+
+/// Serve one player: join the host at `host` on their behalf, then relay
+/// lobby and game between the socket and the network until either side ends
+pub(super) async fn run(ws: WebSocketStream<TcpStream>, host: Multiaddr, memory: bool, seat: SeatClaim) {
+    let mut socket = Socket { ws };
+    // held until we are in the lobby, the lobby counts us from then on
+    let mut seat = Some(seat);
+
+    let (name, password) = loop {
+        match socket.next_request().await {
+            None => return,
+            Some(Ok(GatewayRequest::Join { name, password })) => break (name, password),
+            Some(Ok(_)) => {
+                if !socket.error("Send a join request first").await {
+                    return;
+                }
+            }
+            Some(Err(e)) => {
+                if !socket.error(e).await {
+                    return;
+                }
+            }
+        }
+    };
+
+    let mut client = match lobby_client(name, &host, memory).await {
+        Ok(client) => client,
+        Err(e) => return socket.close(e).await,
+    };
+    if let Some(password) = password {
+        client.submit_password(password);
+    }
+    if let Err(e) = client.join(&host.to_string()).map_err(|e| e.to_string()) {
+        return socket.close(format!("Could not reach the host: {e}")).await;
+    }
+    log::gateway(format!("{} joining as {}", client.local_peer_id(), host));
+
+    let start = loop {
+        tokio::select! {
+            event = client.next_event() => {
+                let Some(event) = event else {
+                    return socket.close("Lost connection to the host").await;
+                };
+                let open = match event {
+                    ClientEvent::JoinedLobby { player_id, lobby_players } => {
+                        drop(seat.take());
+                        socket.send(&GatewayEvent::Joined { player_id, players: lobby_players }).await
+                            && socket.send(&lobby_event(&client)).await
+                    }
+                    ClientEvent::JoinRejected { reason } => {
+                        socket.send(&GatewayEvent::Rejected { reason: reason.clone() }).await;
+                        return socket.close(reason).await;
+                    }
+                    ClientEvent::PasswordRequired { reason } => {
+                        socket.send(&GatewayEvent::PasswordRequired { reason }).await
+                    }
+                    ClientEvent::Kicked { reason } => return socket.close(reason).await,
+                    ClientEvent::LobbyUpdated { .. } | ClientEvent::SettingsUpdated { .. } => {
+                        socket.send(&lobby_event(&client)).await
+                    }
+                    ClientEvent::ChatReceived { message } => socket.send(&GatewayEvent::Chat { message }).await,
+                    ClientEvent::Error { message } => socket.error(message).await,
+                    ClientEvent::Disconnected => return socket.close("Lost connection to the host").await,
+                    start @ (ClientEvent::GameStarting { .. } | ClientEvent::HostlessStarting { .. }) => break start,
+                };
+                if !open {
+                    return;
+                }
+            }
+            request = socket.next_request() => {
+                let Some(request) = request else {
+                    return;
+                };
+                let error = match request {
+                    Ok(GatewayRequest::SetReady { ready }) => {
+                        client.set_ready(ready);
+                        None
+                    }
+                    Ok(GatewayRequest::Password { password }) => {
+                        client.submit_password(password);
+                        None
+                    }
+                    Ok(GatewayRequest::Chat { text }) => client.send_chat(&text).err(),
+                    Ok(GatewayRequest::Join { .. }) => Some("Already joined".to_string()),
+                    Ok(GatewayRequest::SubmitTurn { .. } | GatewayRequest::ActivateDrinkTray) => {
+                        Some("The game hasn't started yet".to_string())
+                    }
+                    Err(e) => Some(e),
+                };
+                if let Some(message) = error && !socket.error(message).await {
+                    return;
+                }
+            }
+        }
+    };
+
+    match start {
+        ClientEvent::GameStarting { all_hands, players_public, game_status } => {
+            let Some(seat) = client.game_seat() else {
+                return socket.close("No seat in this game").await;
+            };
+            let hand = all_hands.get(seat).cloned().unwrap_or_default();
+            let game = lobby_to_game_client(client, seat, hand, players_public, game_status);
+            play(socket, game).await;
+        }
+        ClientEvent::HostlessStarting { seats, config } => match lobby_to_hostless_client(client, seats, config) {
+            Ok(peer) => play(socket, peer).await,
+            Err(e) => socket.close(format!("Could not start the game: {e:?}")).await,
+        },
+        _ => unreachable!(),
+    }
+}
+
+/// Tell a connection there is no room for it and close it
+pub(super) async fn turn_away(ws: WebSocketStream<TcpStream>, reason: &str) {
+    Socket { ws }.close(reason).await;
+}
+
+// a fresh identity for every connection, web players don't have one of their own
+async fn lobby_client(name: String, host: &Multiaddr, memory: bool) -> Result<Client<LobbyClientState>, String> {
+    let key = Keypair::generate_ed25519();
+    let swarm = if memory {
+        build_memory_swarm(key).map_err(|e| e.to_string())?
+    } else {
+        let transport = TransportKind::of_address(host).ok_or(format!("Can't dial {host}"))?;
+        build_dial_swarm(key, transport).await.map_err(|e| e.to_string())?
+    };
+    Client::<LobbyClientState>::with_swarm(swarm, name)
+        .map_err(|e| format!("Could not join {host}: {e}"))
+}
+
+fn lobby_event(client: &Client<LobbyClientState>) -> GatewayEvent {
+    GatewayEvent::Lobby {
+        players: client.get_lobby_players(),
+        countdown: client.countdown(),
+        settings: client.settings().clone(),
+    }
+}
+
+fn game_event(game: &impl GameInterface) -> GatewayEvent {
+    GatewayEvent::GameState {
+        player_id: game.get_player_id(),
+        hand: game.get_hand(),
+        status: game.get_game_status(),
+        players: game.get_players_public(),
+    }
+}
+
+// the hand minus the picked cards, or why the pick isn't in the hand
+fn remaining_hand(
+    hand: &HashMap<CardKind, usize>,
+    selected: &HashMap<CardKind, usize>,
+) -> Result<HashMap<CardKind, usize>, String> {
+    let mut remaining = hand.clone();
+    for (card, count) in selected {
+        let held = remaining.get_mut(card).filter(|held| **held >= *count);
+        let Some(held) = held else {
+            return Err(format!("Not enough {card:?} in your hand"));
+        };
+        *held -= count;
+    }
+    remaining.retain(|_, count| *count > 0);
+    Ok(remaining)
+}
+
+async fn play(mut socket: Socket, mut game: impl GatewayGame) {
+    if !socket.send(&game_event(&game)).await {
+        return;
+    }
+    loop {
+        tokio::select! {
+            update = game.next_update() => {
+                let open = match update {
+                    GameUpdate::Changed => socket.send(&game_event(&game)).await,
+                    GameUpdate::Rejected(reason) => {
+                        socket.error(reason).await && socket.send(&game_event(&game)).await
+                    }
+                    GameUpdate::Chat(message) => socket.send(&GatewayEvent::Chat { message }).await,
+                    GameUpdate::Over(scores) => {
+                        socket.send(&game_event(&game)).await;
+                        socket.send(&GatewayEvent::GameOver { scores }).await;
+                        return socket.close("Game over").await;
+                    }
+                    GameUpdate::Ended(reason) => return socket.close(reason).await,
+                };
+                if !open {
+                    return;
+                }
+            }
+            request = socket.next_request() => {
+                let Some(request) = request else {
+                    return;
+                };
+                let result = match request {
+                    Ok(GatewayRequest::SubmitTurn { selected }) => remaining_hand(&game.get_hand(), &selected)
+                        .and_then(|remaining| game.submit_turn(selected, remaining)),
+                    Ok(GatewayRequest::ActivateDrinkTray) => game.activate_drink_tray(),
+                    Ok(GatewayRequest::Chat { text }) => game.send_chat(&text),
+                    Ok(GatewayRequest::Join { .. } | GatewayRequest::Password { .. } | GatewayRequest::SetReady { .. }) => {
+                        Err("The game has already started".to_string())
+                    }
+                    Err(e) => Err(e),
+                };
+                if let Err(message) = result && !socket.error(message).await {
+                    return;
+                }
+            }
+        }
+    }
+}
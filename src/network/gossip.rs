@@ -0,0 +1,118 @@
+use libp2p::gossipsub::{self, IdentTopic, MessageAcceptance, MessageId};
+use libp2p::{PeerId, Swarm};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::network::behaviour::BobaGoBehaviour;
+use crate::network::chat::{self, ChatGossip, ChatLog};
+use crate::network::wire;
+
+/// Everything the host publishes goes out in this envelope. The sequence
+/// number only ever goes up, so clients can drop anything they've seen before.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostGossip<M> {
+    pub seq: u64,
+    pub message: M,
+}
+
+// just the sequence number, to validate before knowing which message it is
+#[derive(Deserialize)]
+struct HostGossipHeader {
+    seq: u64,
+}
+
+/// Sequence number of a host envelope, None if it isn't one
+pub fn host_gossip_seq(data: &[u8]) -> Option<u64> {
+    wire::decode::<HostGossipHeader>(data).map(|header| header.seq)
+}
+
+/// The message inside a host envelope, if it is of type `M`
+pub fn parse_host_gossip<M: DeserializeOwned>(data: &[u8]) -> Option<M> {
+    wire::decode::<HostGossip<M>>(data).map(|gossip| gossip.message)
+}
+
+pub fn publish_host_gossip<M: Serialize>(
+    swarm: &mut Swarm<BobaGoBehaviour>,
+    topic: &IdentTopic,
+    seq: u64,
+    message: M,
+) -> Result<MessageId, String> {
+    let data = wire::encode(&HostGossip { seq, message })?;
+    swarm
+        .behaviour_mut()
+        .gossipsub
+        .publish(topic.clone(), data)
+        .map_err(|e| format!("{e:?}"))
+}
+
+/// Only the host may publish host messages, and each sequence number is
+/// accepted once, in order. Before we know the host everything is ignored,
+/// the join response carries what we need.
+pub fn check_host_gossip(source: Option<PeerId>, host: Option<PeerId>, seq: u64, last_seq: &mut Option<u64>) -> MessageAcceptance {
+    let Some(host) = host else {
+        return MessageAcceptance::Ignore;
+    };
+    if source != Some(host) {
+        return MessageAcceptance::Reject;
+    }
+    if last_seq.is_some_and(|last| seq <= last) {
+        return MessageAcceptance::Ignore;
+    }
+    *last_seq = Some(seq);
+    MessageAcceptance::Accept
+}
+
+/// Players can chat as themselves while they aren't muted, mute lists have
+/// to come from the host. Messages from peers we don't know yet are ignored,
+/// they may have joined after the last lobby update reached us.
+pub fn check_chat_gossip(gossip: &ChatGossip, source: Option<PeerId>, host: Option<PeerId>, chat: &ChatLog) -> MessageAcceptance {
+    let from_host = source.is_some() && source == host;
+    match gossip {
+        ChatGossip::Message(_) if from_host => MessageAcceptance::Accept,
+        ChatGossip::Message(message) => match source.and_then(|peer| chat.sender(&peer)) {
+            None if source.is_some() => MessageAcceptance::Ignore,
+            Some((player_id, _)) if player_id == message.player_id && !chat.is_muted(player_id) => MessageAcceptance::Accept,
+            _ => MessageAcceptance::Reject,
+        },
+        ChatGossip::MuteList { .. } if from_host => MessageAcceptance::Accept,
+        ChatGossip::MuteList { .. } => MessageAcceptance::Reject,
+    }
+}
+
+/// Verdict on gossip arriving at a client, `host` being the peer we joined
+pub fn validate_client_gossip(
+    message: &gossipsub::Message,
+    host: Option<PeerId>,
+    last_seq: &mut Option<u64>,
+    chat: &ChatLog,
+) -> MessageAcceptance {
+    if let Some(gossip) = chat::parse_chat_gossip(&message.data) {
+        return check_chat_gossip(&gossip, message.source, host, chat);
+    }
+    match host_gossip_seq(&message.data) {
+        Some(seq) => check_host_gossip(message.source, host, seq, last_seq),
+        None => MessageAcceptance::Ignore,
+    }
+}
+
+/// Verdict on gossip arriving at the host: players only ever publish chat
+pub fn validate_host_gossip(message: &gossipsub::Message, local_peer_id: PeerId, chat: &ChatLog) -> MessageAcceptance {
+    match chat::parse_chat_gossip(&message.data) {
+        Some(gossip) => check_chat_gossip(&gossip, message.source, Some(local_peer_id), chat),
+        None => MessageAcceptance::Reject,
+    }
+}
+
+/// Tell gossipsub what we made of a message, only accepted ones are passed on
+pub fn report(
+    swarm: &mut Swarm<BobaGoBehaviour>,
+    message_id: &MessageId,
+    propagation_source: &PeerId,
+    acceptance: MessageAcceptance,
+) -> bool {
+    let accepted = matches!(acceptance, MessageAcceptance::Accept);
+    swarm
+        .behaviour_mut()
+        .gossipsub
+        .report_message_validation_result(message_id, propagation_source, acceptance);
+    accepted
+}
@@ -1,17 +1,28 @@
 use libp2p::{
-    gossipsub::IdentTopic,
+    gossipsub::{IdentTopic, MessageId},
     swarm::Swarm,
-    Multiaddr,
+    multiaddr::Protocol,
+    ping, Multiaddr,
 };
 use std::error::Error;
 
 use crate::network::behaviour::BobaGoBehaviour;
+use crate::network::swarm::TransportKind;
+use crate::network::chat::{self, ChatGossip, ChatLog};
+use crate::network::gossip;
+use crate::network::latency::LatencyTracker;
+use serde::Serialize;
 
 /// Generic host with networking (works for any state type)
 pub struct Host<S> {
     pub(crate) swarm: Swarm<BobaGoBehaviour>,
     pub(crate) state: S,
     pub(crate) topic: IdentTopic,
+    pub(crate) chat: ChatLog,
+    // sequence number of the last message we published, carried across transitions
+    pub(crate) gossip_seq: u64,
+    // round trips to each connected player
+    pub(crate) latency: LatencyTracker,
 }
 
 // Generic impl - works for any state type
@@ -23,6 +34,21 @@ impl<S> Host<S> {
         Ok(())
     }
 
+    /// Listen on every interface with each of the given transports
+    pub fn listen_on_transports(&mut self, transports: &[TransportKind]) -> Result<(), Box<dyn Error>> {
+        for transport in transports {
+            self.swarm.listen_on(transport.listen_address())?;
+        }
+        Ok(())
+    }
+
+    /// Reserve a slot on a circuit relay so players behind other NATs can reach us.
+    /// The relay address has to end with the relay's `/p2p/<peer id>`.
+    pub fn listen_via_relay(&mut self, relay: Multiaddr) -> Result<(), Box<dyn Error>> {
+        self.swarm.listen_on(relay.with(Protocol::P2pCircuit))?;
+        Ok(())
+    }
+
     /// Get the listening addresses
     pub fn listeners(&self) -> Vec<Multiaddr> {
         self.swarm.listeners().cloned().collect()
@@ -36,4 +62,52 @@ impl<S> Host<S> {
     pub fn state_mut(&mut self) -> &mut S {
         &mut self.state
     }
+
+    pub fn chat(&self) -> &ChatLog {
+        &self.chat
+    }
+
+    /// Send a chat message as the host
+    pub fn send_chat(&mut self, text: &str) -> Result<(), String> {
+        let message = self.chat.compose(text)?;
+        if let Err(e) = chat::publish_chat_gossip(&mut self.swarm, &self.topic, &ChatGossip::Message(message)) {
+            crate::log::host(e);
+        }
+        Ok(())
+    }
+
+    /// Mute or unmute a player's chat for everyone in the room
+    pub fn set_player_muted(&mut self, player_id: usize, muted: bool) -> Result<(), String> {
+        if Some(player_id) == self.chat.local_player_id() {
+            return Err("The host cannot be muted".to_string());
+        }
+        if self.chat.set_muted(player_id, muted) {
+            self.publish_mute_list();
+        }
+        Ok(())
+    }
+
+    /// Publish a message to the room in a numbered envelope, so clients
+    /// can tell it came from us and drop replays
+    pub(crate) fn publish_host_message<M: Serialize>(&mut self, message: M) -> Result<MessageId, String> {
+        self.gossip_seq += 1;
+        gossip::publish_host_gossip(&mut self.swarm, &self.topic, self.gossip_seq, message)
+    }
+
+    /// Record a ping, dropping players who stopped answering. Returns true
+    /// when it's time to send the room the latency table again
+    pub(crate) fn handle_ping(&mut self, event: ping::Event) -> bool {
+        if self.latency.record(&event) {
+            crate::log::host(format!("{} stopped answering pings, disconnecting", event.peer));
+            self.swarm.disconnect_peer_id(event.peer).ok();
+        }
+        self.latency.report_due()
+    }
+
+    pub(crate) fn publish_mute_list(&mut self) {
+        let gossip = ChatGossip::MuteList { muted: self.chat.muted_players() };
+        if let Err(e) = chat::publish_chat_gossip(&mut self.swarm, &self.topic, &gossip) {
+            crate::log::host(e);
+        }
+    }
 }
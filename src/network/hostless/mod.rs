@@ -0,0 +1,7 @@
+pub mod protocol;
+pub mod state;
+pub mod peer;
+
+pub use protocol::{HostlessMessage, HostlessSeat, TurnReveal};
+pub use state::HostlessGameState;
+pub use peer::PeerEvent;
@@ -0,0 +1,415 @@
+use libp2p::{
+    futures::StreamExt,
+    gossipsub::{IdentTopic, MessageAcceptance},
+    swarm::{Swarm, SwarmEvent},
+    PeerId,
+};
+use std::collections::HashMap;
+use std::time::Instant;
+
+use crate::network::behaviour::{BobaGoBehaviour, BobaGoBehaviourEvent};
+use crate::network::{gossip, wire, Peer};
+use crate::network::chat::{self, ChatLog, ChatMessage};
+use crate::network::latency::{LatencyTracker, PlayerLatencies, PlayerLatency};
+use crate::engine::models::CardKind;
+use super::protocol::{HostlessMessage, TurnReveal};
+use super::state::HostlessGameState;
+use crate::log;
+
+impl Peer<HostlessGameState> {
+    pub fn new(
+        swarm: Swarm<BobaGoBehaviour>,
+        topic: IdentTopic,
+        chat: ChatLog,
+        state: HostlessGameState,
+    ) -> Self {
+        Self {
+            swarm,
+            state,
+            topic,
+            chat,
+            latency: LatencyTracker::new(),
+        }
+    }
+
+    /// Our own round trip to the peer in a seat, there's no host to ask
+    pub fn player_latency(&self, player_id: usize) -> Option<PlayerLatency> {
+        if player_id == self.state.local_player_id {
+            return None;
+        }
+        self.state.seats.get(player_id).and_then(|peer| self.latency.latency(peer))
+    }
+
+    pub fn get_score_breakdowns(&self) -> Vec<(String, crate::engine::ScoreBreakdown)> {
+        let mut score_data = Vec::new();
+        for player in self.state.game.get_players_public() {
+            if let Ok((_total, breakdown)) = self.state.game.calculate_player_score(player.id) {
+                score_data.push((player.name, breakdown));
+            }
+        }
+        score_data
+    }
+
+    fn publish(&mut self, message: &HostlessMessage) {
+        if let Ok(data) = wire::encode(message)
+            && let Err(e) = self.swarm
+                .behaviour_mut()
+                .gossipsub
+                .publish(self.topic.clone(), data)
+        {
+            log::peer(format!("Failed to publish {:?}: {:?}", message, e));
+        }
+    }
+
+    /// Commit to a pick for the current turn, the pick itself stays hidden until everyone has committed
+    pub fn submit_turn(&mut self, selected_cards: HashMap<CardKind, usize>, remaining_hand: HashMap<CardKind, usize>) -> Result<(), String> {
+        if self.state.aborted {
+            return Err("Game has been stopped".to_string());
+        }
+        if self.state.has_committed() {
+            return Err("Turn already submitted".to_string());
+        }
+
+        let player_id = self.state.local_player_id;
+        self.state.game.validate_hand_submission(player_id, &selected_cards, &remaining_hand)
+            .map_err(|e| format!("Invalid submission: {:?}", e))?;
+
+        let (round, turn) = self.state.current_turn();
+        let reveal = TurnReveal {
+            selected_cards,
+            remaining_hand,
+            drink_tray: self.state.drink_tray_activated,
+            salt: rand::random(),
+        };
+        let commitment = reveal.commitment(player_id, round, turn);
+
+        self.state.exchange_mut(round, turn).commitments.insert(player_id, commitment);
+        self.state.game.mark_player_selected(player_id).ok();
+        self.state.own_reveal = Some(reveal);
+
+        self.publish(&HostlessMessage::Commit { player_id, round, turn, commitment });
+        log::peer(format!("Committed to round {round} turn {turn}"));
+
+        self.advance();
+        Ok(())
+    }
+
+    /// Activate our Drink Tray locally, peers learn about it from our reveal
+    pub fn activate_drink_tray(&mut self) -> Result<(), String> {
+        if self.state.has_committed() {
+            return Err("Turn already submitted".to_string());
+        }
+        self.state.game.activate_drink_tray(self.state.local_player_id)
+            .map_err(|_| "No DrinkTray in public cards".to_string())?;
+        self.state.drink_tray_activated = true;
+        Ok(())
+    }
+
+    /// Stop the game and tell the other peers why
+    fn abort(&mut self, reason: String) {
+        if self.state.aborted {
+            return;
+        }
+        log::peer(format!("Aborting game: {reason}"));
+        let player_id = self.state.local_player_id;
+        self.publish(&HostlessMessage::Abort { player_id, reason: reason.clone() });
+        self.state.abort(reason);
+    }
+
+    // reveal once everyone has committed, resolve once everyone has revealed
+    fn advance(&mut self) {
+        if self.state.aborted {
+            return;
+        }
+
+        if self.state.all_committed() && !self.state.revealed
+            && let Some(reveal) = self.state.own_reveal.clone()
+        {
+            let player_id = self.state.local_player_id;
+            let (round, turn) = self.state.current_turn();
+            self.state.exchange_mut(round, turn).reveals.insert(player_id, reveal.clone());
+            self.state.revealed = true;
+            self.state.reveal_deadline = Some(Instant::now() + self.state.reveal_timeout);
+            self.publish(&HostlessMessage::Reveal { player_id, round, turn, reveal });
+        }
+
+        if self.state.all_revealed() {
+            self.resolve_turn();
+        }
+    }
+
+    // every peer applies the same reveals to its own copy of the game
+    fn resolve_turn(&mut self) {
+        let (round, turn) = self.state.current_turn();
+        let Some(exchange) = self.state.exchanges.remove(&(round, turn)) else {
+            return;
+        };
+
+        let mut submissions = Vec::new();
+        for player_id in 0..self.state.seats.len() {
+            let name = self.state.player_name(player_id);
+            let (Some(reveal), Some(commitment)) = (exchange.reveals.get(&player_id), exchange.commitments.get(&player_id)) else {
+                self.abort(format!("Missing pick from {}", name));
+                return;
+            };
+
+            if reveal.commitment(player_id, round, turn) != *commitment {
+                self.abort(format!("{} revealed a different pick than they committed to", name));
+                return;
+            }
+
+            // our own activation was applied when we made it
+            if reveal.drink_tray && player_id != self.state.local_player_id
+                && self.state.game.activate_drink_tray(player_id).is_err()
+            {
+                self.abort(format!("{} used a Drink Tray they don't have", name));
+                return;
+            }
+
+            if self.state.game
+                .validate_hand_submission(player_id, &reveal.selected_cards, &reveal.remaining_hand)
+                .is_err()
+            {
+                self.abort(format!("{} picked cards they don't hold or the wrong number of them", name));
+                return;
+            }
+
+            self.state.game.mark_player_selected(player_id).ok();
+            submissions.push(Some((reveal.selected_cards.clone(), reveal.remaining_hand.clone())));
+        }
+
+        if let Err(e) = self.state.game.process_turn(submissions) {
+            self.abort(format!("Failed to process turn: {:?}", e));
+            return;
+        }
+
+        self.state.own_reveal = None;
+        self.state.revealed = false;
+        self.state.reveal_deadline = None;
+        self.state.drink_tray_activated = false;
+
+        let hash = self.state.game.state_hash();
+        self.state.state_hashes.insert((round, turn), hash);
+        let player_id = self.state.local_player_id;
+        self.publish(&HostlessMessage::StateHash { player_id, round, turn, hash });
+        self.check_pending_hashes();
+        log::peer(format!("Resolved round {round} turn {turn}"));
+
+        // commitments for the new turn may have arrived before we got here
+        let (round, turn) = self.state.current_turn();
+        if let Some(next) = self.state.exchanges.get(&(round, turn)) {
+            let committed: Vec<usize> = next.commitments.keys().copied().collect();
+            for player_id in committed {
+                self.state.game.mark_player_selected(player_id).ok();
+            }
+        }
+
+        let event = if self.state.game.is_game_over() {
+            super::PeerEvent::GameOver
+        } else {
+            super::PeerEvent::TurnResolved
+        };
+        self.state.pending_events.push_back(event);
+    }
+
+    // everyone committed but someone is holding back their reveal
+    fn reveal_timed_out(&mut self) {
+        self.state.reveal_deadline = None;
+        let names: Vec<String> = self.state.missing_reveals().into_iter().map(|id| self.state.player_name(id)).collect();
+        self.abort(format!("Waited too long for {} to reveal their pick", names.join(", ")));
+    }
+
+    // compare peer hashes against ours once we have resolved the same turn
+    fn check_pending_hashes(&mut self) {
+        let pending = std::mem::take(&mut self.state.pending_hashes);
+        for (player_id, round, turn, hash) in pending {
+            match self.state.state_hashes.get(&(round, turn)) {
+                Some(own) if *own != hash => {
+                    let name = self.state.player_name(player_id);
+                    self.abort(format!("Game state diverged from {} at round {} turn {}", name, round, turn));
+                }
+                Some(_) => {}
+                None => self.state.pending_hashes.push((player_id, round, turn, hash)),
+            }
+        }
+    }
+
+    fn handle_hostless_message(&mut self, source: Option<PeerId>, message: HostlessMessage) -> Option<super::PeerEvent> {
+        let player_id = message.player_id();
+
+        // only accept messages signed by the player's own peer
+        let sender = source.and_then(|peer| self.state.get_player_id(&peer));
+        if sender != Some(player_id) || player_id == self.state.local_player_id {
+            log::peer(format!("Ignoring message for player {player_id} from {source:?}"));
+            return None;
+        }
+
+        let current = self.state.current_turn();
+
+        match message {
+            HostlessMessage::Commit { round, turn, commitment, .. } => {
+                if !self.state.accepts_turn(round, turn) {
+                    return None;
+                }
+                let exchange = self.state.exchange_mut(round, turn);
+                if let Some(existing) = exchange.commitments.get(&player_id)
+                    && *existing != commitment
+                {
+                    let name = self.state.player_name(player_id);
+                    self.abort(format!("{} committed to two different picks", name));
+                    return None;
+                }
+                exchange.commitments.insert(player_id, commitment);
+                if (round, turn) == current {
+                    self.state.game.mark_player_selected(player_id).ok();
+                    self.advance();
+                    return Some(super::PeerEvent::PlayerCommitted { player_id });
+                }
+            }
+            HostlessMessage::Reveal { round, turn, reveal, .. } => {
+                if !self.state.accepts_turn(round, turn) {
+                    return None;
+                }
+                self.state.exchange_mut(round, turn).reveals.insert(player_id, reveal);
+                if (round, turn) == current {
+                    self.advance();
+                }
+            }
+            HostlessMessage::StateHash { round, turn, hash, .. } => {
+                // a hash comes after resolving a turn, so it is for one we resolved
+                // or the current one, and each peer sends one per turn
+                let known = (round, turn) == current || self.state.state_hashes.contains_key(&(round, turn));
+                let repeated = self.state.pending_hashes.iter().any(|(p, r, t, _)| (*p, *r, *t) == (player_id, round, turn));
+                if !known || repeated {
+                    return None;
+                }
+                self.state.pending_hashes.push((player_id, round, turn, hash));
+                self.check_pending_hashes();
+            }
+            HostlessMessage::Abort { reason, .. } => {
+                let name = self.state.player_name(player_id);
+                self.state.abort(format!("{} stopped the game: {}", name, reason));
+            }
+        }
+        None
+    }
+
+    // chat from anyone in the room, game messages only from the players at the table
+    fn validate_gossip(&self, message: &libp2p::gossipsub::Message) -> MessageAcceptance {
+        if let Some(gossip) = chat::parse_chat_gossip(&message.data) {
+            return gossip::check_chat_gossip(&gossip, message.source, Some(self.state.lobby_host), &self.chat);
+        }
+        if wire::decode::<HostlessMessage>(&message.data).is_none() {
+            return MessageAcceptance::Ignore;
+        }
+        match message.source {
+            Some(source) if self.state.get_player_id(&source).is_some() => MessageAcceptance::Accept,
+            _ => MessageAcceptance::Reject,
+        }
+    }
+
+    /// run event loop
+    /// events = player committed, turn resolved, game over, aborted, chat
+    pub async fn next_event(&mut self) -> Option<super::PeerEvent> {
+        loop {
+            if let Some(event) = self.state.pending_events.pop_front() {
+                return Some(event);
+            }
+
+            let deadline = self.state.reveal_deadline.filter(|_| !self.state.aborted);
+            let event = match deadline {
+                Some(at) => tokio::select! {
+                    event = self.swarm.select_next_some() => event,
+                    _ = tokio::time::sleep_until(at.into()) => {
+                        self.reveal_timed_out();
+                        continue;
+                    }
+                },
+                None => self.swarm.select_next_some().await,
+            };
+            match event {
+                SwarmEvent::Behaviour(BobaGoBehaviourEvent::Gossipsub(
+                    libp2p::gossipsub::Event::Message { message, message_id, propagation_source }
+                )) => {
+                    let acceptance = self.validate_gossip(&message);
+                    if !gossip::report(&mut self.swarm, &message_id, &propagation_source, acceptance) {
+                        log::peer(format!("Dropped gossip published by {:?} via {propagation_source}", message.source));
+                        continue;
+                    }
+                    if let Some(gossip) = chat::parse_chat_gossip(&message.data) {
+                        let from_host = message.source == Some(self.state.lobby_host);
+                        if let Some(message) = self.chat.handle_gossip(gossip, message.source, from_host) {
+                            return Some(super::PeerEvent::ChatReceived { message });
+                        }
+                        continue;
+                    }
+                    if self.state.aborted {
+                        continue;
+                    }
+                    if let Some(hostless_message) = wire::decode::<HostlessMessage>(&message.data)
+                        && let Some(event) = self.handle_hostless_message(message.source, hostless_message)
+                    {
+                        return Some(event);
+                    }
+                }
+                SwarmEvent::Behaviour(BobaGoBehaviourEvent::Ping(event)) => {
+                    self.handle_ping(event);
+                }
+                SwarmEvent::ConnectionEstablished { peer_id, .. } => {
+                    super::super::events::handle_client_connection_established(&mut self.swarm, peer_id);
+                }
+                SwarmEvent::ConnectionClosed { peer_id, num_established: 0, .. } => {
+                    log::peer(format!("Connection closed with {peer_id}"));
+                    // everyone else is reached through the lobby host, losing it ends the game
+                    if let Some(player_id) = self.state.get_player_id(&peer_id) {
+                        let name = self.state.player_name(player_id);
+                        self.abort(format!("Lost connection to {}", name));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PeerEvent {
+    PlayerCommitted { player_id: usize },
+    TurnResolved,
+    GameOver,
+    // a peer cheated, diverged or left, the game can't continue
+    Aborted { reason: String },
+    ChatReceived { message: ChatMessage },
+}
+
+impl crate::engine::GameInterface for Peer<HostlessGameState> {
+    fn get_hand(&self) -> HashMap<CardKind, usize> {
+        self.state.game.get_player_hand(self.state.local_player_id).cloned().unwrap_or_default()
+    }
+
+    fn get_game_status(&self) -> crate::engine::state::GameStatus {
+        self.state.game.get_game_status()
+    }
+
+    fn get_players_public(&self) -> Vec<crate::engine::models::PlayerPublic> {
+        self.state.game.get_players_public()
+    }
+
+    fn submit_turn(&mut self, selected: HashMap<CardKind, usize>, remaining: HashMap<CardKind, usize>) -> Result<(), String> {
+        Peer::<HostlessGameState>::submit_turn(self, selected, remaining)
+    }
+
+    fn get_player_id(&self) -> usize {
+        self.state.local_player_id
+    }
+
+    fn activate_drink_tray(&mut self) -> Result<(), String> {
+        Peer::<HostlessGameState>::activate_drink_tray(self)
+    }
+}
+
+impl PlayerLatencies for Peer<HostlessGameState> {
+    fn player_latency(&self, player_id: usize) -> Option<PlayerLatency> {
+        Peer::<HostlessGameState>::player_latency(self, player_id)
+    }
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+use crate::engine::fairness::Commitment;
+use crate::engine::models::CardKind;
+
+/// A seat at a hostless table, index in the seat list is the player id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostlessSeat {
+    pub peer_id: String,
+    pub name: String,
+}
+
+/// A player's pick for one turn, published once every player has committed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TurnReveal {
+    pub selected_cards: HashMap<CardKind, usize>,
+    pub remaining_hand: HashMap<CardKind, usize>,
+    // Drink Tray is activated by the pick itself, no one sees it early
+    pub drink_tray: bool,
+    pub salt: [u8; 32],
+}
+
+impl TurnReveal {
+    /// Commitment binding this pick to a player and turn
+    pub fn commitment(&self, player_id: usize, round: usize, turn: usize) -> Commitment {
+        let sorted = |cards: &HashMap<CardKind, usize>| {
+            let mut cards: Vec<(CardKind, usize)> = cards.iter().map(|(k, c)| (*k, *c)).collect();
+            cards.sort();
+            cards
+        };
+
+        let mut hasher = Sha256::new();
+        hasher.update(b"boba-go/turn");
+        hasher.update(format!(
+            "{}|{}|{}|{:?}|{:?}|{};",
+            player_id,
+            round,
+            turn,
+            sorted(&self.selected_cards),
+            sorted(&self.remaining_hand),
+            self.drink_tray,
+        ));
+        hasher.update(self.salt);
+        hasher.finalize().into()
+    }
+}
+
+// gossip between peers during a hostless game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum HostlessMessage {
+    // hash of a pick, sent as soon as the player confirms
+    Commit {
+        player_id: usize,
+        round: usize,
+        turn: usize,
+        commitment: Commitment,
+    },
+    // the pick itself, sent once all commitments for the turn are in
+    Reveal {
+        player_id: usize,
+        round: usize,
+        turn: usize,
+        reveal: TurnReveal,
+    },
+    // hash of the game state after resolving a turn, used to detect divergence
+    StateHash {
+        player_id: usize,
+        round: usize,
+        turn: usize,
+        hash: [u8; 32],
+    },
+    // sent by a peer that can't continue, so everyone stops together
+    Abort {
+        player_id: usize,
+        reason: String,
+    },
+}
+
+impl HostlessMessage {
+    pub fn player_id(&self) -> usize {
+        match self {
+            HostlessMessage::Commit { player_id, .. }
+            | HostlessMessage::Reveal { player_id, .. }
+            | HostlessMessage::StateHash { player_id, .. }
+            | HostlessMessage::Abort { player_id, .. } => *player_id,
+        }
+    }
+}
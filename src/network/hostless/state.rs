@@ -0,0 +1,123 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use libp2p::PeerId;
+
+use crate::engine::Game;
+use crate::engine::fairness::Commitment;
+use super::peer::PeerEvent;
+use super::protocol::TurnReveal;
+
+/// how long everyone gets to reveal once the whole table has committed
+pub const REVEAL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Commitments and reveals collected for one turn
+#[derive(Default)]
+pub struct TurnExchange {
+    pub commitments: HashMap<usize, Commitment>,
+    pub reveals: HashMap<usize, TurnReveal>,
+}
+
+pub struct HostlessGameState {
+    pub game: Game,
+    pub local_player_id: usize,
+    // index = player id
+    pub seats: Vec<PeerId>,
+    // the peer that ran the lobby, still the one allowed to moderate chat
+    pub lobby_host: PeerId,
+    // keyed by (round, turn), peers that are ahead can send messages for the next turn early
+    pub exchanges: BTreeMap<(usize, usize), TurnExchange>,
+    // our pick for the current turn, published once everyone has committed
+    pub own_reveal: Option<TurnReveal>,
+    pub revealed: bool,
+    // a peer that committed but holds back its reveal can't stall the table past this
+    pub reveal_deadline: Option<Instant>,
+    pub reveal_timeout: Duration,
+    pub drink_tray_activated: bool,
+    // our own state hash after each resolved turn, and hashes from peers we can't check yet
+    pub state_hashes: HashMap<(usize, usize), [u8; 32]>,
+    pub pending_hashes: Vec<(usize, usize, usize, [u8; 32])>,
+    // events produced outside next_event, e.g. when our own submission completes a turn
+    pub pending_events: VecDeque<PeerEvent>,
+    pub aborted: bool,
+}
+
+impl HostlessGameState {
+    pub fn new(game: Game, local_player_id: usize, seats: Vec<PeerId>, lobby_host: PeerId) -> Self {
+        Self {
+            game,
+            local_player_id,
+            seats,
+            lobby_host,
+            exchanges: BTreeMap::new(),
+            own_reveal: None,
+            revealed: false,
+            reveal_deadline: None,
+            reveal_timeout: REVEAL_TIMEOUT,
+            drink_tray_activated: false,
+            state_hashes: HashMap::new(),
+            pending_hashes: Vec::new(),
+            pending_events: VecDeque::new(),
+            aborted: false,
+        }
+    }
+
+    pub fn current_turn(&self) -> (usize, usize) {
+        (self.game.round, self.game.turn)
+    }
+
+    pub fn get_player_id(&self, peer: &PeerId) -> Option<usize> {
+        self.seats.iter().position(|p| p == peer)
+    }
+
+    /// Whether messages for (round, turn) are taken: the current turn, or the
+    /// next one from peers that resolved the current turn before us
+    pub fn accepts_turn(&self, round: usize, turn: usize) -> bool {
+        let (current_round, current_turn) = self.current_turn();
+        (round, turn) == (current_round, current_turn)
+            || (round, turn) == (current_round, current_turn + 1)
+            || (round, turn) == (current_round + 1, 1)
+    }
+
+    pub fn exchange_mut(&mut self, round: usize, turn: usize) -> &mut TurnExchange {
+        self.exchanges.entry((round, turn)).or_default()
+    }
+
+    pub fn all_committed(&self) -> bool {
+        self.exchanges
+            .get(&self.current_turn())
+            .is_some_and(|e| e.commitments.len() == self.seats.len())
+    }
+
+    pub fn all_revealed(&self) -> bool {
+        self.exchanges
+            .get(&self.current_turn())
+            .is_some_and(|e| e.reveals.len() == self.seats.len())
+    }
+
+    /// Players we are still waiting on for a reveal this turn
+    pub fn missing_reveals(&self) -> Vec<usize> {
+        let exchange = self.exchanges.get(&self.current_turn());
+        (0..self.seats.len())
+            .filter(|player_id| !exchange.is_some_and(|e| e.reveals.contains_key(player_id)))
+            .collect()
+    }
+
+    pub fn has_committed(&self) -> bool {
+        self.own_reveal.is_some()
+    }
+
+    pub fn player_name(&self, player_id: usize) -> String {
+        self.game.players
+            .get(player_id)
+            .map(|p| p.username.clone())
+            .unwrap_or_else(|| format!("Player {}", player_id))
+    }
+
+    /// Stop the game, nothing else is processed after this
+    pub fn abort(&mut self, reason: String) {
+        if !self.aborted {
+            self.aborted = true;
+            self.pending_events.push_back(PeerEvent::Aborted { reason });
+        }
+    }
+}
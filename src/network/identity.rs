@@ -0,0 +1,209 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use libp2p::{identity::Keypair, PeerId};
+use serde::{Deserialize, Serialize};
+
+/// file holding the keypair, protobuf encoded
+const KEY_FILE: &str = "identity.key";
+
+/// file holding the profile, as JSON so it can be edited by hand
+const PROFILE_FILE: &str = "profile.json";
+
+/// profile picked on the command line, wins over `BOBA_PROFILE`
+static PROFILE: OnceLock<String> = OnceLock::new();
+
+/// Glyphs to pick an avatar from, all one column wide in a terminal
+pub const AVATARS: [char; 10] = ['●', '★', '♠', '♥', '♦', '♣', '☺', '♪', '☼', '✿'];
+
+/// Colors a player can show up in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProfileColor {
+    #[default]
+    Yellow,
+    Red,
+    Green,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl ProfileColor {
+    pub const ALL: [ProfileColor; 7] = [
+        ProfileColor::Yellow,
+        ProfileColor::Red,
+        ProfileColor::Green,
+        ProfileColor::Blue,
+        ProfileColor::Magenta,
+        ProfileColor::Cyan,
+        ProfileColor::White,
+    ];
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|c| *c == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn previous(self) -> Self {
+        let index = Self::ALL.iter().position(|c| *c == self).unwrap_or(0);
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+/// How a player shows up next to their name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Avatar {
+    pub glyph: char,
+    pub color: ProfileColor,
+}
+
+impl Default for Avatar {
+    fn default() -> Self {
+        Self { glyph: AVATARS[0], color: ProfileColor::default() }
+    }
+}
+
+impl Avatar {
+    /// The next glyph in `AVATARS`, for cycling through them
+    pub fn next_glyph(self) -> Self {
+        let index = AVATARS.iter().position(|g| *g == self.glyph).map_or(0, |i| i + 1);
+        Self { glyph: AVATARS[index % AVATARS.len()], ..self }
+    }
+}
+
+/// What other players see of us
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    /// empty until the player has picked a name
+    pub name: String,
+    pub avatar: Avatar,
+}
+
+/// A player's keypair and profile, kept between launches so hosts see the
+/// same peer id every time. The keypair is used for the transport and for
+/// signing gossip alike.
+pub struct Identity {
+    pub keypair: Keypair,
+    pub profile: Profile,
+    // where it is saved, None for a throwaway identity
+    dir: Option<PathBuf>,
+}
+
+impl Identity {
+    /// Fresh identity that is never saved, for dedicated servers and tests
+    pub fn generate() -> Self {
+        Self { keypair: Keypair::generate_ed25519(), profile: Profile::default(), dir: None }
+    }
+
+    /// Load the identity saved in `dir`, creating and saving one the first time
+    pub fn load_or_create(dir: &Path) -> Result<Self, String> {
+        let keypair = load_or_create_key(&dir.join(KEY_FILE))?;
+
+        // a missing or broken profile just means picking a name again
+        let profile = fs::read_to_string(dir.join(PROFILE_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        Ok(Self { keypair, profile, dir: Some(dir.to_path_buf()) })
+    }
+
+    /// Identity from the config directory, see `config_dir`
+    pub fn load() -> Result<Self, String> {
+        let dir = config_dir().ok_or("No config directory, set BOBA_CONFIG_DIR")?;
+        Self::load_or_create(&dir)
+    }
+
+    pub fn peer_id(&self) -> PeerId {
+        self.keypair.public().to_peer_id()
+    }
+
+    /// Write the profile back, a no-op for throwaway identities
+    pub fn save_profile(&self) -> Result<(), String> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {e}", dir.display()))?;
+        let json = serde_json::to_string_pretty(&self.profile).map_err(|e| e.to_string())?;
+        let path = dir.join(PROFILE_FILE);
+        fs::write(&path, json).map_err(|e| format!("Could not write {}: {e}", path.display()))
+    }
+}
+
+/// Keypair saved at `path`, generated and saved the first time. Also keeps
+/// the relay's peer id, and so its address, the same across restarts.
+pub fn load_or_create_key(path: &Path) -> Result<Keypair, String> {
+    if path.exists() {
+        let bytes = fs::read(path).map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+        return Keypair::from_protobuf_encoding(&bytes).map_err(|e| format!("Invalid identity in {}: {e}", path.display()));
+    }
+    let keypair = Keypair::generate_ed25519();
+    save_key(path, &keypair)?;
+    Ok(keypair)
+}
+
+fn save_key(path: &Path, keypair: &Keypair) -> Result<(), String> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {e}", dir.display()))?;
+    }
+    let bytes = keypair.to_protobuf_encoding().map_err(|e| e.to_string())?;
+    fs::write(path, bytes).map_err(|e| format!("Could not write {}: {e}", path.display()))?;
+    // the key is who we are, nobody else should read it
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600)).ok();
+    }
+    Ok(())
+}
+
+/// Run as a named profile with its own identity and recent rooms, so a host
+/// and a player on the same machine don't share a peer id. Call before
+/// anything reads `config_dir`.
+pub fn set_profile(name: &str) -> Result<(), String> {
+    if !is_profile_name(name) {
+        return Err(format!("Invalid profile name '{name}', use letters, digits, '-' and '_'"));
+    }
+    PROFILE.set(name.to_string()).map_err(|_| "Profile is already set".to_string())
+}
+
+fn is_profile_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Directory of a named profile under `config_dir`
+pub fn profile_dir(config_dir: &Path, profile: &str) -> PathBuf {
+    config_dir.join("profiles").join(profile)
+}
+
+/// Where the identity lives: `BOBA_CONFIG_DIR` if set, otherwise the
+/// platform's config directory. A profile from `set_profile` or
+/// `BOBA_PROFILE` gets its own directory inside it.
+pub fn config_dir() -> Option<PathBuf> {
+    let profile = PROFILE
+        .get()
+        .cloned()
+        .or_else(|| std::env::var("BOBA_PROFILE").ok().filter(|name| is_profile_name(name)));
+    let dir = base_config_dir()?;
+    Some(match profile {
+        Some(profile) => profile_dir(&dir, &profile),
+        None => dir,
+    })
+}
+
+fn base_config_dir() -> Option<PathBuf> {
+    let env_dir = |var: &str| std::env::var_os(var).filter(|v| !v.is_empty()).map(PathBuf::from);
+    if let Some(dir) = env_dir("BOBA_CONFIG_DIR") {
+        return Some(dir);
+    }
+    let base = if cfg!(windows) {
+        env_dir("APPDATA")?
+    } else if cfg!(target_os = "macos") {
+        env_dir("HOME")?.join("Library").join("Application Support")
+    } else {
+        env_dir("XDG_CONFIG_HOME").or_else(|| env_dir("HOME").map(|home| home.join(".config")))?
+    };
+    Some(base.join("boba-go"))
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+use libp2p::{ping, PeerId};
+use serde::{Deserialize, Serialize};
+
+use crate::engine::GamePlayerView;
+
+/// time between pings on every connection
+pub const PING_INTERVAL: Duration = Duration::from_secs(2);
+
+/// a ping without a reply after this long counts as missed
+pub const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// missed pings in a row before we give up on the connection,
+/// the peer shows as unstable from the first one
+pub const MAX_MISSED_PINGS: u32 = 4;
+
+/// How a player's connection looks, as measured by the host
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerLatency {
+    // last round trip, None until the first ping comes back
+    pub rtt_ms: Option<u32>,
+    // the last ping went unanswered
+    pub unstable: bool,
+}
+
+impl fmt::Display for PlayerLatency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.rtt_ms {
+            _ if self.unstable => write!(f, "connection unstable"),
+            Some(ms) => write!(f, "{ms} ms"),
+            None => write!(f, "… ms"),
+        }
+    }
+}
+
+/// Round trips shown next to player names, for frontends that play network
+/// games next to local ones. Games without connections keep the default.
+pub trait PlayerLatencies {
+    fn player_latency(&self, _player_id: usize) -> Option<PlayerLatency> {
+        None
+    }
+}
+
+impl PlayerLatencies for GamePlayerView<'_> {}
+
+#[derive(Debug, Default)]
+struct PeerPing {
+    rtt: Option<Duration>,
+    missed: u32,
+}
+
+/// Round trips to the peers we're connected to, fed by libp2p ping
+#[derive(Debug, Default)]
+pub struct LatencyTracker {
+    peers: HashMap<PeerId, PeerPing>,
+    // when the host last sent the table to the room
+    last_report: Option<Instant>,
+    // someone went unstable or recovered since then
+    changed: bool,
+}
+
+impl LatencyTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a ping result, returns true once the peer has missed
+    /// `MAX_MISSED_PINGS` in a row and the connection should be closed
+    pub fn record(&mut self, event: &ping::Event) -> bool {
+        let peer = self.peers.entry(event.peer).or_default();
+        let was_unstable = peer.missed > 0;
+        match &event.result {
+            Ok(rtt) => {
+                peer.rtt = Some(*rtt);
+                peer.missed = 0;
+            }
+            // peers built before ping was added never answer, that's not their connection's fault
+            Err(ping::Failure::Unsupported) => {}
+            Err(_) => peer.missed += 1,
+        }
+        self.changed |= was_unstable != (peer.missed > 0);
+        peer.missed >= MAX_MISSED_PINGS
+    }
+
+    pub fn remove(&mut self, peer: &PeerId) {
+        self.peers.remove(peer);
+    }
+
+    /// None until we've heard anything about the peer
+    pub fn latency(&self, peer: &PeerId) -> Option<PlayerLatency> {
+        self.peers.get(peer).map(|ping| PlayerLatency {
+            rtt_ms: ping.rtt.map(|rtt| rtt.as_millis().min(u32::MAX as u128) as u32),
+            unstable: ping.missed > 0,
+        })
+    }
+
+    /// Whether the host should send the table to the room again: right away
+    /// when someone's connection went bad or recovered, otherwise at most once
+    /// per `PING_INTERVAL` so it isn't republished for every single ping
+    pub fn report_due(&mut self) -> bool {
+        if !self.changed && self.last_report.is_some_and(|last| last.elapsed() < PING_INTERVAL) {
+            return false;
+        }
+        self.last_report = Some(Instant::now());
+        self.changed = false;
+        true
+    }
+}
@@ -0,0 +1,41 @@
+use hmac::{Hmac, Mac};
+use libp2p::PeerId;
+use rand::RngCore;
+use sha2::Sha256;
+
+/// Random challenge the host hands out, answered with a `PasswordProof`
+pub type Nonce = [u8; 32];
+pub type PasswordProof = [u8; 32];
+
+pub fn random_nonce() -> Nonce {
+    let mut nonce = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    nonce
+}
+
+// keyed with the password, bound to both ends so a proof can't be replayed
+// against another host or by another peer
+fn proof_mac(password: &str, nonce: &Nonce, host: &PeerId, client: &PeerId) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(password.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(nonce);
+    mac.update(&host.to_bytes());
+    mac.update(&client.to_bytes());
+    mac
+}
+
+/// Answer a host's challenge without sending the password itself
+pub fn password_proof(password: &str, nonce: &Nonce, host: &PeerId, client: &PeerId) -> PasswordProof {
+    proof_mac(password, nonce, host, client).finalize().into_bytes().into()
+}
+
+/// Constant-time check of a client's answer
+pub fn verify_password_proof(
+    password: &str,
+    nonce: &Nonce,
+    host: &PeerId,
+    client: &PeerId,
+    proof: &PasswordProof,
+) -> bool {
+    proof_mac(password, nonce, host, client).verify_slice(proof).is_ok()
+}
@@ -1,73 +1,166 @@
 use libp2p::{
-    core::upgrade,
     futures::StreamExt,
     gossipsub::IdentTopic,
-    identity, noise,
     swarm::{Swarm, SwarmEvent},
-    tcp, yamux, PeerId, Transport,
+    Multiaddr, PeerId,
 };
+use std::collections::HashMap;
 use std::error::Error;
 
 use crate::network::behaviour::{BobaGoBehaviour, BobaGoBehaviourEvent, ClientRequest, HostResponse};
 use crate::network::Client;
-use super::protocol::{ClientMessage, HostMessage, LobbyPlayer};
+use crate::network::chat::{self, ChatLog, ChatMessage};
+use crate::network::latency::{LatencyTracker, PlayerLatency};
+use crate::engine::fairness::{self, Commitment, Entropy};
+use crate::engine::{GameConfig, GameSettings};
+use crate::network::hostless::HostlessSeat;
+use crate::network::{gossip, room_code, swarm};
+use crate::network::identity::{Avatar, Identity};
+use crate::network::version::VersionInfo;
+use super::auth::{self, Nonce, PasswordProof};
+use super::protocol::{self, ClientMessage, HostMessage, LobbyPlayer};
 use crate::log;
 
 /// Lobby-specific client state
 pub struct LobbyClientState {
     player_name: String,
+    avatar: Avatar,
     player_id: Option<usize>,
+    // as the host calls it, empty until the host answers
+    room_name: String,
     lobby_players: Vec<LobbyPlayer>,
     host_peer_id: Option<PeerId>,
     join_request_sent: bool,
+    // our contribution to the seed, and the host's commitments when fair dealing is on.
+    // The first entropy commitment we see is kept, so a host that changes it later
+    // fails verification instead of getting to pick its entropy after seeing ours
+    entropy: Entropy,
+    entropy_commitment: Option<Commitment>,
+    fair_dealing: bool,
+    seed_commitment: Option<Commitment>,
+    // version of the game update that started the game
+    game_state_version: u64,
+    ready: bool,
+    countdown: Option<u64>,
+    settings: GameSettings,
+    // password for the room, asked from the player when the host challenges us
+    password: Option<String>,
+    challenge: Option<Nonce>,
+    proof_sent: bool,
+    // room code we are looking for on the LAN, cleared once we dial the host
+    room_code: Option<String>,
+    // host peer id when known from the address, relayed joins connect to the relay first
+    target_peer: Option<PeerId>,
 }
 
 // Lobby-specific impl
 impl Client<LobbyClientState> {
-    pub async fn new(player_name: String) -> Result<Self, Box<dyn Error>> {
-        let local_key = identity::Keypair::generate_ed25519();
-        let local_peer_id = PeerId::from(local_key.public());
-
-        log::client(format!("Local peer ID: {local_peer_id}"));
-
-        // encrypted, multiplexed TCP transport
-        let transport = tcp::tokio::Transport::default()
-            .upgrade(upgrade::Version::V1)
-            .authenticate(noise::Config::new(&local_key)?)
-            .multiplex(yamux::Config::default())
-            .boxed();
-
-        let behaviour = BobaGoBehaviour::new(local_peer_id)?;
+    /// Client playing as `identity`, under its profile name and avatar
+    pub async fn new(identity: &Identity) -> Result<Self, Box<dyn Error>> {
+        let swarm = swarm::build_swarm(identity.keypair.clone()).await?;
+        let mut client = Self::with_swarm(swarm, identity.profile.name.clone())?;
+        client.set_avatar(identity.profile.avatar);
+        Ok(client)
+    }
 
-        let mut swarm = Swarm::new(
-            transport,
-            behaviour,
-            local_peer_id,
-            libp2p::swarm::Config::with_tokio_executor(),
-        );
+    /// Client on an already built swarm, e.g. one from `swarm::build_memory_swarm`
+    pub fn with_swarm(mut swarm: Swarm<BobaGoBehaviour>, player_name: String) -> Result<Self, Box<dyn Error>> {
+        log::client(format!("Local peer ID: {}", swarm.local_peer_id()));
 
         let topic = IdentTopic::new("boba-go-lobby");
         swarm.behaviour_mut().gossipsub.subscribe(&topic)?;
 
+        let chat = ChatLog::new(None, player_name.clone());
         let state = LobbyClientState {
             player_name,
+            avatar: Avatar::default(),
             player_id: None,
+            room_name: String::new(),
             lobby_players: vec![],
             host_peer_id: None,
             join_request_sent: false,
+            entropy: fairness::random_entropy(),
+            entropy_commitment: None,
+            fair_dealing: false,
+            seed_commitment: None,
+            game_state_version: 0,
+            ready: false,
+            countdown: None,
+            settings: GameSettings::default(),
+            password: None,
+            challenge: None,
+            proof_sent: false,
+            room_code: None,
+            target_peer: None,
         };
 
         Ok(Self {
             swarm,
             state,
             topic,
+            chat,
+            host_seq: None,
+            latency: LatencyTracker::new(),
+            room_latency: HashMap::new(),
         })
     }
 
+    /// Avatar sent with the join request, so set it before joining
+    pub fn set_avatar(&mut self, avatar: Avatar) {
+        self.state.avatar = avatar;
+    }
+
+    /// Join by room code or by multiaddr. Room codes are resolved through
+    /// local discovery, the host is dialed once it shows up.
+    pub fn join(&mut self, target: &str) -> Result<(), Box<dyn Error>> {
+        match room_code::parse_room_code(target) {
+            Some(code) => {
+                if !self.swarm.behaviour().mdns.is_enabled() {
+                    return Err("Room codes only work on the local network and discovery is unavailable, use the host address instead".into());
+                }
+                log::client(format!("Looking for room {code} on the local network"));
+                self.state.room_code = Some(code);
+                Ok(())
+            }
+            None => {
+                let address: Multiaddr = target.trim().parse()?;
+                self.state.target_peer = address.iter().last().and_then(|p| match p {
+                    libp2p::multiaddr::Protocol::P2p(peer) => Some(peer),
+                    _ => None,
+                });
+                self.swarm.dial(address)?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Room code we are still searching for, if any
+    pub fn searching_room_code(&self) -> Option<&str> {
+        self.state.room_code.as_deref()
+    }
+
+    // dial the first discovered host whose peer id matches our room code
+    fn handle_discovered(&mut self, peers: Vec<(PeerId, Multiaddr)>) {
+        let Some(code) = self.state.room_code.clone() else {
+            return;
+        };
+        if let Some((peer, address)) = peers.into_iter().find(|(peer, _)| room_code::room_code(peer) == code) {
+            log::client(format!("Found room {code} at {address}"));
+            if self.swarm.dial(address.with_p2p(peer).unwrap_or_else(|a| a)).is_ok() {
+                self.state.room_code = None;
+                self.state.target_peer = Some(peer);
+            }
+        }
+    }
+
     /// Send join request to host
-    fn send_join_request(&mut self, peer_id: PeerId) {
+    fn send_join_request(&mut self, peer_id: PeerId, password_proof: Option<PasswordProof>) {
+        self.state.proof_sent = password_proof.is_some();
         let request = ClientRequest::Lobby(ClientMessage::JoinRequest {
             player_name: self.state.player_name.clone(),
+            version: VersionInfo::local(),
+            password_proof,
+            avatar: self.state.avatar,
         });
 
         self.swarm
@@ -76,10 +169,114 @@ impl Client<LobbyClientState> {
             .send_request(&peer_id, request);
     }
 
+    // answer a password challenge, returns false if we still need the password
+    fn answer_challenge(&mut self) -> bool {
+        let (Some(host_peer), Some(password)) = (self.state.host_peer_id, &self.state.password) else {
+            return false;
+        };
+        let Some(nonce) = self.state.challenge.take() else {
+            return false;
+        };
+        let proof = auth::password_proof(password, &nonce, &host_peer, self.swarm.local_peer_id());
+        self.send_join_request(host_peer, Some(proof));
+        true
+    }
+
+    /// Try joining again with the room password, after `ClientEvent::PasswordRequired`
+    pub fn submit_password(&mut self, password: String) {
+        self.state.password = Some(password);
+        if !self.answer_challenge() {
+            // the last challenge was used up, ask for a new one
+            if let Some(host_peer) = self.state.host_peer_id {
+                self.send_join_request(host_peer, None);
+            }
+        }
+    }
+
+    /// Name of the room, known once the host answered the join request
+    pub fn room_name(&self) -> &str {
+        &self.state.room_name
+    }
+
+    /// Our identity, the host needs this to invite us to invite-only rooms
+    pub fn local_peer_id(&self) -> PeerId {
+        *self.swarm.local_peer_id()
+    }
+
     pub fn get_lobby_players(&self) -> Vec<LobbyPlayer> {
         self.state.lobby_players.clone()
     }
 
+    /// Tell the host whether we are ready to start
+    pub fn set_ready(&mut self, ready: bool) {
+        if let Some(host_peer) = self.state.host_peer_id
+            && self.state.player_id.is_some()
+        {
+            self.swarm
+                .behaviour_mut()
+                .request_response
+                .send_request(&host_peer, ClientRequest::Lobby(ClientMessage::SetReady { ready }));
+            self.state.ready = ready;
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.state.ready
+    }
+
+    /// Seconds until the host starts the game, if a countdown is running
+    pub fn countdown(&self) -> Option<u64> {
+        self.state.countdown
+    }
+
+    /// Rules the host picked for the next game
+    pub fn settings(&self) -> &GameSettings {
+        &self.state.settings
+    }
+
+    // keep our view of the lobby in sync with the host
+    fn apply_lobby_update(
+        &mut self,
+        players: Vec<LobbyPlayer>,
+        countdown: Option<u64>,
+        entropy_commitment: Option<Commitment>,
+        seed_commitment: Option<Commitment>,
+    ) {
+        if let Some(me) = self.state.player_id.and_then(|id| players.iter().find(|p| p.id == id)) {
+            self.state.ready = me.ready;
+        }
+        self.chat.set_senders(protocol::lobby_chat_senders(&players));
+        self.state.lobby_players = players;
+        self.state.countdown = countdown;
+        // the latest one counts, it changes whenever someone joins or leaves
+        self.state.seed_commitment = seed_commitment;
+        self.observe_entropy_commitment(entropy_commitment);
+    }
+
+    // pin the host's first commitment and only then send our entropy
+    fn observe_entropy_commitment(&mut self, commitment: Option<Commitment>) {
+        self.state.fair_dealing = commitment.is_some();
+        let Some(commitment) = commitment else {
+            return;
+        };
+        match self.state.entropy_commitment {
+            None => {
+                self.state.entropy_commitment = Some(commitment);
+                if let Some(host_peer) = self.state.host_peer_id {
+                    let request = ClientMessage::SubmitEntropy { entropy: self.state.entropy };
+                    self.swarm
+                        .behaviour_mut()
+                        .request_response
+                        .send_request(&host_peer, ClientRequest::Lobby(request));
+                }
+            }
+            Some(pinned) if pinned != commitment => {
+                log::client("Host changed its entropy commitment, the deal will fail verification");
+            }
+            Some(_) => {}
+        }
+    }
+
     pub fn subscribe_to_game_topic(&mut self) -> Result<(), Box<dyn Error>> {
         let game_topic = IdentTopic::new("boba-go-game");
         self.swarm.behaviour_mut().gossipsub.subscribe(&game_topic)?;
@@ -91,10 +288,40 @@ impl Client<LobbyClientState> {
         self.state.player_id
     }
 
+    /// Our seat in the game, the position of our lobby id in the player list
+    pub fn game_seat(&self) -> Option<usize> {
+        let player_id = self.state.player_id?;
+        self.state.lobby_players.iter().position(|p| p.id == player_id)
+    }
+
+    /// Version of the update the game started with
+    pub fn game_state_version(&self) -> u64 {
+        self.state.game_state_version
+    }
+
+    /// Latency of a player as the host measured it, our own entry is our round trip to the host
+    pub fn player_latency(&self, player_id: usize) -> Option<PlayerLatency> {
+        if Some(player_id) == self.state.player_id {
+            return self.host_latency(self.state.host_peer_id);
+        }
+        self.room_latency.get(&player_id).copied()
+    }
+
     pub fn get_host_peer_id(&self) -> Option<PeerId> {
         self.state.host_peer_id
     }
 
+    pub fn is_fair_dealing(&self) -> bool {
+        self.state.fair_dealing
+    }
+
+    /// Entropy we contributed and the host's commitments, used to verify the deal afterwards
+    pub fn fairness_commitments(&self) -> (Entropy, Option<Commitment>, Option<Commitment>) {
+        let entropy_commitment = self.state.entropy_commitment.filter(|_| self.state.fair_dealing);
+        let seed_commitment = self.state.seed_commitment.filter(|_| self.state.fair_dealing);
+        (self.state.entropy, entropy_commitment, seed_commitment)
+    }
+
     /// run event loop
     /// events = lobby join accept/reject, update, disconnect, error
     pub async fn next_event(&mut self) -> Option<ClientEvent> {
@@ -114,14 +341,33 @@ impl Client<LobbyClientState> {
                                             player_id,
                                             rejection_reason,
                                             lobby_players,
+                                            host_version,
+                                            entropy_commitment,
+                                            settings,
+                                            room_name,
                                         } => {
+                                            log::client(format!("Host version: {host_version:?}"));
+                                            if !room_name.is_empty() {
+                                                self.state.room_name = room_name;
+                                            }
                                             if accepted {
                                                 self.state.player_id = player_id;
+                                                self.observe_entropy_commitment(entropy_commitment);
+                                                self.state.settings = settings;
+                                                self.chat.set_senders(protocol::lobby_chat_senders(&lobby_players));
                                                 self.state.lobby_players = lobby_players.clone();
+                                                if let Some(id) = player_id {
+                                                    self.chat.set_local_player_id(id);
+                                                }
                                                 return Some(ClientEvent::JoinedLobby {
                                                     player_id: player_id.unwrap(),
                                                     lobby_players,
                                                 });
+                                            } else if self.state.proof_sent {
+                                                // most likely a wrong password, let the player try again
+                                                self.state.password = None;
+                                                self.state.proof_sent = false;
+                                                return Some(ClientEvent::PasswordRequired { reason: rejection_reason });
                                             } else {
                                                 return Some(ClientEvent::JoinRejected {
                                                     reason: rejection_reason
@@ -129,63 +375,123 @@ impl Client<LobbyClientState> {
                                                 });
                                             }
                                         }
+                                        HostMessage::PasswordChallenge { nonce } => {
+                                            self.state.challenge = Some(nonce);
+                                            if !self.answer_challenge() {
+                                                return Some(ClientEvent::PasswordRequired { reason: None });
+                                            }
+                                        }
+                                        // reply to SetReady
+                                        HostMessage::LobbyUpdate { players, countdown, entropy_commitment, seed_commitment } => {
+                                            self.apply_lobby_update(players.clone(), countdown, entropy_commitment, seed_commitment);
+                                            return Some(ClientEvent::LobbyUpdated { players });
+                                        }
                                         _ => {}
                                     }
                                 }
                             }
                         }
                         request_response::Event::OutboundFailure { error, .. } => {
-                            return Some(ClientEvent::Error {
-                                message: format!("Request failed: {error:?}"),
-                            });
+                            let message = match error {
+                                request_response::OutboundFailure::UnsupportedProtocols => {
+                                    "Host is not running a compatible version of boba-go".to_string()
+                                }
+                                request_response::OutboundFailure::Io(e) => {
+                                    format!("Could not read host response, it may be running a different version ({e})")
+                                }
+                                other => format!("Request failed: {other:?}"),
+                            };
+                            return Some(ClientEvent::Error { message });
                         }
                         _ => {}
                     }
                 }
                 SwarmEvent::Behaviour(BobaGoBehaviourEvent::Gossipsub(gossipsub_event)) => {
-                    if let libp2p::gossipsub::Event::Message { message, .. } = gossipsub_event {
+                    if let libp2p::gossipsub::Event::Message { message, message_id, propagation_source } = gossipsub_event {
                         log::client(format!("Received message on topic: {:?}", message.topic));
-                        if let Ok(json_str) = std::str::from_utf8(&message.data) {
-                            log::client(format!("Received gossipsub message: {}", json_str));
-                            if let Ok(host_message) =
-                                serde_json::from_str::<HostMessage>(json_str)
-                            {
-                                match host_message {
-                                    HostMessage::LobbyUpdate { players } => {
-                                        self.state.lobby_players = players.clone();
-                                        return Some(ClientEvent::LobbyUpdated { players });
+                        let acceptance = gossip::validate_client_gossip(&message, self.state.host_peer_id, &mut self.host_seq, &self.chat);
+                        if !gossip::report(&mut self.swarm, &message_id, &propagation_source, acceptance) {
+                            log::client(format!("Dropped gossip published by {:?} via {propagation_source}", message.source));
+                            continue;
+                        }
+                        if let Some(gossip) = chat::parse_chat_gossip(&message.data) {
+                            let from_host = message.source.is_some() && message.source == self.state.host_peer_id;
+                            if let Some(message) = self.chat.handle_gossip(gossip, message.source, from_host) {
+                                return Some(ClientEvent::ChatReceived { message });
+                            }
+                            continue;
+                        }
+                        // validation made sure anything past this point was published by the host
+                        if let Some(host_message) = gossip::parse_host_gossip::<HostMessage>(&message.data) {
+                            match host_message {
+                                HostMessage::LobbyUpdate { players, countdown, entropy_commitment, seed_commitment } => {
+                                    self.apply_lobby_update(players.clone(), countdown, entropy_commitment, seed_commitment);
+                                    return Some(ClientEvent::LobbyUpdated { players });
+                                }
+                                HostMessage::SettingsUpdate { settings } => {
+                                    self.state.settings = settings.clone();
+                                    self.state.ready = false;
+                                    return Some(ClientEvent::SettingsUpdated { settings });
+                                }
+                                HostMessage::Kicked { player_id, reason } if Some(player_id) == self.state.player_id => {
+                                    if let Some(host) = self.state.host_peer_id.take() {
+                                        self.swarm.disconnect_peer_id(host).ok();
                                     }
-                                    _ => {}
+                                    return Some(ClientEvent::Kicked { reason });
                                 }
-                            } else if let Ok(game_message) =
-                                serde_json::from_str::<super::super::game::GameHostMessage>(json_str)
-                            {
-                                use super::super::game::GameHostMessage;
-                                log::client("Parsed as GameHostMessage".to_string());
-                                if let GameHostMessage::GameUpdate { all_hands, players_public, game_status } = game_message {
-                                    log::client("Detected GameUpdate, transitioning to game".to_string());
-                                    return Some(ClientEvent::GameStarting {
-                                        all_hands,
-                                        players_public,
-                                        game_status,
-                                    });
+                                HostMessage::Latency { players } => self.apply_latency_report(players),
+                                HostMessage::StartHostless { seats, seed, round_count, card_distribution } => {
+                                    let config = GameConfig {
+                                        player_names: seats.iter().map(|s| s.name.clone()).collect(),
+                                        seed: Some(seed),
+                                        round_count,
+                                        card_distribution,
+                                    };
+                                    return Some(ClientEvent::HostlessStarting { seats, config });
                                 }
-                            } else {
-                                log::client("Failed to parse message".to_string());
+                                _ => {}
                             }
+                        } else if let Some(game_message) =
+                            gossip::parse_host_gossip::<super::super::game::GameHostMessage>(&message.data)
+                        {
+                            use super::super::game::GameHostMessage;
+                            log::client("Parsed as GameHostMessage".to_string());
+                            if let GameHostMessage::GameUpdate { all_hands, players_public, game_status, state_version } = game_message {
+                                log::client("Detected GameUpdate, transitioning to game".to_string());
+                                self.state.game_state_version = state_version;
+                                return Some(ClientEvent::GameStarting {
+                                    all_hands,
+                                    players_public,
+                                    game_status,
+                                });
+                            }
+                        } else {
+                            log::client("Failed to parse message".to_string());
                         }
                     }
                 }
+                SwarmEvent::Behaviour(BobaGoBehaviourEvent::Ping(event)) => {
+                    self.handle_ping(event, self.state.host_peer_id);
+                }
+                SwarmEvent::Behaviour(BobaGoBehaviourEvent::Mdns(libp2p::mdns::Event::Discovered(peers))) => {
+                    self.handle_discovered(peers);
+                }
+                SwarmEvent::ConnectionEstablished { peer_id, .. }
+                    if self.state.target_peer.is_some_and(|target| target != peer_id) =>
+                {
+                    // the relay on the way to the host, not the host itself
+                    log::client(format!("Connected to relay {peer_id}"));
+                }
                 SwarmEvent::ConnectionEstablished { peer_id, .. } => {
                     super::super::events::handle_client_connection_established(&mut self.swarm, peer_id);
                     log::client(format!("Client topics: {:?}", self.swarm.behaviour().gossipsub.topics().collect::<Vec<_>>()));
                     self.state.host_peer_id = Some(peer_id);
                     if !self.state.join_request_sent {
                         self.state.join_request_sent = true;
-                        self.send_join_request(peer_id);
+                        self.send_join_request(peer_id, None);
                     }
                 }
-                SwarmEvent::ConnectionClosed { peer_id, .. } => {
+                SwarmEvent::ConnectionClosed { peer_id, num_established: 0, .. } => {
                     super::super::events::log_client_connection_closed(peer_id);
                     if Some(peer_id) == self.state.host_peer_id {
                         return Some(ClientEvent::Disconnected);
@@ -207,14 +513,31 @@ pub enum ClientEvent {
     JoinRejected {
         reason: String,
     },
+    // the room has a password, answer with `submit_password`
+    PasswordRequired {
+        reason: Option<String>,
+    },
+    Kicked {
+        reason: String,
+    },
     LobbyUpdated {
         players: Vec<LobbyPlayer>,
     },
+    SettingsUpdated {
+        settings: GameSettings,
+    },
+    HostlessStarting {
+        seats: Vec<HostlessSeat>,
+        config: GameConfig,
+    },
     GameStarting {
         all_hands: Vec<std::collections::HashMap<crate::engine::models::CardKind, usize>>,
         players_public: Vec<crate::engine::models::PlayerPublic>,
         game_status: crate::engine::state::GameStatus,
     },
+    ChatReceived {
+        message: ChatMessage,
+    },
     Disconnected,
     Error {
         message: String,
@@ -1,85 +1,163 @@
 use libp2p::{
-    core::upgrade,
     futures::StreamExt,
     gossipsub::IdentTopic,
-    identity, noise,
+    identity,
     swarm::{Swarm, SwarmEvent},
-    tcp, yamux, Multiaddr, PeerId, Transport,
+    Multiaddr, PeerId,
 };
 use std::error::Error;
 
 use crate::network::behaviour::{BobaGoBehaviour, BobaGoBehaviourEvent, ClientRequest, HostResponse};
 use crate::network::Host;
-use super::protocol::{ClientMessage, HostMessage, LobbyPlayer};
+use crate::network::{gossip, room_code, swarm};
+use crate::network::identity::{Avatar, Identity};
+use crate::network::chat::{self, ChatLog, ChatMessage};
+use crate::network::latency::{LatencyTracker, PlayerLatency};
+use crate::engine::GameSettings;
+use crate::network::version::VersionInfo;
+use super::auth::PasswordProof;
+use super::protocol::{self, ClientMessage, HostMessage, LobbyPlayer};
 use super::state::LobbyHostState;
 use crate::log;
 
 // Lobby-specific impl
 impl Host<LobbyHostState> {
-    pub async fn new(room_name: String, host_player_name: String) -> Result<Self, Box<dyn Error>> {
-        let local_key = identity::Keypair::generate_ed25519();
-        let local_peer_id = PeerId::from(local_key.public());
-
-        log::host(format!("Local peer ID: {local_peer_id}"));
-
-        // encrypted, multiplexed TCP transport
-        let transport = tcp::tokio::Transport::default()
-            .upgrade(upgrade::Version::V1)
-            .authenticate(noise::Config::new(&local_key)?)
-            .multiplex(yamux::Config::default())
-            .boxed();
-
-        let behaviour = BobaGoBehaviour::new(local_peer_id)?;
-        let mut swarm = Swarm::new(
-            transport,
-            behaviour,
-            local_peer_id,
-            libp2p::swarm::Config::with_tokio_executor(),
-        );
+    /// Lobby hosted as `identity`, players see its name and avatar
+    pub async fn new(room_name: String, identity: &Identity) -> Result<Self, Box<dyn Error>> {
+        let swarm = swarm::build_swarm(identity.keypair.clone()).await?;
+        let mut host = Self::with_swarm(swarm, room_name, identity.profile.name.clone())?;
+        host.state.host_avatar = identity.profile.avatar;
+        Ok(host)
+    }
+
+    /// Lobby on an already built swarm, e.g. one from `swarm::build_memory_swarm`
+    pub fn with_swarm(swarm: Swarm<BobaGoBehaviour>, room_name: String, host_player_name: String) -> Result<Self, Box<dyn Error>> {
+        let chat = ChatLog::new(Some(0), host_player_name.clone());
+        Self::with_state(swarm, LobbyHostState::new(room_name, host_player_name), chat)
+    }
+
+    /// Lobby for a dedicated server, the host runs the room without a seat
+    /// and chats under `host_name`
+    pub async fn new_dedicated(room_name: String, host_name: String) -> Result<Self, Box<dyn Error>> {
+        let swarm = swarm::build_swarm(identity::Keypair::generate_ed25519()).await?;
+        let chat = ChatLog::new(Some(0), host_name.clone());
+        Self::with_state(swarm, LobbyHostState::dedicated(room_name, host_name), chat)
+    }
+
+    fn with_state(mut swarm: Swarm<BobaGoBehaviour>, state: LobbyHostState, chat: ChatLog) -> Result<Self, Box<dyn Error>> {
+        log::host(format!("Local peer ID: {}", swarm.local_peer_id()));
 
         let topic = IdentTopic::new("boba-go-lobby");
         swarm.behaviour_mut().gossipsub.subscribe(&topic)?;
 
-        let state = LobbyHostState::new(room_name, host_player_name);
-
         Ok(Self {
             swarm,
             state,
             topic,
+            chat,
+            gossip_seq: 0,
+            latency: LatencyTracker::new(),
         })
     }
 
+    /// Short code players on the same network can join with
+    pub fn room_code(&self) -> String {
+        room_code::room_code(self.swarm.local_peer_id())
+    }
+
     pub fn get_lobby_players(&self) -> Vec<LobbyPlayer> {
         self.state.get_all_players()
     }
 
+    // rejected JoinResponse with the reason shown to the player
+    fn join_rejection(&self, reason: String) -> (HostMessage, Option<HostEvent>) {
+        let response = HostMessage::JoinResponse {
+            accepted: false,
+            player_id: None,
+            rejection_reason: Some(reason),
+            lobby_players: vec![],
+            host_version: VersionInfo::local(),
+            entropy_commitment: None,
+            settings: self.state.settings().clone(),
+            room_name: self.state.room_name.clone(),
+        };
+        (response, None)
+    }
+
     // process join request, return response and event
-    fn process_join_request(&mut self, peer: PeerId, player_name: String) -> (HostMessage, Option<HostEvent>) {
-        log::host(format!("Join request from peer {peer} with name '{player_name}'"));
+    fn process_join_request(
+        &mut self,
+        peer: PeerId,
+        player_name: String,
+        version: VersionInfo,
+        password_proof: Option<PasswordProof>,
+        avatar: Avatar,
+    ) -> (HostMessage, Option<HostEvent>) {
+        log::host(format!("Join request from peer {peer} with name '{player_name}', version {version:?}"));
+
+        let host_version = VersionInfo::local();
+        if let Err(reason) = host_version.check_compatible(&version) {
+            log::host(format!("Rejecting incompatible client: {reason}"));
+            return self.join_rejection(reason);
+        }
+
+        if self.state.is_banned(&peer) {
+            return self.join_rejection("You are banned from this lobby".to_string());
+        }
+        self.state.cancel_disconnect(&peer);
+
+        if !self.state.is_invited(&peer) {
+            log::host(format!("Rejecting uninvited peer {peer}"));
+            return self.join_rejection(format!(
+                "This room is invite-only, ask the host to invite your identity {peer}"
+            ));
+        }
+
+        if self.state.has_password() {
+            let local_peer_id = *self.swarm.local_peer_id();
+            match password_proof {
+                // first attempt, the client has to prove it knows the password
+                None => {
+                    let nonce = self.state.issue_challenge(peer);
+                    return (HostMessage::PasswordChallenge { nonce }, None);
+                }
+                Some(proof) if !self.state.verify_password(&local_peer_id, &peer, &proof) => {
+                    log::host(format!("Wrong password from peer {peer}"));
+                    return self.join_rejection("Wrong password".to_string());
+                }
+                Some(_) => {}
+            }
+        }
+
+        if self.state.is_full() {
+            return self.join_rejection(format!("Lobby is full ({} players max)", self.state.max_seats()));
+        }
 
         if self.state.is_name_taken(&player_name) {
             log::host("Name taken: true");
-            let response = HostMessage::JoinResponse {
-                accepted: false,
-                player_id: None,
-                rejection_reason: Some("Name already taken".to_string()),
-                lobby_players: vec![],
-            };
-            return (response, None);
+            return self.join_rejection("Name already taken".to_string());
         }
 
         log::host("Name taken: false");
 
-        // add player and broadcast update
-        let player_id = self.state.add_player(peer, player_name.clone());
+        // add player and broadcast update, the new player isn't ready so any countdown stops
+        let player_id = self.state.add_player(peer, player_name.clone(), avatar);
+        self.state.cancel_countdown();
         let lobby_players = self.get_lobby_players();
         self.broadcast_lobby_update();
+        if !self.chat.muted_players().is_empty() {
+            self.publish_mute_list();
+        }
 
         let response = HostMessage::JoinResponse {
             accepted: true,
             player_id: Some(player_id),
             rejection_reason: None,
             lobby_players,
+            host_version,
+            entropy_commitment: self.state.entropy_commitment(),
+            settings: self.state.settings().clone(),
+            room_name: self.state.room_name.clone(),
         };
 
         let event = HostEvent::PlayerJoined {
@@ -91,18 +169,152 @@ impl Host<LobbyHostState> {
         (response, Some(event))
     }
 
+    /// Turn fair dealing on or off, clients see the commitment in the next lobby update
+    pub fn set_fair_dealing(&mut self, enabled: bool) {
+        self.state.set_fair_dealing(enabled);
+        log::host(format!("Fair dealing {}", if enabled { "enabled" } else { "disabled" }));
+        self.broadcast_lobby_update();
+    }
+
+    pub fn is_fair_dealing(&self) -> bool {
+        self.state.is_fair_dealing()
+    }
+
+    /// Require a password from players joining from now on
+    pub fn set_password(&mut self, password: Option<String>) {
+        self.state.set_password(password);
+        log::host(format!("Room password {}", if self.state.has_password() { "set" } else { "cleared" }));
+    }
+
+    /// Only let invited identities join, players already in the lobby stay invited
+    pub fn set_invite_only(&mut self, enabled: bool) {
+        self.state.set_invite_only(enabled);
+        log::host(format!("Invite-only {}", if enabled { "enabled" } else { "disabled" }));
+    }
+
+    pub fn is_invite_only(&self) -> bool {
+        self.state.is_invite_only()
+    }
+
+    /// Add a player identity to the invite list
+    pub fn invite(&mut self, peer: PeerId) {
+        log::host(format!("Invited {peer}"));
+        self.state.invite(peer);
+    }
+
+    pub fn settings(&self) -> &GameSettings {
+        self.state.settings()
+    }
+
+    /// Change the rules for the next game and tell everyone in the lobby
+    pub fn set_settings(&mut self, settings: GameSettings) {
+        if &settings == self.state.settings() {
+            return;
+        }
+        log::host(format!("Game settings changed: {}", settings.summary()));
+        self.state.set_settings(settings.clone());
+
+        if let Err(e) = self.publish_host_message(HostMessage::SettingsUpdate { settings }) {
+            log::host(format!("Failed to publish settings: {e}"));
+        }
+        self.broadcast_lobby_update();
+    }
+
+    fn lobby_update(&self, players: Vec<LobbyPlayer>) -> HostMessage {
+        HostMessage::LobbyUpdate {
+            players,
+            countdown: self.state.countdown_remaining(),
+            entropy_commitment: self.state.entropy_commitment(),
+            seed_commitment: self.state.seed_commitment(),
+        }
+    }
+
+    // update a player's ready flag, un-readying stops the countdown
+    fn process_set_ready(&mut self, peer: PeerId, ready: bool) -> (HostMessage, Option<HostEvent>) {
+        let mut event = None;
+        if self.state.set_ready(&peer, ready) {
+            if !ready {
+                self.state.cancel_countdown();
+            }
+            self.broadcast_lobby_update();
+            if let Some(player_id) = self.state.get_player_id(&peer) {
+                event = Some(HostEvent::ReadyChanged { player_id, ready });
+            }
+        }
+        (self.lobby_update(self.get_lobby_players()), event)
+    }
+
+    /// Remove a player from the lobby, and keep them out if `ban` is set
+    pub fn kick_player(&mut self, player_id: usize, ban: bool) -> Result<(), String> {
+        let peer = self.state.get_peer_id(player_id).ok_or("No such player")?;
+
+        self.state.remove_player(&peer);
+        if ban {
+            self.state.ban(peer);
+        }
+        self.state.cancel_countdown();
+
+        let reason = if ban { "You were banned by the host" } else { "You were kicked by the host" };
+        let message = HostMessage::Kicked { player_id, reason: reason.to_string() };
+        if let Err(e) = self.publish_host_message(message) {
+            log::host(format!("Failed to publish kick: {e}"));
+        }
+        self.state.schedule_disconnect(peer);
+        log::host(format!("{} player {player_id} ({peer})", if ban { "Banned" } else { "Kicked" }));
+
+        self.broadcast_lobby_update();
+        Ok(())
+    }
+
+    /// Start the countdown to the game, fails if anyone isn't ready
+    pub fn start_countdown(&mut self) -> Result<(), String> {
+        self.state.start_countdown()?;
+        self.broadcast_lobby_update();
+        Ok(())
+    }
+
+    pub fn cancel_countdown(&mut self) {
+        if self.state.cancel_countdown() {
+            self.broadcast_lobby_update();
+        }
+    }
+
+    pub fn countdown_remaining(&self) -> Option<u64> {
+        self.state.countdown_remaining()
+    }
+
+    /// Call regularly while a countdown runs, sends the remaining seconds
+    /// to clients and returns true once it reaches zero
+    pub fn poll_countdown(&mut self) -> bool {
+        if let Some(remaining) = self.state.take_countdown_change() {
+            self.broadcast_lobby_update();
+            return remaining == 0;
+        }
+        false
+    }
+
     // broadcast lobby update to all clients
     fn broadcast_lobby_update(&mut self) {
         let players = self.get_lobby_players();
-        let message = HostMessage::LobbyUpdate { players };
+        self.chat.set_senders(protocol::lobby_chat_senders(&players));
+        let message = self.lobby_update(players);
+        // fails with InsufficientPeers while nobody has joined, that's fine
+        self.publish_host_message(message).ok();
+    }
 
-        if let Ok(json) = serde_json::to_string(&message) {
-            self.swarm
-                .behaviour_mut()
-                .gossipsub
-                .publish(self.topic.clone(), json.as_bytes())
-                .ok();
-        }
+    /// Round trip to a player, None for the host and until the first ping
+    pub fn player_latency(&self, player_id: usize) -> Option<PlayerLatency> {
+        self.state.get_peer_id(player_id).and_then(|peer| self.latency.latency(&peer))
+    }
+
+    // send everyone the latency of every player we've measured
+    fn broadcast_latency(&mut self) {
+        let players = self
+            .get_lobby_players()
+            .iter()
+            .filter_map(|p| Some((p.id, self.player_latency(p.id)?)))
+            .collect();
+        self.publish_host_message(HostMessage::Latency { players }).ok();
     }
 
     /// Handle request-response network events
@@ -110,23 +322,35 @@ impl Host<LobbyHostState> {
         use libp2p::request_response;
 
         match rr_event {
-            request_response::Event::Message { peer, message, .. } => {
-                if let request_response::Message::Request {
-                    request: ClientRequest::Lobby(ClientMessage::JoinRequest { player_name }),
-                    channel,
-                    ..
-                } = message
-                {
-                    let (response, event) = self.process_join_request(peer, player_name);
-
-                    self.swarm
-                        .behaviour_mut()
-                        .request_response
-                        .send_response(channel, HostResponse::Lobby(response))
-                        .ok();
-
-                    return event;
-                }
+            request_response::Event::Message {
+                peer,
+                message: request_response::Message::Request { request: ClientRequest::Lobby(request), channel, .. },
+                ..
+            } => {
+                let (response, event) = match request {
+                    ClientMessage::JoinRequest { player_name, version, password_proof, avatar } => {
+                        self.process_join_request(peer, player_name, version, password_proof, avatar)
+                    }
+                    ClientMessage::SetReady { ready } => self.process_set_ready(peer, ready),
+                    ClientMessage::SubmitEntropy { entropy } => {
+                        self.state.set_player_entropy(peer, entropy);
+                        // the last contribution completes the seed, everyone needs its commitment
+                        self.broadcast_lobby_update();
+                        (self.lobby_update(self.get_lobby_players()), None)
+                    }
+                };
+
+                self.swarm
+                    .behaviour_mut()
+                    .request_response
+                    .send_response(channel, HostResponse::Lobby(response))
+                    .ok();
+
+                return event;
+            }
+            request_response::Event::InboundFailure { peer, error, .. } => {
+                // usually a peer built from a different version sending a message we can't decode
+                log::host(format!("Inbound request from {peer} failed: {error:?}"));
             }
             _ => {}
         }
@@ -136,7 +360,9 @@ impl Host<LobbyHostState> {
     /// Handle connection closed event
     fn handle_connection_closed(&mut self, peer_id: PeerId, _cause: &Option<libp2p::swarm::ConnectionError>) -> Option<HostEvent> {
         super::super::events::log_host_connection_closed(peer_id);
+        self.latency.remove(&peer_id);
         if self.state.remove_player(&peer_id).is_some() {
+            self.state.cancel_countdown();
             self.broadcast_lobby_update();
             return Some(HostEvent::PlayerLeft { peer_id });
         }
@@ -147,8 +373,26 @@ impl Host<LobbyHostState> {
     /// events = listening, join request, player joined/left, gossipsub message, connection established/closed, error
     pub async fn next_event(&mut self) -> Option<HostEvent> {
         loop {
-            match self.swarm.select_next_some().await {
+            let event = match self.state.next_disconnect() {
+                Some(at) => tokio::select! {
+                    event = self.swarm.select_next_some() => event,
+                    _ = tokio::time::sleep_until(at.into()) => {
+                        for peer in self.state.take_due_disconnects() {
+                            self.swarm.disconnect_peer_id(peer).ok();
+                        }
+                        continue;
+                    }
+                },
+                None => self.swarm.select_next_some().await,
+            };
+            match event {
                 SwarmEvent::NewListenAddr { address, .. } => {
+                    // relayed addresses are only dialable with our peer id on the end
+                    let address = if swarm::is_relayed(&address) {
+                        address.with_p2p(*self.swarm.local_peer_id()).unwrap_or_else(|a| a)
+                    } else {
+                        address
+                    };
                     return Some(HostEvent::Listening { address });
                 }
                 SwarmEvent::Behaviour(BobaGoBehaviourEvent::RequestResponse(rr_event)) => {
@@ -157,14 +401,45 @@ impl Host<LobbyHostState> {
                     }
                 }
                 SwarmEvent::ConnectionEstablished { peer_id, .. } => {
+                    if self.state.is_banned(&peer_id) {
+                        log::host(format!("Refusing connection from banned peer {peer_id}"));
+                        self.swarm.disconnect_peer_id(peer_id).ok();
+                        continue;
+                    }
                     super::super::events::handle_host_connection_established(&mut self.swarm, peer_id);
                 }
                 SwarmEvent::Behaviour(BobaGoBehaviourEvent::Gossipsub(
                     libp2p::gossipsub::Event::Subscribed { peer_id, topic }
                 )) => {
                     log::host(format!("Peer {peer_id} subscribed to topic {:?}", topic));
+                    // updates published before the subscription reached us never got to
+                    // this player, so send them the current lobby again
+                    if self.state.get_player_id(&peer_id).is_some() {
+                        self.broadcast_lobby_update();
+                    }
+                }
+                SwarmEvent::Behaviour(BobaGoBehaviourEvent::Gossipsub(
+                    libp2p::gossipsub::Event::Message { message, message_id, propagation_source }
+                )) => {
+                    let acceptance = gossip::validate_host_gossip(&message, *self.swarm.local_peer_id(), &self.chat);
+                    if !gossip::report(&mut self.swarm, &message_id, &propagation_source, acceptance) {
+                        log::host(format!("Dropped gossip published by {:?} via {propagation_source}", message.source));
+                        continue;
+                    }
+                    if let Some(gossip) = chat::parse_chat_gossip(&message.data)
+                        && let Some(message) = self.chat.handle_gossip(gossip, message.source, false)
+                    {
+                        return Some(HostEvent::ChatReceived { message });
+                    }
+                }
+                SwarmEvent::Behaviour(BobaGoBehaviourEvent::Ping(event)) => {
+                    let report_due = self.handle_ping(event);
+                    if report_due {
+                        self.broadcast_latency();
+                    }
                 }
-                SwarmEvent::ConnectionClosed { peer_id, cause, .. } => {
+                // a relayed player may still be connected directly after hole punching
+                SwarmEvent::ConnectionClosed { peer_id, cause, num_established: 0, .. } => {
                     if let Some(event) = self.handle_connection_closed(peer_id, &cause) {
                         return Some(event);
                     }
@@ -180,4 +455,6 @@ pub enum HostEvent {
     Listening { address: Multiaddr },
     PlayerJoined { peer_id: PeerId, player_id: usize, player_name: String },
     PlayerLeft { peer_id: PeerId },
+    ReadyChanged { player_id: usize, ready: bool },
+    ChatReceived { message: ChatMessage },
 }
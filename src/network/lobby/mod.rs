@@ -1,3 +1,4 @@
+pub mod auth;
 pub mod protocol;
 pub mod state;
 pub mod client;
@@ -1,8 +1,38 @@
+use libp2p::PeerId;
 use serde::{Deserialize, Serialize};
 
+use std::collections::HashMap;
+
+use crate::engine::fairness::{Commitment, Entropy};
+use super::auth::{Nonce, PasswordProof};
+use crate::engine::models::{CardKind, GameSettings};
+use crate::network::hostless::HostlessSeat;
+use crate::network::latency::PlayerLatency;
+use crate::network::identity::Avatar;
+use crate::network::version::VersionInfo;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ClientMessage {
-    JoinRequest { player_name: String },
+    JoinRequest {
+        player_name: String,
+        #[serde(default)]
+        version: VersionInfo,
+        // answer to the host's PasswordChallenge for password-protected rooms
+        #[serde(default)]
+        password_proof: Option<PasswordProof>,
+        #[serde(default)]
+        avatar: Avatar,
+    },
+    // mark ourselves ready (or not) to start, answered with a LobbyUpdate
+    SetReady {
+        ready: bool,
+    },
+    // our contribution to the game seed, sent only once we hold the host's
+    // entropy commitment so the host can't pick its entropy after seeing ours.
+    // Answered with a LobbyUpdate
+    SubmitEntropy {
+        entropy: Entropy,
+    },
 }
 
 /// messages sent from host to clients
@@ -15,11 +45,58 @@ pub enum HostMessage {
         player_id: Option<usize>,
         rejection_reason: Option<String>,
         lobby_players: Vec<LobbyPlayer>,
+        #[serde(default)]
+        host_version: VersionInfo,
+        #[serde(default)]
+        entropy_commitment: Option<Commitment>,
+        #[serde(default)]
+        settings: GameSettings,
+        // shown in the player's list of recent rooms
+        #[serde(default)]
+        room_name: String,
+    },
+
+    // reply to a JoinRequest without a valid proof when the room has a password
+    PasswordChallenge {
+        nonce: Nonce,
     },
 
     // broadcast when players join/leave
     LobbyUpdate {
         players: Vec<LobbyPlayer>,
+        // seconds until the game starts, None when no countdown is running
+        #[serde(default)]
+        countdown: Option<u64>,
+        // set while fair dealing is enabled
+        #[serde(default)]
+        entropy_commitment: Option<Commitment>,
+        // set once every player's entropy is in, so the seed is committed before the deal
+        #[serde(default)]
+        seed_commitment: Option<Commitment>,
+    },
+
+    // broadcast whenever the host changes the rules, readiness is reset
+    SettingsUpdate {
+        settings: GameSettings,
+    },
+
+    // a player was removed by the host, they get disconnected right after
+    Kicked {
+        player_id: usize,
+        reason: String,
+    },
+
+    // round trips the host measured to each player, by player id
+    Latency {
+        players: Vec<(usize, PlayerLatency)>,
+    },
+
+    // start a hostless game, every peer builds the same Game from this
+    StartHostless {
+        seats: Vec<HostlessSeat>,
+        seed: u64,
+        round_count: usize,
+        card_distribution: Option<HashMap<CardKind, usize>>,
     },
 }
 
@@ -27,5 +104,26 @@ pub enum HostMessage {
 pub struct LobbyPlayer {
     pub id: usize,
     pub name: String,
+    #[serde(default)]
+    pub ready: bool,
+    #[serde(default)]
+    pub avatar: Avatar,
+    // empty for the host, clients know it as the peer they joined
+    #[serde(default)]
+    pub peer_id: String,
+}
+
+impl LobbyPlayer {
+    pub fn peer(&self) -> Option<PeerId> {
+        self.peer_id.parse().ok()
+    }
+}
+
+/// The id and name each player's peer chats as while in the lobby
+pub fn lobby_chat_senders(players: &[LobbyPlayer]) -> HashMap<PeerId, (usize, String)> {
+    players
+        .iter()
+        .filter_map(|p| Some((p.peer()?, (p.id, p.name.clone()))))
+        .collect()
 }
 
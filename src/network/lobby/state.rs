@@ -1,14 +1,45 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 use libp2p::PeerId;
 
+use crate::engine::constants;
+use crate::engine::GameSettings;
+use crate::engine::fairness::{self, Commitment, Entropy};
+use crate::network::game::FairDealing;
+use super::auth::{self, Nonce, PasswordProof};
 use super::protocol::LobbyPlayer;
+use crate::network::identity::Avatar;
 
+/// seconds between pressing start and the game starting
+pub const START_COUNTDOWN_SECS: u64 = 5;
+
+/// how long a kicked player gets to read why before the host hangs up
+pub const KICK_GRACE: Duration = Duration::from_millis(500);
 
 pub struct LobbyHostState {
     pub room_name: String,
     pub host_player_name: String,
+    pub host_avatar: Avatar,
+    // false for a dedicated server, which runs the room without taking a seat
+    host_plays: bool,
     players: HashMap<PeerId, LobbyPlayer>,
     next_player_id: usize,
+    // entropy each client sent once it saw our commitment
+    player_entropy: HashMap<PeerId, Entropy>,
+    host_entropy: Entropy,
+    fair_dealing: bool,
+    banned: HashSet<PeerId>,
+    countdown_started: Option<Instant>,
+    // last remaining-seconds value sent to clients
+    countdown_sent: Option<u64>,
+    settings: GameSettings,
+    password: Option<String>,
+    // outstanding password challenges, each nonce can be answered once
+    challenges: HashMap<PeerId, Nonce>,
+    invite_only: bool,
+    invited: HashSet<PeerId>,
+    // kicked peers and when to close their connection if they haven't left
+    kicked: HashMap<PeerId, Instant>,
 }
 
 impl LobbyHostState {
@@ -17,24 +48,58 @@ impl LobbyHostState {
         Self {
             room_name,
             host_player_name,
+            host_avatar: Avatar::default(),
+            host_plays: true,
             players: HashMap::new(),
             next_player_id: 0,
+            player_entropy: HashMap::new(),
+            host_entropy: fairness::random_entropy(),
+            fair_dealing: false,
+            banned: HashSet::new(),
+            countdown_started: None,
+            countdown_sent: None,
+            settings: GameSettings::default(),
+            password: None,
+            challenges: HashMap::new(),
+            invite_only: false,
+            invited: HashSet::new(),
+            kicked: HashMap::new(),
+        }
+    }
+
+    /// Lobby for a dedicated server, the host only moderates and never plays
+    pub fn dedicated(room_name: String, host_name: String) -> Self {
+        Self {
+            host_plays: false,
+            ..Self::new(room_name, host_name)
         }
     }
 
+    pub fn host_plays(&self) -> bool {
+        self.host_plays
+    }
+
+    /// Players that will be dealt in, including the host if it plays
+    pub fn seat_count(&self) -> usize {
+        self.players.len() + usize::from(self.host_plays)
+    }
+
     /// Check if a player name is already taken
     pub fn is_name_taken(&self, name: &str) -> bool {
         self.host_player_name == name || self.players.values().any(|p| p.name == name)
     }
 
     /// Add a new player to the lobby and return their assigned ID
-    pub fn add_player(&mut self, peer: PeerId, player_name: String) -> usize {
+    pub fn add_player(&mut self, peer: PeerId, player_name: String, avatar: Avatar) -> usize {
         let player_id = self.next_player_id + 1;
         self.next_player_id += 1;
 
         let lobby_player = LobbyPlayer {
             id: player_id,
             name: player_name,
+            ready: false,
+            avatar,
+            peer_id: peer.to_string(),
         };
 
         self.players.insert(peer, lobby_player);
@@ -43,36 +108,281 @@ impl LobbyHostState {
 
     /// Remove a player from the lobby
     pub fn remove_player(&mut self, peer: &PeerId) -> Option<LobbyPlayer> {
+        self.player_entropy.remove(peer);
+        self.challenges.remove(peer);
         self.players.remove(peer)
     }
 
-    /// Get all players in the lobby
+    /// Remember the entropy a player contributed, only their first contribution counts
+    pub fn set_player_entropy(&mut self, peer: PeerId, entropy: Entropy) {
+        if self.players.contains_key(&peer) {
+            self.player_entropy.entry(peer).or_insert(entropy);
+        }
+    }
+
+    pub fn is_fair_dealing(&self) -> bool {
+        self.fair_dealing
+    }
+
+    pub fn set_fair_dealing(&mut self, enabled: bool) {
+        self.fair_dealing = enabled;
+    }
+
+    /// Commitment to the host entropy, only published while fair dealing is on
+    pub fn entropy_commitment(&self) -> Option<Commitment> {
+        self.fair_dealing.then(|| fairness::commit_entropy(&self.host_entropy))
+    }
+
+    /// Commitment to the seed the game will be dealt from, once every player's
+    /// entropy is in. Clients hold it before any hand is dealt.
+    pub fn seed_commitment(&self) -> Option<Commitment> {
+        if !self.players.keys().all(|peer| self.player_entropy.contains_key(peer)) {
+            return None;
+        }
+        self.fair_dealing().map(|fair_dealing| fair_dealing.seed_commitment())
+    }
+
+    /// Host entropy and every joined player's contribution keyed by game seat,
+    /// the way clients look their own up, if fair dealing is on
+    pub fn fair_dealing(&self) -> Option<FairDealing> {
+        if !self.fair_dealing {
+            return None;
+        }
+
+        let contributions = self.get_all_players()
+            .iter()
+            .enumerate()
+            .filter_map(|(seat, player)| {
+                let peer = self.get_peer_id(player.id)?;
+                self.player_entropy.get(&peer).map(|e| (seat, *e))
+            })
+            .collect();
+
+        Some(FairDealing::new(self.host_entropy, contributions))
+    }
+
+    pub fn settings(&self) -> &GameSettings {
+        &self.settings
+    }
+
+    /// Replace the game settings, players have to ready up again for the new rules
+    pub fn set_settings(&mut self, settings: GameSettings) {
+        self.settings = settings;
+        for player in self.players.values_mut() {
+            player.ready = false;
+        }
+        self.cancel_countdown();
+    }
+
+    pub fn has_password(&self) -> bool {
+        self.password.is_some()
+    }
+
+    /// Set or clear the room password, an empty password counts as none
+    pub fn set_password(&mut self, password: Option<String>) {
+        self.password = password.filter(|p| !p.is_empty());
+        self.challenges.clear();
+    }
+
+    /// Hand out a fresh challenge for a peer, replacing any earlier one
+    pub fn issue_challenge(&mut self, peer: PeerId) -> Nonce {
+        let nonce = auth::random_nonce();
+        self.challenges.insert(peer, nonce);
+        nonce
+    }
+
+    /// Check a peer's answer to its outstanding challenge, the challenge is used up either way
+    pub fn verify_password(&mut self, host: &PeerId, peer: &PeerId, proof: &PasswordProof) -> bool {
+        let (Some(password), Some(nonce)) = (&self.password, self.challenges.remove(peer)) else {
+            return false;
+        };
+        auth::verify_password_proof(password, &nonce, host, peer, proof)
+    }
+
+    pub fn is_invite_only(&self) -> bool {
+        self.invite_only
+    }
+
+    /// Turning invite-only on keeps everyone already in the lobby invited
+    pub fn set_invite_only(&mut self, enabled: bool) {
+        self.invite_only = enabled;
+        if enabled {
+            self.invited.extend(self.players.keys().copied());
+        }
+    }
+
+    pub fn invite(&mut self, peer: PeerId) {
+        self.invited.insert(peer);
+    }
+
+    pub fn uninvite(&mut self, peer: &PeerId) {
+        self.invited.remove(peer);
+    }
+
+    pub fn invited(&self) -> impl Iterator<Item = &PeerId> {
+        self.invited.iter()
+    }
+
+    /// Whether a peer may join, always true unless the room is invite-only
+    pub fn is_invited(&self, peer: &PeerId) -> bool {
+        !self.invite_only || self.invited.contains(peer)
+    }
+
+    /// Get all players in the lobby, ordered by player id. A player's
+    /// position in this list is their seat once the game starts.
     pub fn get_all_players(&self) -> Vec<LobbyPlayer> {
         let mut players: Vec<LobbyPlayer> = self.players.values().cloned().collect();
-        // Add host as player 0
-        players.insert(
-            0,
-            LobbyPlayer {
-                id: 0,
-                name: self.host_player_name.clone(),
-            },
-        );
+        players.sort_by_key(|p| p.id);
+        // Add host as player 0, the host is ready by pressing start
+        if self.host_plays {
+            players.insert(
+                0,
+                LobbyPlayer {
+                    id: 0,
+                    name: self.host_player_name.clone(),
+                    ready: true,
+                    avatar: self.host_avatar,
+                    peer_id: String::new(),
+                },
+            );
+        }
         players
     }
 
+    /// Seats including the host, limited by what the engine can deal for
+    pub fn max_seats(&self) -> usize {
+        constants::max_players()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.seat_count() >= self.max_seats()
+    }
+
+    pub fn free_seats(&self) -> usize {
+        self.max_seats().saturating_sub(self.seat_count())
+    }
+
+    /// Returns true if the ready flag changed
+    pub fn set_ready(&mut self, peer: &PeerId, ready: bool) -> bool {
+        match self.players.get_mut(peer) {
+            Some(player) if player.ready != ready => {
+                player.ready = ready;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Everyone is ready, and with fair dealing on everyone's entropy has arrived
+    pub fn all_ready(&self) -> bool {
+        self.players
+            .iter()
+            .all(|(peer, p)| p.ready && (!self.fair_dealing || self.player_entropy.contains_key(peer)))
+    }
+
+    pub fn get_player_id(&self, peer: &PeerId) -> Option<usize> {
+        self.players.get(peer).map(|p| p.id)
+    }
+
+    pub fn get_peer_id(&self, player_id: usize) -> Option<PeerId> {
+        self.players
+            .iter()
+            .find(|(_, p)| p.id == player_id)
+            .map(|(peer, _)| *peer)
+    }
+
+    pub fn ban(&mut self, peer: PeerId) {
+        self.invited.remove(&peer);
+        self.banned.insert(peer);
+    }
+
+    pub fn is_banned(&self, peer: &PeerId) -> bool {
+        self.banned.contains(peer)
+    }
+
+    /// Close a kicked peer's connection after `KICK_GRACE`, so the
+    /// message telling them why still gets out
+    pub fn schedule_disconnect(&mut self, peer: PeerId) {
+        self.kicked.insert(peer, Instant::now() + KICK_GRACE);
+    }
+
+    /// A kicked peer that came back before the grace period ran out keeps its connection
+    pub fn cancel_disconnect(&mut self, peer: &PeerId) {
+        self.kicked.remove(peer);
+    }
+
+    pub fn next_disconnect(&self) -> Option<Instant> {
+        self.kicked.values().min().copied()
+    }
+
+    /// Kicked peers whose grace period is over
+    pub fn take_due_disconnects(&mut self) -> Vec<PeerId> {
+        let now = Instant::now();
+        let due: Vec<PeerId> = self.kicked.iter().filter(|(_, at)| **at <= now).map(|(peer, _)| *peer).collect();
+        for peer in &due {
+            self.kicked.remove(peer);
+        }
+        due
+    }
+
+    /// Enough players and all of them ready
+    pub fn can_start(&self) -> bool {
+        self.seat_count() >= constants::min_players() && self.all_ready()
+    }
+
+    /// Start the countdown, everyone has to be ready
+    pub fn start_countdown(&mut self) -> Result<(), String> {
+        if self.seat_count() < constants::min_players() {
+            return Err(format!("Need at least {} players", constants::min_players()));
+        }
+        if !self.all_ready() {
+            return Err("Not everyone is ready".to_string());
+        }
+        self.countdown_started = Some(Instant::now());
+        self.countdown_sent = None;
+        Ok(())
+    }
+
+    /// Returns true if a countdown was running
+    pub fn cancel_countdown(&mut self) -> bool {
+        self.countdown_sent = None;
+        self.countdown_started.take().is_some()
+    }
+
+    /// Whole seconds left before the game starts
+    pub fn countdown_remaining(&self) -> Option<u64> {
+        self.countdown_started.map(|started| {
+            let elapsed = started.elapsed().as_millis() as u64;
+            (START_COUNTDOWN_SECS * 1000).saturating_sub(elapsed).div_ceil(1000)
+        })
+    }
+
+    /// Returns the remaining seconds if they changed since the last call
+    pub fn take_countdown_change(&mut self) -> Option<u64> {
+        let remaining = self.countdown_remaining()?;
+        if self.countdown_sent == Some(remaining) {
+            return None;
+        }
+        self.countdown_sent = Some(remaining);
+        Some(remaining)
+    }
+
     /// Get the number of players (excluding host)
     pub fn player_count(&self) -> usize {
         self.players.len()
     }
 
-    /// Get peer to player mapping for transition to game
+    /// Get peer to game seat mapping for transition to game. Seats follow
+    /// `get_all_players`, lobby ids can have gaps after players leave.
     pub fn get_peer_mappings(&self) -> (HashMap<PeerId, usize>, HashMap<usize, PeerId>) {
         let mut peer_to_player_id = HashMap::new();
         let mut player_id_to_peer = HashMap::new();
 
-        for (peer, lobby_player) in &self.players {
-            peer_to_player_id.insert(*peer, lobby_player.id);
-            player_id_to_peer.insert(lobby_player.id, *peer);
+        for (seat, lobby_player) in self.get_all_players().iter().enumerate() {
+            if let Some(peer) = self.get_peer_id(lobby_player.id) {
+                peer_to_player_id.insert(peer, seat);
+                player_id_to_peer.insert(seat, peer);
+            }
         }
 
         (peer_to_player_id, player_id_to_peer)
@@ -1,14 +1,38 @@
 pub mod behaviour;
 pub mod host;
 pub mod client;
+pub mod peer;
 pub mod lobby;
 pub mod game;
+pub mod hostless;
 pub mod transition;
 pub mod events;
+pub mod chat;
+pub mod gossip;
+pub mod latency;
+pub mod identity;
+pub mod recent_hosts;
+pub mod correspondence;
+pub mod gateway;
+pub mod version;
+pub mod wire;
+pub mod room_code;
+pub mod swarm;
+pub mod server;
 
 pub use host::Host;
 pub use client::Client;
+pub use peer::Peer;
 pub use behaviour::{BobaGoBehaviour, ClientRequest, HostResponse};
 pub use lobby::{LobbyHostState, LobbyClientState, ClientEvent, HostEvent, ClientMessage, HostMessage, LobbyPlayer};
-pub use game::{GameHostState, GameClientState, GameClientEvent, GameHostEvent, GameClientMessage, GameHostMessage, GameEndReason};
-pub use transition::{lobby_to_game_host, lobby_to_game_client};
+pub use game::{GameHostState, GameClientState, GameClientEvent, GameHostEvent, GameClientMessage, GameHostMessage, GameEndReason, GameRequestKind};
+pub use hostless::{HostlessGameState, HostlessSeat, PeerEvent};
+pub use chat::{ChatLog, ChatMessage};
+pub use latency::{PlayerLatencies, PlayerLatency};
+pub use identity::{Avatar, Identity, Profile, ProfileColor};
+pub use correspondence::{CorrespondenceGame, TurnFile};
+pub use gateway::Gateway;
+pub use recent_hosts::{JoinOutcome, Reachability, ReachabilityProbe, RecentHost, RecentHosts};
+pub use version::{VersionInfo, GAME_VERSION, PROTOCOL_VERSION};
+pub use room_code::{room_code, parse_room_code};
+pub use transition::{lobby_to_game_host, lobby_to_game_client, lobby_to_hostless_host, lobby_to_hostless_client, game_to_lobby_host};
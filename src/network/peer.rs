@@ -0,0 +1,52 @@
+use libp2p::{
+    gossipsub::IdentTopic,
+    swarm::Swarm,
+    ping,
+};
+
+use crate::network::behaviour::BobaGoBehaviour;
+use crate::network::chat::{self, ChatGossip, ChatLog};
+use crate::network::latency::LatencyTracker;
+
+/// Generic peer for hostless games (works for any state type).
+/// Unlike Host and Client, every peer has the same authority.
+pub struct Peer<S> {
+    pub(crate) swarm: Swarm<BobaGoBehaviour>,
+    pub(crate) state: S,
+    pub(crate) topic: IdentTopic,
+    pub(crate) chat: ChatLog,
+    // round trips to the other seats, measured directly
+    pub(crate) latency: LatencyTracker,
+}
+
+// Generic impl - works for any state type
+impl<S> Peer<S> {
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    pub fn state_mut(&mut self) -> &mut S {
+        &mut self.state
+    }
+
+    pub fn chat(&self) -> &ChatLog {
+        &self.chat
+    }
+
+    // record a ping, a seat that stopped answering gets disconnected
+    pub(crate) fn handle_ping(&mut self, event: ping::Event) {
+        if self.latency.record(&event) {
+            crate::log::peer(format!("{} stopped answering pings, disconnecting", event.peer));
+            self.swarm.disconnect_peer_id(event.peer).ok();
+        }
+    }
+
+    /// Send a chat message to everyone in the room
+    pub fn send_chat(&mut self, text: &str) -> Result<(), String> {
+        let message = self.chat.compose(text)?;
+        if let Err(e) = chat::publish_chat_gossip(&mut self.swarm, &self.topic, &ChatGossip::Message(message)) {
+            crate::log::peer(e);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,285 @@
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use libp2p::{futures::StreamExt, identity::Keypair, multiaddr::Protocol, swarm::{dial_opts::DialOpts, ConnectionId, SwarmEvent}, Multiaddr, PeerId, Swarm};
+use serde::{Deserialize, Serialize};
+
+use crate::network::behaviour::{BobaGoBehaviour, BobaGoBehaviourEvent};
+use crate::network::{identity, room_code, swarm};
+
+/// file holding the list, next to the identity
+const RECENT_HOSTS_FILE: &str = "recent_hosts.json";
+
+/// rooms kept besides the favorites, the oldest are forgotten first
+pub const MAX_RECENT_HOSTS: usize = 10;
+
+/// how long the reachability check waits for an answer
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How the last attempt to join a room went
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JoinOutcome {
+    Joined,
+    Rejected(String),
+    // never got an answer from the host
+    Unreachable,
+}
+
+impl fmt::Display for JoinOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JoinOutcome::Joined => write!(f, "joined"),
+            JoinOutcome::Rejected(reason) => write!(f, "rejected: {reason}"),
+            JoinOutcome::Unreachable => write!(f, "no answer"),
+        }
+    }
+}
+
+/// A room we tried to join, by what was typed to get there
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecentHost {
+    // room code or multiaddr
+    pub address: String,
+    // room name, empty if the host never answered
+    #[serde(default)]
+    pub name: String,
+    // unix seconds of the last attempt
+    pub last_seen: u64,
+    pub outcome: JoinOutcome,
+    #[serde(default)]
+    pub favorite: bool,
+}
+
+/// Rooms the player joined before, saved as JSON in the config directory
+#[derive(Debug, Default)]
+pub struct RecentHosts {
+    hosts: Vec<RecentHost>,
+    // where it is saved, None to keep it in memory only
+    dir: Option<PathBuf>,
+}
+
+impl RecentHosts {
+    /// List saved in `dir`, empty if there is none yet or it can't be read
+    pub fn load_from(dir: &Path) -> Self {
+        let hosts = fs::read_to_string(dir.join(RECENT_HOSTS_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Self { hosts, dir: Some(dir.to_path_buf()) }
+    }
+
+    /// List from the config directory, see `identity::config_dir`
+    pub fn load() -> Self {
+        identity::config_dir().map(|dir| Self::load_from(&dir)).unwrap_or_default()
+    }
+
+    /// Favorites first, then the most recent
+    pub fn list(&self) -> Vec<&RecentHost> {
+        let mut hosts: Vec<&RecentHost> = self.hosts.iter().collect();
+        hosts.sort_by(|a, b| b.favorite.cmp(&a.favorite).then(b.last_seen.cmp(&a.last_seen)));
+        hosts
+    }
+
+    pub fn get(&self, address: &str) -> Option<&RecentHost> {
+        self.hosts.iter().find(|host| host.address == address)
+    }
+
+    /// Remember an attempt to join `address`, keeping the old name if the
+    /// host didn't tell us one this time
+    pub fn record(&mut self, address: &str, name: &str, outcome: JoinOutcome) -> Result<(), String> {
+        let address = address.trim();
+        let last_seen = unix_now();
+        match self.hosts.iter_mut().find(|host| host.address == address) {
+            Some(host) => {
+                if !name.is_empty() {
+                    host.name = name.to_string();
+                }
+                host.last_seen = last_seen;
+                host.outcome = outcome;
+            }
+            None => self.hosts.push(RecentHost {
+                address: address.to_string(),
+                name: name.to_string(),
+                last_seen,
+                outcome,
+                favorite: false,
+            }),
+        }
+        self.forget_oldest();
+        self.save()
+    }
+
+    pub fn toggle_favorite(&mut self, address: &str) -> Result<(), String> {
+        if let Some(host) = self.hosts.iter_mut().find(|host| host.address == address) {
+            host.favorite = !host.favorite;
+        }
+        self.forget_oldest();
+        self.save()
+    }
+
+    pub fn remove(&mut self, address: &str) -> Result<(), String> {
+        self.hosts.retain(|host| host.address != address);
+        self.save()
+    }
+
+    // favorites are kept no matter how old
+    fn forget_oldest(&mut self) {
+        self.hosts.sort_by_key(|host| std::cmp::Reverse(host.last_seen));
+        let mut kept = 0;
+        self.hosts.retain(|host| {
+            if host.favorite {
+                return true;
+            }
+            kept += 1;
+            kept <= MAX_RECENT_HOSTS
+        });
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {e}", dir.display()))?;
+        let json = serde_json::to_string_pretty(&self.hosts).map_err(|e| e.to_string())?;
+        let path = dir.join(RECENT_HOSTS_FILE);
+        fs::write(&path, json).map_err(|e| format!("Could not write {}: {e}", path.display()))
+    }
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Whether a recent room answers right now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reachability {
+    Checking,
+    Reachable,
+    Unreachable,
+}
+
+impl fmt::Display for Reachability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reachability::Checking => write!(f, "checking…"),
+            Reachability::Reachable => write!(f, "online"),
+            Reachability::Unreachable => write!(f, "offline"),
+        }
+    }
+}
+
+/// Checks which rooms are up: addresses are dialed and dropped again once
+/// connected, room codes count as up when the host shows up on the LAN.
+/// Nothing is sent to the hosts, they only see a connection come and go.
+pub struct ReachabilityProbe {
+    swarm: Swarm<BobaGoBehaviour>,
+    // dials in flight, and the host we expect at the other end if the address names one
+    dials: HashMap<ConnectionId, (String, Option<PeerId>)>,
+    // room codes we are looking for on the LAN, by code
+    codes: HashMap<String, String>,
+    deadline: tokio::time::Instant,
+    results: VecDeque<(String, Reachability)>,
+}
+
+impl ReachabilityProbe {
+    /// Probe from a throwaway identity, so hosts don't mistake it for us joining
+    pub async fn new(targets: impl IntoIterator<Item = String>) -> Result<Self, Box<dyn Error>> {
+        let swarm = swarm::build_swarm(Keypair::generate_ed25519()).await?;
+        Ok(Self::with_swarm(swarm, targets))
+    }
+
+    /// Probe on an already built swarm, e.g. one from `swarm::build_memory_swarm`
+    pub fn with_swarm(swarm: Swarm<BobaGoBehaviour>, targets: impl IntoIterator<Item = String>) -> Self {
+        let mut probe = Self {
+            swarm,
+            dials: HashMap::new(),
+            codes: HashMap::new(),
+            deadline: tokio::time::Instant::now() + PROBE_TIMEOUT,
+            results: VecDeque::new(),
+        };
+        for target in targets {
+            probe.start(target);
+        }
+        probe
+    }
+
+    fn start(&mut self, target: String) {
+        if let Some(code) = room_code::parse_room_code(&target) {
+            if self.swarm.behaviour().mdns.is_enabled() {
+                self.codes.insert(code, target);
+            } else {
+                self.results.push_back((target, Reachability::Unreachable));
+            }
+            return;
+        }
+        let Ok(address) = target.trim().parse::<Multiaddr>() else {
+            self.results.push_back((target, Reachability::Unreachable));
+            return;
+        };
+        let host = match address.iter().last() {
+            Some(Protocol::P2p(peer)) => Some(peer),
+            _ => None,
+        };
+        let opts = DialOpts::from(address);
+        let connection = opts.connection_id();
+        match self.swarm.dial(opts) {
+            Ok(()) => {
+                self.dials.insert(connection, (target, host));
+            }
+            Err(_) => self.results.push_back((target, Reachability::Unreachable)),
+        }
+    }
+
+    /// Next room we know the answer for, None once every room is settled
+    pub async fn next_result(&mut self) -> Option<(String, Reachability)> {
+        loop {
+            if let Some(result) = self.results.pop_front() {
+                return Some(result);
+            }
+            if self.dials.is_empty() && self.codes.is_empty() {
+                return None;
+            }
+            tokio::select! {
+                event = self.swarm.select_next_some() => self.handle_event(event),
+                _ = tokio::time::sleep_until(self.deadline) => self.give_up(),
+            }
+        }
+    }
+
+    fn handle_event(&mut self, event: SwarmEvent<BobaGoBehaviourEvent>) {
+        match event {
+            SwarmEvent::ConnectionEstablished { peer_id, connection_id, .. } => {
+                // relayed dials connect to the relay first, that says nothing about the host
+                let expected = self.dials.get(&connection_id).is_some_and(|(_, host)| host.is_none_or(|host| host == peer_id));
+                if expected && let Some((target, _)) = self.dials.remove(&connection_id) {
+                    self.results.push_back((target, Reachability::Reachable));
+                }
+                self.swarm.close_connection(connection_id);
+            }
+            SwarmEvent::OutgoingConnectionError { connection_id, .. } => {
+                if let Some((target, _)) = self.dials.remove(&connection_id) {
+                    self.results.push_back((target, Reachability::Unreachable));
+                }
+            }
+            SwarmEvent::Behaviour(BobaGoBehaviourEvent::Mdns(libp2p::mdns::Event::Discovered(peers))) => {
+                for (peer, _) in peers {
+                    if let Some(target) = self.codes.remove(&room_code::room_code(&peer)) {
+                        self.results.push_back((target, Reachability::Reachable));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // whatever hasn't answered by now counts as down
+    fn give_up(&mut self) {
+        let dials = self.dials.drain().map(|(_, (target, _))| target);
+        let codes = self.codes.drain().map(|(_, target)| target);
+        let pending: Vec<String> = dials.chain(codes).collect();
+        self.results.extend(pending.into_iter().map(|target| (target, Reachability::Unreachable)));
+    }
+}
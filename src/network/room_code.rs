@@ -0,0 +1,43 @@
+use libp2p::PeerId;
+use sha2::{Digest, Sha256};
+
+/// Prefix every room code starts with
+pub const ROOM_CODE_PREFIX: &str = "BOBA-";
+const ROOM_CODE_LENGTH: usize = 4;
+
+// Crockford base32, no I, L, O or U so codes are easy to read out loud
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Short code for a host, derived from its peer ID so anyone who
+/// discovers the host on the LAN can check it against the code. The code
+/// carries no address and is only resolved through LAN discovery, players
+/// anywhere else join with one of the host's addresses instead.
+pub fn room_code(peer: &PeerId) -> String {
+    let digest = Sha256::digest(peer.to_bytes());
+    let bits = u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]);
+    let code: String = (0..ROOM_CODE_LENGTH)
+        .map(|i| ALPHABET[((bits >> (27 - 5 * i)) & 0x1f) as usize] as char)
+        .collect();
+    format!("{ROOM_CODE_PREFIX}{code}")
+}
+
+/// Normalize user input into a room code, None if it isn't one.
+/// The prefix is optional and commonly misread letters are accepted.
+pub fn parse_room_code(input: &str) -> Option<String> {
+    let upper = input.trim().to_ascii_uppercase();
+    let body = upper.strip_prefix(ROOM_CODE_PREFIX).unwrap_or(&upper);
+    if body.chars().count() != ROOM_CODE_LENGTH {
+        return None;
+    }
+
+    let code: Option<String> = body
+        .chars()
+        .map(|c| match c {
+            'O' => Some('0'),
+            'I' | 'L' => Some('1'),
+            c if c.is_ascii() && ALPHABET.contains(&(c as u8)) => Some(c),
+            _ => None,
+        })
+        .collect();
+    code.map(|code| format!("{ROOM_CODE_PREFIX}{code}"))
+}
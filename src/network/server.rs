@@ -0,0 +1,224 @@
+use libp2p::{Multiaddr, PeerId};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::time::Duration;
+use tokio::time::sleep;
+
+use crate::engine::{Game, GameSettings};
+use crate::log;
+use crate::network::game::{GameEndReason, GameHostEvent, GameHostState};
+use crate::network::lobby::{HostEvent, LobbyHostState};
+use crate::network::swarm::TransportKind;
+use crate::network::gateway::{self, FreeSeats, Gateway};
+use crate::network::{game_to_lobby_host, lobby_to_game_host, Host};
+
+/// Name the server uses in chat
+pub const SERVER_NAME: &str = "Server";
+
+/// Everything a dedicated server needs to run its rooms
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// Relay address to register every room with
+    pub relay: Option<String>,
+    pub rooms: Vec<RoomConfig>,
+}
+
+/// One table on the server, reopened after every game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RoomConfig {
+    pub name: String,
+    /// Multiaddrs to listen on, every transport on a random port if empty
+    pub listen: Vec<String>,
+    pub password: Option<String>,
+    pub invite_only: bool,
+    /// Player identities allowed in when `invite_only` is set
+    pub invited: Vec<String>,
+    pub fair_dealing: bool,
+    pub settings: GameSettings,
+    /// Socket address for a WebSocket gateway, e.g. `0.0.0.0:4180`
+    pub gateway: Option<String>,
+}
+
+impl Default for RoomConfig {
+    fn default() -> Self {
+        Self {
+            name: "Boba Table".to_string(),
+            listen: Vec::new(),
+            password: None,
+            invite_only: false,
+            invited: Vec::new(),
+            fair_dealing: false,
+            settings: GameSettings::default(),
+            gateway: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("Invalid server config: {e}"))
+    }
+}
+
+impl RoomConfig {
+    pub fn named(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Self::default()
+        }
+    }
+
+    // fresh lobby with this room's rules, used at startup and after every game
+    fn lobby_state(&self) -> Result<LobbyHostState, String> {
+        let mut lobby = LobbyHostState::dedicated(self.name.clone(), SERVER_NAME.to_string());
+        lobby.set_password(self.password.clone());
+        lobby.set_fair_dealing(self.fair_dealing);
+        lobby.set_settings(self.settings.clone());
+        lobby.set_invite_only(self.invite_only);
+        for identity in &self.invited {
+            let peer: PeerId = identity
+                .parse()
+                .map_err(|_| format!("Invalid invited identity '{identity}'"))?;
+            lobby.invite(peer);
+        }
+        Ok(lobby)
+    }
+}
+
+/// Run one room forever: wait for players to ready up, play, reopen the lobby
+pub async fn run_room(config: RoomConfig, relay: Option<Multiaddr>) -> Result<(), Box<dyn Error>> {
+    let room = config.name.clone();
+    let mut lobby = Host::<LobbyHostState>::new_dedicated(room.clone(), SERVER_NAME.to_string()).await?;
+    *lobby.state_mut() = config.lobby_state()?;
+
+    if config.listen.is_empty() {
+        lobby.listen_on_transports(TransportKind::ALL)?;
+    }
+    for address in &config.listen {
+        lobby.listen(address)?;
+    }
+    if let Some(relay) = relay {
+        lobby.listen_via_relay(relay)?;
+    }
+    log::server(&room, format!("Room code {} (same network only)", lobby.room_code()));
+    // started once we know an address to dial the room on
+    let mut gateway = match &config.gateway {
+        Some(address) => Some(Gateway::bind(address).await?),
+        None => None,
+    };
+    let free_seats = gateway.as_ref().map(Gateway::free_seats);
+
+    loop {
+        run_lobby(&room, &mut lobby, &mut gateway, free_seats.as_ref()).await;
+        // web players can't join a game in progress
+        if let Some(free_seats) = &free_seats {
+            free_seats.set(0);
+        }
+
+        let players = lobby.get_lobby_players();
+        let player_names: Vec<String> = players.iter().map(|p| p.name.clone()).collect();
+        let mut game_config = lobby.settings().to_config(player_names.clone());
+        if let Some(fair_dealing) = lobby.state().fair_dealing() {
+            game_config.seed = Some(fair_dealing.seed());
+        }
+        log::server(&room, format!("Starting game for {} ({})", player_names.join(", "), lobby.settings().summary()));
+
+        let game = match Game::new(game_config) {
+            Ok(game) => game,
+            Err(e) => {
+                log::server(&room, format!("Could not start game: {e:?}"));
+                *lobby.state_mut() = config.lobby_state()?;
+                continue;
+            }
+        };
+
+        let mut game_host = lobby_to_game_host(lobby, game);
+        game_host.broadcast_game_update();
+        run_game(&room, &mut game_host).await;
+
+        lobby = game_to_lobby_host(game_host, config.lobby_state()?);
+        log::server(&room, "Lobby reopened");
+    }
+}
+
+// returns once the start countdown runs out
+async fn run_lobby(room: &str, lobby: &mut Host<LobbyHostState>, gateway: &mut Option<Gateway>, free_seats: Option<&FreeSeats>) {
+    loop {
+        if lobby.poll_countdown() {
+            return;
+        }
+        if let Some(free_seats) = free_seats {
+            free_seats.set(lobby.state().free_seats());
+        }
+        // nobody presses start on a server, go as soon as everyone is ready
+        if lobby.countdown_remaining().is_none()
+            && lobby.state().can_start()
+            && lobby.start_countdown().is_ok()
+        {
+            log::server(room, "Everyone is ready, starting soon");
+        }
+
+        tokio::select! {
+            Some(event) = lobby.next_event() => match event {
+                HostEvent::Listening { address } => {
+                    log::server(room, format!("Listening on {address}"));
+                    if gateway::can_dial(&address)
+                        && let Some(gateway) = gateway.take()
+                    {
+                        if let Ok(local) = gateway.local_addr() {
+                            log::server(room, format!("Gateway on ws://{local}"));
+                        }
+                        gateway.spawn(address);
+                    }
+                }
+                HostEvent::PlayerJoined { player_name, peer_id, .. } => {
+                    log::server(room, format!("{player_name} joined ({peer_id})"));
+                }
+                HostEvent::PlayerLeft { peer_id } => {
+                    log::server(room, format!("{peer_id} left"));
+                }
+                HostEvent::ReadyChanged { player_id, ready } => {
+                    log::server(room, format!("Player {player_id} is {}", if ready { "ready" } else { "not ready" }));
+                }
+                HostEvent::ChatReceived { message } => {
+                    log::server(room, format!("<{}> {}", message.player_name, message.text));
+                }
+            },
+            _ = sleep(Duration::from_millis(200)) => {}
+        }
+    }
+}
+
+// returns when the game is over, either completed or abandoned
+async fn run_game(room: &str, game_host: &mut Host<GameHostState>) {
+    while let Some(event) = game_host.next_event().await {
+        match event {
+            GameHostEvent::PlayerSubmitted { .. } | GameHostEvent::PlayerDisconnected { .. } => {}
+            GameHostEvent::AllPlayersSubmitted => match game_host.process_turn() {
+                Ok(Some(GameHostEvent::GameEnded { final_scores, .. })) => {
+                    let scores: Vec<String> = final_scores
+                        .iter()
+                        .map(|(_, score, name, _)| format!("{name} {score}"))
+                        .collect();
+                    log::server(room, format!("Game over: {}", scores.join(", ")));
+                    return;
+                }
+                Ok(_) => {}
+                Err(e) => log::server(room, format!("Turn failed: {e}")),
+            },
+            GameHostEvent::GameEnded { reason, .. } => {
+                let reason = match reason {
+                    GameEndReason::Completed => "completed".to_string(),
+                    GameEndReason::PlayerDisconnected { player_id } => format!("player {player_id} disconnected"),
+                };
+                log::server(room, format!("Game ended: {reason}"));
+                return;
+            }
+            GameHostEvent::ChatReceived { message } => {
+                log::server(room, format!("<{}> {}", message.player_name, message.text));
+            }
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use libp2p::core::{transport::MemoryTransport, upgrade};
+use libp2p::{identity, multiaddr::Protocol, noise, relay, tcp, yamux, Multiaddr, Swarm, Transport};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::network::behaviour::BobaGoBehaviour;
+
+/// Environment variable with a relay multiaddr (including `/p2p/<relay id>`)
+/// hosts register with so players outside the LAN can reach them
+pub const RELAY_ENV: &str = "BOBA_RELAY";
+
+/// Environment variable with a comma separated list of transports to host on,
+/// e.g. `tcp,ws`. Defaults to all of them.
+pub const TRANSPORTS_ENV: &str = "BOBA_TRANSPORTS";
+
+/// Transports a host can listen on, clients can dial any of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportKind {
+    Tcp,
+    Quic,
+    WebSocket,
+}
+
+impl TransportKind {
+    pub const ALL: &'static [TransportKind] = &[TransportKind::Tcp, TransportKind::Quic, TransportKind::WebSocket];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TransportKind::Tcp => "TCP",
+            TransportKind::Quic => "QUIC",
+            TransportKind::WebSocket => "WebSocket",
+        }
+    }
+
+    /// Address to listen on every interface with a random port
+    pub fn listen_address(&self) -> Multiaddr {
+        let address = match self {
+            TransportKind::Tcp => "/ip4/0.0.0.0/tcp/0",
+            TransportKind::Quic => "/ip4/0.0.0.0/udp/0/quic-v1",
+            TransportKind::WebSocket => "/ip4/0.0.0.0/tcp/0/ws",
+        };
+        address.parse().expect("valid listen address")
+    }
+
+    /// Which transport an address would be dialed with, for relayed
+    /// addresses that is the transport used to reach the relay
+    pub fn of_address(address: &Multiaddr) -> Option<TransportKind> {
+        let protocols: Vec<Protocol> = address.iter().collect();
+        if protocols.iter().any(|p| matches!(p, Protocol::Ws(_) | Protocol::Wss(_))) {
+            Some(TransportKind::WebSocket)
+        } else if protocols.contains(&Protocol::QuicV1) {
+            Some(TransportKind::Quic)
+        } else if protocols.iter().any(|p| matches!(p, Protocol::Tcp(_))) {
+            Some(TransportKind::Tcp)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for TransportKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for TransportKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "tcp" => Ok(TransportKind::Tcp),
+            "quic" => Ok(TransportKind::Quic),
+            "ws" | "websocket" => Ok(TransportKind::WebSocket),
+            other => Err(format!("unknown transport '{other}', expected tcp, quic or ws")),
+        }
+    }
+}
+
+/// Build the swarm shared by hosts and clients. Every transport is available
+/// for dialing: TCP and WebSocket with noise and yamux, QUIC, and connections
+/// relayed through a circuit relay.
+pub async fn build_swarm(local_key: identity::Keypair) -> Result<Swarm<BobaGoBehaviour>, Box<dyn Error>> {
+    let swarm = libp2p::SwarmBuilder::with_existing_identity(local_key)
+        .with_tokio()
+        .with_tcp(tcp::Config::default(), noise::Config::new, yamux::Config::default)?
+        .with_quic()
+        .with_dns()?
+        .with_websocket(noise::Config::new, yamux::Config::default)
+        .await?
+        .with_relay_client(noise::Config::new, yamux::Config::default)?
+        .with_behaviour(|key, relay_client: relay::client::Behaviour| {
+            BobaGoBehaviour::new(key, relay_client, true).map_err(Box::from)
+        })?
+        .build();
+    Ok(swarm)
+}
+
+/// Build a swarm that only dials out: the one transport `transport` needs and
+/// no local discovery, for connections made on someone else's behalf like
+/// the gateway's sessions.
+pub async fn build_dial_swarm(local_key: identity::Keypair, transport: TransportKind) -> Result<Swarm<BobaGoBehaviour>, Box<dyn Error>> {
+    let builder = libp2p::SwarmBuilder::with_existing_identity(local_key).with_tokio();
+    let swarm = match transport {
+        TransportKind::Tcp => builder
+            .with_tcp(tcp::Config::default(), noise::Config::new, yamux::Config::default)?
+            .with_dns()?
+            .with_relay_client(noise::Config::new, yamux::Config::default)?
+            .with_behaviour(dial_behaviour)?
+            .build(),
+        TransportKind::Quic => builder
+            .with_quic()
+            .with_dns()?
+            .with_relay_client(noise::Config::new, yamux::Config::default)?
+            .with_behaviour(dial_behaviour)?
+            .build(),
+        TransportKind::WebSocket => builder
+            .with_websocket(noise::Config::new, yamux::Config::default)
+            .await?
+            .with_relay_client(noise::Config::new, yamux::Config::default)?
+            .with_behaviour(dial_behaviour)?
+            .build(),
+    };
+    Ok(swarm)
+}
+
+fn dial_behaviour(
+    key: &identity::Keypair,
+    relay_client: relay::client::Behaviour,
+) -> Result<BobaGoBehaviour, Box<dyn Error + Send + Sync>> {
+    BobaGoBehaviour::new(key, relay_client, false).map_err(Box::from)
+}
+
+/// Build a swarm that only speaks the in-process memory transport, listen on
+/// `/memory/0` and dial the reported address. Local discovery is off so
+/// tests running side by side don't find each other.
+pub fn build_memory_swarm(local_key: identity::Keypair) -> Result<Swarm<BobaGoBehaviour>, Box<dyn Error>> {
+    let swarm = libp2p::SwarmBuilder::with_existing_identity(local_key)
+        .with_tokio()
+        .with_other_transport(|key| {
+            Ok(MemoryTransport::default()
+                .upgrade(upgrade::Version::V1)
+                .authenticate(noise::Config::new(key)?)
+                .multiplex(yamux::Config::default()))
+        })?
+        .with_relay_client(noise::Config::new, yamux::Config::default)?
+        .with_behaviour(|key, relay_client: relay::client::Behaviour| {
+            BobaGoBehaviour::new(key, relay_client, false).map_err(Box::from)
+        })?
+        .build();
+    Ok(swarm)
+}
+
+/// Relay address from `BOBA_RELAY`, if set and valid
+pub fn relay_from_env() -> Option<Multiaddr> {
+    std::env::var(RELAY_ENV).ok()?.trim().parse().ok()
+}
+
+/// Transports from `BOBA_TRANSPORTS`, all of them if unset
+pub fn transports_from_env() -> Result<Vec<TransportKind>, String> {
+    match std::env::var(TRANSPORTS_ENV) {
+        Ok(value) => value.split(',').filter(|s| !s.trim().is_empty()).map(str::parse).collect(),
+        Err(_) => Ok(TransportKind::ALL.to_vec()),
+    }
+}
+
+pub fn is_relayed(address: &Multiaddr) -> bool {
+    address.iter().any(|p| p == Protocol::P2pCircuit)
+}
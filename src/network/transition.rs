@@ -1,43 +1,196 @@
 use std::collections::HashMap;
 
-use crate::engine::Game;
-use super::{Host, Client};
+use libp2p::PeerId;
+
+use crate::engine::{Game, GameConfig, GameError};
+use crate::engine::fairness::FairnessObservations;
+use super::{Host, Client, Peer};
+use super::chat::ChatLog;
+use super::hostless::{HostlessGameState, HostlessSeat};
+use super::lobby::{HostMessage, LobbyPlayer};
 use super::lobby::{LobbyHostState, LobbyClientState};
 use super::game::{GameHostState, GameClientState};
 
+// players chat under their seat once the game starts, `players` in seat order
+fn seat_chat(chat: &mut ChatLog, players: &[LobbyPlayer], local_seat: Option<usize>) {
+    let seats = players.iter().enumerate().map(|(seat, p)| (p.id, seat)).collect();
+    chat.renumber(local_seat, &seats);
+    chat.set_senders(
+        players
+            .iter()
+            .enumerate()
+            .filter_map(|(seat, p)| Some((p.peer()?, (seat, p.name.clone()))))
+            .collect(),
+    );
+}
+
 // transition lobby host to game host
 pub fn lobby_to_game_host(
-    lobby_host: Host<LobbyHostState>,
+    mut lobby_host: Host<LobbyHostState>,
     game: Game,
 ) -> Host<GameHostState> {
     // extract peer mappings from lobby
     let (peer_to_player_id, player_id_to_peer) = lobby_host.state.get_peer_mappings();
+    let fair_dealing = lobby_host.state.fair_dealing();
+    // the host sits in seat 0 unless it's a dedicated server
+    let local_player_id = lobby_host.state.host_plays().then_some(0);
+    seat_chat(&mut lobby_host.chat, &lobby_host.state.get_all_players(), local_player_id);
+
+    let state = GameHostState::new(game, local_player_id, peer_to_player_id, player_id_to_peer, fair_dealing);
 
-    Host::<GameHostState>::new(
-        lobby_host.swarm,
-        lobby_host.topic,
-        game,
-        peer_to_player_id,
-        player_id_to_peer,
-    )
+    let gossip_seq = lobby_host.gossip_seq;
+    let latency = lobby_host.latency;
+    let mut game_host = Host::<GameHostState>::new(lobby_host.swarm, lobby_host.topic, lobby_host.chat, state);
+    game_host.gossip_seq = gossip_seq;
+    game_host.latency = latency;
+    game_host
+}
+
+// transition a finished game back to a lobby on the same swarm, so the
+// room keeps its address. Players rejoin with a new join request.
+pub fn game_to_lobby_host(
+    mut game_host: Host<GameHostState>,
+    lobby: LobbyHostState,
+) -> Host<LobbyHostState> {
+    // lobby ids are handed out afresh, so mutes don't carry over
+    game_host.chat.renumber(Some(0), &HashMap::new());
+    game_host.chat.set_senders(HashMap::new());
+    Host {
+        swarm: game_host.swarm,
+        state: lobby,
+        topic: game_host.topic,
+        chat: game_host.chat,
+        gossip_seq: game_host.gossip_seq,
+        latency: game_host.latency,
+    }
 }
 
 // transition lobby client to game client
 pub fn lobby_to_game_client(
-    lobby_client: Client<LobbyClientState>,
+    mut lobby_client: Client<LobbyClientState>,
     player_id: usize,
     initial_hand: HashMap<crate::engine::models::CardKind, usize>,
     players_public: Vec<crate::engine::models::PlayerPublic>,
     game_status: crate::engine::state::GameStatus,
 ) -> Client<GameClientState> {
     let host_peer_id = lobby_client.get_host_peer_id();
-    Client::<GameClientState>::new(
-        lobby_client.swarm,
-        lobby_client.topic,
+    let players = lobby_client.get_lobby_players();
+    seat_chat(&mut lobby_client.chat, &players, Some(player_id));
+
+    // only keep track of what we were dealt if the host committed to a seed
+    let (entropy, entropy_commitment, seed_commitment) = lobby_client.fairness_commitments();
+    let fairness = (entropy_commitment.is_some() || seed_commitment.is_some()).then(|| {
+        let mut observations = FairnessObservations::new(player_id, entropy, entropy_commitment);
+        observations.seed_commitment = seed_commitment;
+        observations.record_hand(game_status.round, game_status.turn, &initial_hand);
+        observations
+    });
+
+    let mut state = GameClientState::new(
         player_id,
         initial_hand,
         players_public,
         game_status,
         host_peer_id,
-    )
+    );
+    state.fairness = fairness;
+    state.state_version = lobby_client.game_state_version();
+    let host_seq = lobby_client.host_seq;
+    let (latency, room_latency) = (lobby_client.latency, lobby_client.room_latency);
+    let mut game_client = Client::<GameClientState>::new(
+        lobby_client.swarm,
+        lobby_client.topic,
+        lobby_client.chat,
+        state,
+    );
+    game_client.host_seq = host_seq;
+    game_client.latency = latency;
+    game_client.room_latency = room_latency;
+    game_client
+}
+
+// transition lobby host to a hostless game, the host becomes an ordinary peer
+// player order is taken from the seats: host first, then clients in join order
+pub fn lobby_to_hostless_host(
+    mut lobby_host: Host<LobbyHostState>,
+    mut config: GameConfig,
+) -> Result<Peer<HostlessGameState>, GameError> {
+    if !lobby_host.state.host_plays() {
+        return Err(GameError::Other("A dedicated host can't take part in a hostless game".to_string()));
+    }
+
+    let local_peer_id = *lobby_host.swarm.local_peer_id();
+    let players = lobby_host.state.get_all_players();
+    seat_chat(&mut lobby_host.chat, &players, Some(0));
+    let (_, player_id_to_peer) = lobby_host.state.get_peer_mappings();
+
+    let mut clients: Vec<(usize, PeerId)> = player_id_to_peer.into_iter().collect();
+    clients.sort_by_key(|(seat, _)| *seat);
+
+    let mut seat_peers = vec![local_peer_id];
+    let mut seats = vec![HostlessSeat {
+        peer_id: local_peer_id.to_string(),
+        name: lobby_host.state.host_player_name.clone(),
+    }];
+    for (seat, peer) in clients {
+        let name = players
+            .get(seat)
+            .map(|p| p.name.clone())
+            .unwrap_or_else(|| format!("Player {}", seat));
+        seat_peers.push(peer);
+        seats.push(HostlessSeat { peer_id: peer.to_string(), name });
+    }
+
+    let seed = config.seed.unwrap_or_else(rand::random);
+    config.seed = Some(seed);
+    config.player_names = seats.iter().map(|s| s.name.clone()).collect();
+
+    let game = Game::new(config.clone())?;
+
+    let message = HostMessage::StartHostless {
+        seats,
+        seed,
+        round_count: config.round_count,
+        card_distribution: config.card_distribution,
+    };
+    lobby_host
+        .publish_host_message(message)
+        .map_err(|e| GameError::Other(format!("Failed to start hostless game: {e}")))?;
+
+    let state = HostlessGameState::new(game, 0, seat_peers, local_peer_id);
+    let mut peer = Peer::<HostlessGameState>::new(lobby_host.swarm, lobby_host.topic, lobby_host.chat, state);
+    peer.latency = lobby_host.latency;
+    Ok(peer)
+}
+
+// transition lobby client to a hostless game started by the lobby host
+pub fn lobby_to_hostless_client(
+    mut lobby_client: Client<LobbyClientState>,
+    seats: Vec<HostlessSeat>,
+    config: GameConfig,
+) -> Result<Peer<HostlessGameState>, GameError> {
+    let lobby_host = lobby_client
+        .get_host_peer_id()
+        .ok_or_else(|| GameError::Other("Not connected to a lobby".to_string()))?;
+
+    let seat_peers = seats
+        .iter()
+        .map(|seat| seat.peer_id.parse::<PeerId>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| GameError::Other(format!("Invalid seat list: {e}")))?;
+
+    let local_peer_id = *lobby_client.swarm.local_peer_id();
+    let local_player_id = seat_peers
+        .iter()
+        .position(|peer| *peer == local_peer_id)
+        .ok_or_else(|| GameError::Other("You are not seated in this game".to_string()))?;
+
+    let players = lobby_client.get_lobby_players();
+    seat_chat(&mut lobby_client.chat, &players, Some(local_player_id));
+
+    let game = Game::new(config)?;
+    let state = HostlessGameState::new(game, local_player_id, seat_peers, lobby_host);
+    let mut peer = Peer::<HostlessGameState>::new(lobby_client.swarm, lobby_client.topic, lobby_client.chat, state);
+    peer.latency = lobby_client.latency;
+    Ok(peer)
 }
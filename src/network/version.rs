@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+use crate::engine::models::card_set_hash;
+
+/// game version from Cargo.toml
+pub const GAME_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// bump whenever a lobby or game message changes shape
+pub const PROTOCOL_VERSION: u32 = 16;
+
+/// request-response protocol name. Kept stable across releases so that
+/// mismatched peers can still finish the join handshake and get a reason.
+pub const REQUEST_RESPONSE_PROTOCOL: &str = "/boba-go/lobby/1.0.0";
+
+/// identify protocol name, shared with the relay so it can tell boba-go peers apart
+pub const IDENTIFY_PROTOCOL: &str = "/boba-go/id/1.0.0";
+
+/// Version information exchanged during the join handshake
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionInfo {
+    pub game_version: String,
+    pub protocol_version: u32,
+    pub card_set_hash: String,
+}
+
+// clients built before the handshake existed deserialize to this
+impl Default for VersionInfo {
+    fn default() -> Self {
+        Self {
+            game_version: "unknown".to_string(),
+            protocol_version: 0,
+            card_set_hash: String::new(),
+        }
+    }
+}
+
+impl VersionInfo {
+    pub fn local() -> Self {
+        Self {
+            game_version: GAME_VERSION.to_string(),
+            protocol_version: PROTOCOL_VERSION,
+            card_set_hash: card_set_hash(),
+        }
+    }
+
+    /// Check whether a remote peer can play with us, returns a readable reason if not
+    pub fn check_compatible(&self, remote: &VersionInfo) -> Result<(), String> {
+        if self.protocol_version != remote.protocol_version {
+            return Err(format!(
+                "Incompatible protocol: host is v{} (game {}), you are v{} (game {})",
+                self.protocol_version, self.game_version, remote.protocol_version, remote.game_version,
+            ));
+        }
+        if self.card_set_hash != remote.card_set_hash {
+            return Err(format!(
+                "Card set mismatch: host has {}, you have {} (game {} vs {})",
+                self.card_set_hash, remote.card_set_hash, self.game_version, remote.game_version,
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,18 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+/// first byte of every gossip payload, bump it whenever the encoding changes
+/// so older builds drop what they can't read instead of misreading it
+pub const WIRE_FORMAT: u8 = 1;
+
+/// Encode a gossip payload: the format byte, then CBOR
+pub fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+    cbor4ii::serde::to_vec(vec![WIRE_FORMAT], value).map_err(|e| format!("Failed to encode message: {e:?}"))
+}
+
+/// Decode a gossip payload as `T`, None if it's another format or another message
+pub fn decode<T: DeserializeOwned>(data: &[u8]) -> Option<T> {
+    match data.split_first() {
+        Some((&WIRE_FORMAT, body)) => cbor4ii::serde::from_slice(body).ok(),
+        _ => None,
+    }
+}
@@ -0,0 +1,111 @@
+use crossterm::event::KeyCode;
+use crate::network::chat::MAX_CHAT_LENGTH;
+
+/// Chat panel state for lobby and game screens
+pub struct ChatUIState {
+    pub open: bool,
+    pub typing: bool,
+    pub input: String,
+    pub unread: usize,
+    pub notice: Option<String>,
+}
+
+pub enum ChatKeyResult {
+    // key was not meant for chat, let the screen handle it
+    Ignored,
+    Handled,
+    Send(String),
+}
+
+pub enum ChatCommand {
+    Say(String),
+    Mute(String),
+    Unmute(String),
+}
+
+impl ChatUIState {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            typing: false,
+            input: String::new(),
+            unread: 0,
+            notice: None,
+        }
+    }
+
+    /// Handle a key press. While typing every key goes to the chat input,
+    /// otherwise only C (toggle panel) and T (start typing) are consumed.
+    pub fn handle_key(&mut self, key_code: KeyCode) -> ChatKeyResult {
+        if self.typing {
+            match key_code {
+                KeyCode::Char(c) if self.input.chars().count() < MAX_CHAT_LENGTH => {
+                    self.input.push(c);
+                }
+                KeyCode::Backspace => {
+                    self.input.pop();
+                }
+                KeyCode::Esc => {
+                    self.typing = false;
+                    self.input.clear();
+                }
+                KeyCode::Enter => {
+                    self.typing = false;
+                    let text = std::mem::take(&mut self.input);
+                    if !text.trim().is_empty() {
+                        return ChatKeyResult::Send(text);
+                    }
+                }
+                _ => {}
+            }
+            return ChatKeyResult::Handled;
+        }
+
+        match key_code {
+            KeyCode::Char('c') | KeyCode::Char('C') => {
+                self.open = !self.open;
+                if self.open {
+                    self.unread = 0;
+                }
+                ChatKeyResult::Handled
+            }
+            KeyCode::Char('t') | KeyCode::Char('T') => {
+                self.open = true;
+                self.typing = true;
+                self.unread = 0;
+                self.notice = None;
+                ChatKeyResult::Handled
+            }
+            _ => ChatKeyResult::Ignored,
+        }
+    }
+
+    /// Call when a chat message arrives from someone else
+    pub fn on_message_received(&mut self) {
+        if !self.open {
+            self.unread += 1;
+        }
+    }
+
+    pub fn footer_hint(&self) -> String {
+        if self.typing {
+            "Enter: Send  Esc: Cancel".to_string()
+        } else if self.unread > 0 {
+            format!("C: Chat ({} new)  T: Talk", self.unread)
+        } else {
+            "C: Chat  T: Talk".to_string()
+        }
+    }
+}
+
+/// Parse host-only slash commands, anything else is sent as a message
+pub fn parse_chat_command(text: &str) -> ChatCommand {
+    let trimmed = text.trim();
+    if let Some(name) = trimmed.strip_prefix("/mute ") {
+        ChatCommand::Mute(name.trim().to_string())
+    } else if let Some(name) = trimmed.strip_prefix("/unmute ") {
+        ChatCommand::Unmute(name.trim().to_string())
+    } else {
+        ChatCommand::Say(trimmed.to_string())
+    }
+}
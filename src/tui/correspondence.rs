@@ -0,0 +1,187 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use libp2p::PeerId;
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Paragraph},
+    Terminal,
+};
+
+use crate::engine::{GameError, GameSettings};
+use crate::network::correspondence::TURN_FILE_EXTENSION;
+use crate::network::{CorrespondenceGame, TurnFile};
+use crate::engine::GameInterface;
+use super::game_ui::{render_game_ui, GameUIState};
+use super::input::{calculate_max_selections, handle_game_input, InputAction};
+use super::profile::load_identity;
+
+type Term = Terminal<CrosstermBackend<io::Stdout>>;
+
+fn other(e: impl ToString) -> GameError {
+    GameError::Other(e.to_string())
+}
+
+/// Play a game by passing a turn file around: open it, make your pick, save
+/// it and send it on. Picks other players sent back as separate files are
+/// collected from the same folder.
+pub fn run_correspondence_game() -> Result<(), GameError> {
+    enable_raw_mode().map_err(other)?;
+    execute!(io::stdout(), EnterAlternateScreen).map_err(other)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout())).map_err(other)?;
+
+    let result = play(&mut terminal);
+
+    disable_raw_mode().ok();
+    execute!(io::stdout(), LeaveAlternateScreen).ok();
+    result
+}
+
+fn play(terminal: &mut Term) -> Result<(), GameError> {
+    let identity = load_identity();
+    let default_path = format!("game.{TURN_FILE_EXTENSION}");
+    let Some(path) = prompt(terminal, "Turn file to open, or to create for a new game:", &default_path)? else {
+        return Ok(());
+    };
+    let path = PathBuf::from(path.trim());
+
+    let file = if path.exists() {
+        let mut file = TurnFile::load(&path).map_err(other)?;
+        let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        if file.collect_from(dir) > 0 {
+            file.save(&path).map_err(other)?;
+        }
+        file
+    } else {
+        let question = "Players in seating order, comma separated, each as name=identity:";
+        let own_seat = format!("{}={}", identity.profile.name, identity.peer_id());
+        let Some(players) = prompt(terminal, question, &own_seat)? else {
+            return Ok(());
+        };
+        let file = TurnFile::new(GameSettings::default(), parse_seats(&players)?).map_err(other)?;
+        file.save(&path).map_err(other)?;
+        file
+    };
+
+    let Some(seat) = file.seat_for(&identity) else {
+        return Err(other(format!("Your identity {} has no seat in this game", identity.peer_id())));
+    };
+    let mut game = CorrespondenceGame::new(file, seat, &identity).map_err(other)?;
+    run_turns(terminal, &mut game, &path)
+}
+
+/// Pick until the file needs someone else, saving after every pick
+fn run_turns(terminal: &mut Term, game: &mut CorrespondenceGame, path: &Path) -> Result<(), GameError> {
+    let mut ui_state = GameUIState::new();
+    while game.can_pick() {
+        let max_selections = calculate_max_selections(&ui_state, game.get_player_id());
+        terminal.draw(|f| render_game_ui(f, &*game, &ui_state, false, max_selections, None)).map_err(other)?;
+
+        if event::poll(Duration::from_millis(100)).map_err(other)?
+            && let Event::Key(key) = event::read().map_err(other)?
+            && key.kind == KeyEventKind::Press
+        {
+            match handle_game_input(key.code, game, &mut ui_state, max_selections) {
+                InputAction::Quit => return Ok(()),
+                InputAction::SubmitTurn => {
+                    game.file().save(path).map_err(other)?;
+                    ui_state.clear_selections();
+                    ui_state.reset_for_new_turn();
+                }
+                InputAction::Continue => {}
+            }
+        }
+    }
+
+    loop {
+        terminal.draw(|f| {
+            if game.game().is_game_over() {
+                super::render_score_breakdown(f, game.game());
+                return;
+            }
+            let waiting = game.file().waiting_on().join(", ");
+            let text = vec![
+                Line::from(format!("Your pick is saved in {}", path.display())),
+                Line::from(""),
+                Line::from(format!("Waiting on: {waiting}")),
+                Line::from("Send them the file, or put the files they send back in the same folder."),
+                Line::from(""),
+                Line::from("Press Q to exit"),
+            ];
+            let message = Paragraph::new(text)
+                .alignment(Alignment::Center)
+                .block(Block::default().borders(Borders::ALL).title("Turn File"));
+            f.render_widget(message, f.area());
+        }).map_err(other)?;
+
+        if event::poll(Duration::from_millis(200)).map_err(other)?
+            && let Event::Key(key) = event::read().map_err(other)?
+            && key.kind == KeyEventKind::Press
+            && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+        {
+            return Ok(());
+        }
+    }
+}
+
+// `name=identity` entries, the identity being the player's peer id
+fn parse_seats(text: &str) -> Result<Vec<(String, PeerId)>, GameError> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (name, peer) = entry.split_once('=').ok_or_else(|| other(format!("'{entry}' has no identity")))?;
+            let peer = peer.trim().parse().map_err(|_| other(format!("'{}' is not an identity", peer.trim())))?;
+            Ok((name.trim().to_string(), peer))
+        })
+        .collect()
+}
+
+/// One line of text input, None on Esc
+fn prompt(terminal: &mut Term, question: &str, initial: &str) -> Result<Option<String>, GameError> {
+    let mut input = initial.to_string();
+    loop {
+        terminal.draw(|f| {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(3), Constraint::Length(3), Constraint::Min(1)])
+                .split(f.area());
+            let prompt = Paragraph::new(question)
+                .alignment(Alignment::Center)
+                .block(Block::default().borders(Borders::ALL));
+            f.render_widget(prompt, chunks[0]);
+            let field = Paragraph::new(format!("{}_", input))
+                .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+                .alignment(Alignment::Center)
+                .block(Block::default().borders(Borders::ALL));
+            f.render_widget(field, chunks[1]);
+            let footer = Paragraph::new("Press Enter to continue, Backspace to delete, Esc to cancel")
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(Color::Gray));
+            f.render_widget(footer, chunks[2]);
+        }).map_err(other)?;
+
+        if event::poll(Duration::from_millis(100)).map_err(other)?
+            && let Event::Key(key) = event::read().map_err(other)?
+            && key.kind == KeyEventKind::Press
+        {
+            match key.code {
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter if !input.trim().is_empty() => return Ok(Some(input)),
+                KeyCode::Esc => return Ok(None),
+                _ => {}
+            }
+        }
+    }
+}
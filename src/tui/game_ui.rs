@@ -1,5 +1,8 @@
 use std::collections::HashMap;
-use crate::engine::{CardKind, state::GameStatus, models::PlayerPublic};
+use std::time::{Duration, Instant};
+use crate::engine::{CardKind, GameInterface, PlayerTurnState, state::GameStatus, models::PlayerPublic};
+use crate::network::{ChatLog, PlayerLatencies};
+use super::chat::ChatUIState;
 
 #[derive(Copy, Clone, PartialEq)]
 pub enum GameView {
@@ -17,6 +20,61 @@ pub struct GameUIState {
     pub viewing_player_id: usize,
     pub player_list_index: usize,
     pub view_history: Vec<GameView>,
+    pub chat: ChatUIState,
+    // last rejected action, shown in the footer until the next turn
+    pub error_message: Option<String>,
+    // who has picked this turn, only tracked in network games
+    pub turn_progress: Option<TurnProgress>,
+}
+
+/// When each player picked this turn, to show who everyone is waiting on
+pub struct TurnProgress {
+    round: usize,
+    turn: usize,
+    started: Instant,
+    picked: HashMap<usize, Duration>,
+}
+
+impl TurnProgress {
+    pub fn new(status: &GameStatus) -> Self {
+        let mut progress = Self {
+            round: status.round,
+            turn: status.turn,
+            started: Instant::now(),
+            picked: HashMap::new(),
+        };
+        progress.observe(status);
+        progress
+    }
+
+    /// Note who has picked since the last frame, starting over on a new turn
+    pub fn observe(&mut self, status: &GameStatus) {
+        if (status.round, status.turn) != (self.round, self.turn) {
+            *self = Self::new(status);
+            return;
+        }
+        let elapsed = self.started.elapsed();
+        for (player_id, state) in status.player_turn_states.iter().enumerate() {
+            match state {
+                PlayerTurnState::Selected => {
+                    self.picked.entry(player_id).or_insert(elapsed);
+                }
+                // a resync can take a pick back
+                PlayerTurnState::NotSelected => {
+                    self.picked.remove(&player_id);
+                }
+            }
+        }
+    }
+
+    /// How long the player took to pick, None while they're still picking
+    pub fn picked_after(&self, player_id: usize) -> Option<Duration> {
+        self.picked.get(&player_id).copied()
+    }
+
+    pub fn waiting(&self) -> Duration {
+        self.started.elapsed()
+    }
 }
 
 impl GameUIState {
@@ -30,12 +88,23 @@ impl GameUIState {
             viewing_player_id: 0,
             player_list_index: 0,
             view_history: Vec::new(),
+            chat: ChatUIState::new(),
+            error_message: None,
+            turn_progress: None,
+        }
+    }
+
+    pub fn track_turn_progress(&mut self, status: &GameStatus) {
+        match &mut self.turn_progress {
+            Some(progress) => progress.observe(status),
+            None => self.turn_progress = Some(TurnProgress::new(status)),
         }
     }
 
     pub fn reset_for_new_turn(&mut self) {
         self.hand_selection_index = 0;
         self.my_cards_selection_index = 0;
+        self.error_message = None;
     }
 
     pub fn clear_selections(&mut self) {
@@ -43,35 +112,50 @@ impl GameUIState {
     }
 }
 
-pub trait GameInterface {
-    fn get_hand(&self) -> HashMap<CardKind, usize>;
-    fn get_game_status(&self) -> GameStatus;
-    fn get_players_public(&self) -> Vec<PlayerPublic>;
-    fn submit_turn(&mut self, selected: HashMap<CardKind, usize>, remaining: HashMap<CardKind, usize>) -> Result<(), String>;
-    fn get_player_id(&self) -> usize;
-    fn activate_drink_tray(&mut self) -> Result<(), String>;
-}
-
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout},
     style::{Color, Modifier, Style},
+    text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
     Frame,
 };
 
-pub fn render_game_ui<G: GameInterface>(
+// one entry per player: a tick and how long they took, or how long we've been waiting
+fn turn_progress_line(progress: &TurnProgress, players: &[PlayerPublic]) -> Line<'static> {
+    let mut spans = Vec::new();
+    for player in players {
+        if !spans.is_empty() {
+            spans.push(Span::raw("  "));
+        }
+        match progress.picked_after(player.id) {
+            Some(took) => spans.push(Span::styled(
+                format!("{} ✓ {}s", player.name, took.as_secs()),
+                Style::default().fg(Color::Green),
+            )),
+            None => spans.push(Span::styled(
+                format!("{} picking… {}s", player.name, progress.waiting().as_secs()),
+                Style::default().fg(Color::Yellow),
+            )),
+        }
+    }
+    Line::from(spans)
+}
+
+pub fn render_game_ui<G: GameInterface + PlayerLatencies>(
     f: &mut Frame,
     game: &G,
     ui_state: &GameUIState,
     submitted: bool,
     max_selections: usize,
+    chat: Option<&ChatLog>,
 ) {
-    use crate::tui::views::{render_hand, render_my_cards, render_player_cards};
+    use crate::tui::views::{render_hand, render_my_cards, render_player_cards, render_chat_panel};
     let area = f.area();
+    let status_height = if ui_state.turn_progress.is_some() { 4 } else { 3 };
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3), // Status bar
+            Constraint::Length(status_height), // Status bar, with turn progress in network games
             Constraint::Min(10),   // Main content
             Constraint::Length(3), // Footer/controls
         ])
@@ -87,23 +171,47 @@ pub fn render_game_ui<G: GameInterface>(
         game_status.pass_direction,
         if submitted { " [SUBMITTED]" } else { "" }
     );
-    let status_para = Paragraph::new(status_text)
-        .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+    let mut status_lines = vec![Line::styled(
+        status_text,
+        Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+    )];
+    if let Some(progress) = &ui_state.turn_progress
+        && !game_status.is_game_over
+    {
+        status_lines.push(turn_progress_line(progress, &game.get_players_public()));
+    }
+    let status_para = Paragraph::new(status_lines)
         .block(Block::default().borders(Borders::ALL).title("Game Status"));
     f.render_widget(status_para, chunks[0]);
 
     let player_id = game.get_player_id();
     let player_selected = ui_state.player_selections.get(&player_id).map(|(sel, _)| sel);
 
+    // Main content, with the chat panel on the right when open
+    let main_area = match chat {
+        Some(chat_log) if ui_state.chat.open => {
+            let main_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Percentage(65),
+                    Constraint::Percentage(35),
+                ])
+                .split(chunks[1]);
+            render_chat_panel(f, main_chunks[1], chat_log, &ui_state.chat);
+            main_chunks[0]
+        }
+        _ => chunks[1],
+    };
+
     match ui_state.current_view {
         GameView::Hand => {
-            render_hand(f, game, chunks[1], player_selected, ui_state.hand_selection_index);
+            render_hand(f, game, main_area, player_selected, ui_state.hand_selection_index);
         }
         GameView::MyCards => {
-            render_my_cards(f, game, chunks[1], ui_state.my_cards_selection_index);
+            render_my_cards(f, game, main_area, ui_state.my_cards_selection_index);
         }
         GameView::PlayerCards => {
-            render_player_cards(f, game, ui_state.viewing_player_id, chunks[1], ui_state.player_list_index);
+            render_player_cards(f, game, ui_state.viewing_player_id, main_area, ui_state.player_list_index);
         }
     }
 
@@ -139,7 +247,19 @@ pub fn render_game_ui<G: GameInterface>(
         }
     };
 
+    let selection_text = if chat.is_some() {
+        format!("{} | {}", selection_text, ui_state.chat.footer_hint())
+    } else {
+        selection_text
+    };
+
+    let (selection_text, footer_style) = match &ui_state.error_message {
+        Some(error) => (format!("Rejected: {} | {}", error, selection_text), Style::default().fg(Color::Red)),
+        None => (selection_text, Style::default()),
+    };
+
     let footer = Paragraph::new(selection_text)
+        .style(footer_style)
         .alignment(Alignment::Center)
         .block(Block::default().borders(Borders::ALL));
     f.render_widget(footer, chunks[2]);
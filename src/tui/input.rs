@@ -1,7 +1,7 @@
 use crossterm::event::KeyCode;
 use std::collections::HashMap;
-use crate::engine::CardKind;
-use crate::tui::{GameView, GameUIState, GameInterface};
+use crate::engine::{CardKind, GameInterface};
+use crate::tui::{GameView, GameUIState};
 use crate::engine::models::PlayerPublic;
 
 pub enum InputAction {
@@ -260,8 +260,12 @@ fn handle_enter_key<G: GameInterface>(
                     }
                     remaining.retain(|_, count| *count > 0);
 
-                    if game.submit_turn(selected.clone(), remaining).is_ok() {
-                        return InputAction::SubmitTurn;
+                    match game.submit_turn(selected.clone(), remaining) {
+                        Ok(()) => {
+                            ui_state.error_message = None;
+                            return InputAction::SubmitTurn;
+                        }
+                        Err(e) => ui_state.error_message = Some(e),
                     }
                 }
             }
@@ -291,11 +295,15 @@ fn handle_drink_tray_activation<G: GameInterface>(game: &mut G, ui_state: &mut G
         if !card_list.is_empty() && ui_state.my_cards_selection_index < card_list.len() {
             let (card_kind, _, is_boosted) = card_list[ui_state.my_cards_selection_index];
             if card_kind == CardKind::DrinkTray && !is_boosted {
-                if game.activate_drink_tray().is_ok() {
-                    ui_state.drink_tray_activated.insert(player_id, true);
-                    ui_state.view_history.push(GameView::MyCards);
-                    ui_state.current_view = GameView::Hand;
-                    ui_state.hand_selection_index = 0;
+                match game.activate_drink_tray() {
+                    Ok(()) => {
+                        ui_state.drink_tray_activated.insert(player_id, true);
+                        ui_state.view_history.push(GameView::MyCards);
+                        ui_state.current_view = GameView::Hand;
+                        ui_state.hand_selection_index = 0;
+                        ui_state.error_message = None;
+                    }
+                    Err(e) => ui_state.error_message = Some(e),
                 }
             }
         }
@@ -2,14 +2,22 @@ use std::io;
 use std::collections::HashMap;
 use crate::engine::{Game, GameConfig, GameError, ScoreBreakdown, PlayerTurnState, CardKind};
 use crate::engine::models::PlayerPublic;
+use crate::engine::fairness::FairnessReport;
+use crate::bot::ExternalBot;
 
 mod views;
 mod network_game;
 mod game_ui;
 mod input;
+mod chat;
+mod settings;
+mod profile;
+mod recent_hosts;
+mod correspondence;
 
 pub use network_game::{run_host_game, run_join_game};
-pub use game_ui::{GameView, GameUIState, GameInterface, render_game_ui};
+pub use correspondence::run_correspondence_game;
+pub use game_ui::{GameView, GameUIState, render_game_ui};
 pub use input::{handle_game_input, calculate_max_selections, InputAction};
 
 fn count_player_cards(player_public: &PlayerPublic) -> usize {
@@ -32,6 +40,7 @@ pub enum StartAction {
     NewLocalGame,
     HostNetworkGame,
     JoinNetworkGame,
+    CorrespondenceGame,
     HowToPlay,
     Quit,
 }
@@ -67,6 +76,7 @@ pub fn run_start_page() -> StartAction {
         ("Start new local game", StartAction::NewLocalGame),
         ("Host network game", StartAction::HostNetworkGame),
         ("Join network game", StartAction::JoinNetworkGame),
+        ("Play by turn file", StartAction::CorrespondenceGame),
         ("How to play", StartAction::HowToPlay),
         ("Quit", StartAction::Quit),
     ];
@@ -277,8 +287,31 @@ pub fn run_start_page() -> StartAction {
 }
 
 
-pub fn render_score_breakdown_data(f: &mut Frame, mut score_data: Vec<(String, ScoreBreakdown)>) {
-    let area = f.area();
+pub fn render_score_breakdown_data(
+    f: &mut Frame,
+    mut score_data: Vec<(String, ScoreBreakdown)>,
+    fairness: Option<&FairnessReport>,
+) {
+    let mut area = f.area();
+
+    // fair dealing result goes below the table
+    if let Some(report) = fairness {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(report.mismatches.len().min(5) as u16 + 2)])
+            .split(area);
+        area = chunks[0];
+
+        let (text, color) = if report.is_fair() {
+            ("Deal verified: seed and hands match the host's commitment".to_string(), Color::Green)
+        } else {
+            (report.mismatches.join("\n"), Color::Red)
+        };
+        let fairness_box = Paragraph::new(text)
+            .style(Style::default().fg(color))
+            .block(Block::default().borders(Borders::ALL).title("Fair Dealing"));
+        f.render_widget(fairness_box, chunks[1]);
+    }
 
     score_data.sort_by(|a, b| b.1.total_score.partial_cmp(&a.1.total_score).unwrap_or(std::cmp::Ordering::Equal));
     
@@ -320,19 +353,32 @@ pub fn render_score_breakdown(f: &mut Frame, game: &Game) {
         }
     }
 
-    render_score_breakdown_data(f, score_data);
+    render_score_breakdown_data(f, score_data, None);
 }
 
 pub fn run_local_game() -> Result<(), GameError> {
+    // seats taken by external bots, started before the terminal is taken over so their errors show
+    let mut bots: HashMap<usize, ExternalBot> = HashMap::new();
+    for (seat, command) in crate::bot::bot_seats_from_env().map_err(GameError::Other)? {
+        let bot = ExternalBot::spawn(&command, crate::bot::DEFAULT_MOVE_TIMEOUT).map_err(GameError::Other)?;
+        bots.insert(seat, bot);
+    }
+
     let _ = enable_raw_mode();
     let mut stdout = io::stdout();
     let _ = execute!(stdout, EnterAlternateScreen);
     let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend).expect("create terminal");
 
-    // Initialize game with 2 players
+    // Initialize game with 2 players, more if a bot sits further down
     let mut config = GameConfig::default();
-    config.player_names = vec!["Player 1".to_string(), "Player 2".to_string()];
+    let player_count = bots.keys().map(|seat| seat + 1).max().unwrap_or(0).max(2);
+    config.player_names = (0..player_count)
+        .map(|seat| match bots.get(&seat) {
+            Some(bot) => bot.name().to_string(),
+            None => format!("Player {}", seat + 1),
+        })
+        .collect();
     
     let mut game = match Game::new(config) {
         Ok(g) => g,
@@ -342,6 +388,12 @@ pub fn run_local_game() -> Result<(), GameError> {
             return Err(e);
         }
     };
+    let players = game.get_players_public();
+    for (seat, bot) in bots.iter_mut() {
+        if let Err(e) = bot.start_game(*seat, &players, game.round_count) {
+            crate::log::bot(e);
+        }
+    }
 
     let mut current_player_id = 0;
     let mut ui_state = GameUIState::new();
@@ -371,19 +423,37 @@ pub fn run_local_game() -> Result<(), GameError> {
 
         if status.is_game_over {
             show_scores = true;
+            let scores: Vec<(usize, f32)> = (0..game.num_players())
+                .filter_map(|seat| game.calculate_player_score(seat).ok().map(|(total, _)| (seat, total)))
+                .collect();
+            for bot in bots.values_mut() {
+                if let Err(e) = bot.end_game(&scores) {
+                    crate::log::bot(e);
+                }
+            }
             continue;
         }
 
         // Check if current player has already selected
         let player_state = game.get_player_turn_state(current_player_id).unwrap_or(PlayerTurnState::NotSelected);
         
-        if player_state == PlayerTurnState::NotSelected {
+        if player_state == PlayerTurnState::NotSelected && let Some(bot) = bots.get_mut(&current_player_id) {
+            // bots pick on their own, keep the last screen up meanwhile
+            let mut game_view = crate::engine::GamePlayerView::new(&mut game, current_player_id);
+            match bot.play_turn(&mut game_view) {
+                Ok(submission) => {
+                    ui_state.player_selections.insert(current_player_id, submission);
+                    current_player_id = (current_player_id + 1) % game.num_players();
+                }
+                Err(e) => break Err(GameError::Other(e)),
+            }
+        } else if player_state == PlayerTurnState::NotSelected {
             let has_drink_tray_activated = ui_state.drink_tray_activated.get(&current_player_id).copied().unwrap_or(false);
             let max_selections = if has_drink_tray_activated { 2 } else { 1 };
 
             let _ = terminal.draw(|f| {
                 let mut game_view = crate::engine::GamePlayerView::new(&mut game, current_player_id);
-                render_game_ui(f, &mut game_view, &ui_state, false, max_selections);
+                render_game_ui(f, &mut game_view, &ui_state, false, max_selections, None);
             });
 
             if let Ok(true) = event::poll(std::time::Duration::from_millis(200)) {
@@ -631,24 +701,16 @@ pub fn run_local_game() -> Result<(), GameError> {
                             }
                             KeyCode::Char('u') => {
                                 // Unuse Drink Tray (move from hand back to public_cards)
-                                if ui_state.current_view == GameView::Hand && has_drink_tray_activated {
-                                    let player = &mut game.players[current_player_id];
-                                    if let Some(drink_tray_count) = player.hand.get_mut(&CardKind::DrinkTray) {
-                                        if *drink_tray_count > 0 {
-                                            *drink_tray_count -= 1;
-                                            if *drink_tray_count == 0 {
-                                                player.hand.remove(&CardKind::DrinkTray);
-                                            }
-                                            // Move back to public_cards
-                                            *player.public_cards.entry(CardKind::DrinkTray).or_insert(0) += 1;
-                                            // Deactivate Drink Tray
-                                            ui_state.drink_tray_activated.remove(&current_player_id);
-                                            // Navigate to My Cards view
-                                            ui_state.view_history.push(GameView::Hand);
-                                            ui_state.current_view = GameView::MyCards;
-                                            ui_state.my_cards_selection_index = 0;
-                                        }
-                                    }
+                                if ui_state.current_view == GameView::Hand
+                                    && has_drink_tray_activated
+                                    && game.undo_drink_tray(current_player_id).is_ok()
+                                {
+                                    // Deactivate Drink Tray
+                                    ui_state.drink_tray_activated.remove(&current_player_id);
+                                    // Navigate to My Cards view
+                                    ui_state.view_history.push(GameView::Hand);
+                                    ui_state.current_view = GameView::MyCards;
+                                    ui_state.my_cards_selection_index = 0;
                                 }
                             }
                             _ => {}
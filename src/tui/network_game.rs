@@ -9,14 +9,163 @@ use ratatui::{
     backend::CrosstermBackend,
     layout::{Alignment, Constraint, Direction, Layout},
     style::{Color, Modifier, Style},
+    text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, Paragraph},
     Terminal,
 };
 use tokio::time::sleep;
 
 use crate::engine::GameError;
-use crate::network::{Host, Client, lobby::{LobbyHostState, LobbyClientState}};
-use super::game_ui::GameInterface;
+use crate::network::swarm::TransportKind;
+use crate::network::{Host, Client, Peer, HostlessGameState, JoinOutcome, ReachabilityProbe, RecentHosts, lobby::{LobbyHostState, LobbyClientState}};
+use crate::engine::GameInterface;
+use super::chat::{ChatUIState, ChatKeyResult, ChatCommand, parse_chat_command};
+use super::settings::{SettingsUIState, SettingsKeyResult};
+use super::recent_hosts::{RecentHostsUIState, RecentHostsKeyResult};
+use super::profile::{avatar_span, handle_avatar_key, load_identity, name_input_line, save_profile, AVATAR_KEYS_HINT};
+use super::views::{latency_label, latency_style, render_chat_panel, render_settings_panel, render_recent_hosts_panel};
+
+/// Send a chat line as the host, handling /mute and /unmute commands
+fn send_host_chat<S>(host: &mut Host<S>, chat_ui: &mut ChatUIState, text: &str, players: &[(usize, String)]) {
+    let find_player = |name: &str| {
+        players.iter()
+            .find(|(_, n)| n == name)
+            .map(|(id, _)| *id)
+            .ok_or_else(|| format!("No player named {}", name))
+    };
+    let result = match parse_chat_command(text) {
+        ChatCommand::Say(text) => host.send_chat(&text).map(|_| None),
+        ChatCommand::Mute(name) => find_player(&name)
+            .and_then(|id| host.set_player_muted(id, true))
+            .map(|_| Some(format!("Muted {}", name))),
+        ChatCommand::Unmute(name) => find_player(&name)
+            .and_then(|id| host.set_player_muted(id, false))
+            .map(|_| Some(format!("Unmuted {}", name))),
+    };
+    chat_ui.notice = result.unwrap_or_else(Some);
+}
+
+/// Send a chat line as a client
+fn send_client_chat<S>(client: &mut Client<S>, chat_ui: &mut ChatUIState, text: &str) {
+    match parse_chat_command(text) {
+        ChatCommand::Say(text) => {
+            chat_ui.notice = client.send_chat(&text).err();
+        }
+        ChatCommand::Mute(_) | ChatCommand::Unmute(_) => {
+            chat_ui.notice = Some("Only the host can mute players".to_string());
+        }
+    }
+}
+
+/// Send a chat line during a hostless game, nobody moderates there
+fn send_peer_chat<S>(peer: &mut Peer<S>, chat_ui: &mut ChatUIState, text: &str) {
+    match parse_chat_command(text) {
+        ChatCommand::Say(text) => {
+            chat_ui.notice = peer.send_chat(&text).err();
+        }
+        ChatCommand::Mute(_) | ChatCommand::Unmute(_) => {
+            chat_ui.notice = Some("Muting is not available in hostless games".to_string());
+        }
+    }
+}
+
+/// Play a hostless game, shared by the lobby host and the clients
+async fn run_hostless_game(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    mut peer: Peer<HostlessGameState>,
+) -> Result<(), GameError> {
+    let mut ui_state = super::game_ui::GameUIState::new();
+    let mut submitted = false;
+    let mut aborted: Option<String> = None;
+
+    loop {
+        // Poll for network events (non-blocking)
+        tokio::select! {
+            Some(event) = peer.next_event() => {
+                use crate::network::PeerEvent;
+                match event {
+                    PeerEvent::PlayerCommitted { player_id } => {
+                        crate::log::peer(format!("Player {} committed", player_id));
+                    }
+                    PeerEvent::TurnResolved => {
+                        submitted = false;
+                        ui_state.clear_selections();
+                        ui_state.reset_for_new_turn();
+                    }
+                    PeerEvent::GameOver => break,
+                    PeerEvent::Aborted { reason } => {
+                        aborted = Some(reason);
+                        break;
+                    }
+                    PeerEvent::ChatReceived { .. } => {
+                        ui_state.chat.on_message_received();
+                    }
+                }
+            }
+            _ = sleep(Duration::from_millis(16)) => {
+                // Timeout to keep UI responsive
+            }
+        }
+
+        // Render game UI
+        ui_state.track_turn_progress(&peer.get_game_status());
+        let has_drink_tray = ui_state.drink_tray_activated.get(&peer.get_player_id()).copied().unwrap_or(false);
+        let max_selections = if has_drink_tray { 2 } else { 1 };
+        terminal.draw(|f| {
+            super::game_ui::render_game_ui(f, &peer, &ui_state, submitted, max_selections, Some(peer.chat()));
+        }).map_err(|e| GameError::Other(e.to_string()))?;
+
+        // Handle input (non-blocking poll)
+        if event::poll(Duration::from_millis(10)).map_err(|e| GameError::Other(e.to_string()))?
+            && let Event::Key(key) = event::read().map_err(|e| GameError::Other(e.to_string()))?
+            && key.kind == KeyEventKind::Press
+        {
+            use super::input::{handle_game_input, InputAction};
+            match ui_state.chat.handle_key(key.code) {
+                ChatKeyResult::Send(text) => {
+                    send_peer_chat(&mut peer, &mut ui_state.chat, &text);
+                    continue;
+                }
+                ChatKeyResult::Handled => continue,
+                ChatKeyResult::Ignored if submitted => continue,
+                ChatKeyResult::Ignored => {}
+            }
+            match handle_game_input(key.code, &mut peer, &mut ui_state, max_selections) {
+                InputAction::Quit => return Ok(()),
+                InputAction::SubmitTurn => {
+                    submitted = true;
+                    crate::log::peer("Submitted turn commitment");
+                }
+                InputAction::Continue => {}
+            }
+        }
+    }
+
+    let score_data = peer.get_score_breakdowns();
+    loop {
+        terminal.draw(|f| {
+            match &aborted {
+                Some(reason) => {
+                    let message = Paragraph::new(format!("Game stopped: {}\n\nPress Q to exit", reason))
+                        .style(Style::default().fg(Color::Red))
+                        .alignment(Alignment::Center)
+                        .block(Block::default().borders(Borders::ALL).title("Hostless Game"));
+                    f.render_widget(message, f.area());
+                }
+                None => super::render_score_breakdown_data(f, score_data.clone(), None),
+            }
+        }).ok();
+
+        if event::poll(Duration::from_millis(100)).ok().unwrap_or(false)
+            && let Ok(Event::Key(key)) = event::read()
+            && key.kind == KeyEventKind::Press
+            && (key.code == KeyCode::Char('q') || key.code == KeyCode::Esc)
+        {
+            break;
+        }
+    }
+    Ok(())
+}
 
 /// Host a network game
 pub async fn run_host_game() -> Result<(), GameError> {
@@ -26,8 +175,11 @@ pub async fn run_host_game() -> Result<(), GameError> {
     let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend).map_err(|e| GameError::Other(e.to_string()))?;
 
+    let mut identity = load_identity();
     let mut room_name = String::new();
-    let mut host_name = String::new();
+    let mut host_name = identity.profile.name.clone();
+    let mut avatar = identity.profile.avatar;
+    let mut room_password = String::new();
     let mut input_phase = 0;
 
     // Input phase
@@ -43,21 +195,20 @@ pub async fn run_host_game() -> Result<(), GameError> {
                 ])
                 .split(area);
 
-            let prompt = if input_phase == 0 {
-                "Enter room name:"
-            } else {
-                "Enter your name:"
+            let prompt = match input_phase {
+                0 => "Enter room name:",
+                1 => "Enter your name:",
+                _ => "Room password (leave empty for an open room):",
             };
             let prompt_para = Paragraph::new(prompt)
                 .alignment(Alignment::Center)
                 .block(Block::default().borders(Borders::ALL));
             f.render_widget(prompt_para, chunks[0]);
 
-            let current_input = if input_phase == 0 { &room_name } else { &host_name };
-            let input_display = if current_input.is_empty() {
-                "_".to_string()
-            } else {
-                format!("{}_", current_input)
+            let input_display = match input_phase {
+                0 => Line::from(format!("{}_", room_name)),
+                1 => name_input_line(avatar, &host_name),
+                _ => Line::from(format!("{}_", "*".repeat(room_password.chars().count()))),
             };
             let input = Paragraph::new(input_display)
                 .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
@@ -65,7 +216,11 @@ pub async fn run_host_game() -> Result<(), GameError> {
                 .block(Block::default().borders(Borders::ALL));
             f.render_widget(input, chunks[1]);
 
-            let footer = Paragraph::new("Press Enter to continue, Backspace to delete, Esc to cancel")
+            let mut footer_text = vec![Line::from("Press Enter to continue, Backspace to delete, Esc to cancel")];
+            if input_phase == 1 {
+                footer_text.push(Line::from(AVATAR_KEYS_HINT));
+            }
+            let footer = Paragraph::new(footer_text)
                 .alignment(Alignment::Center)
                 .style(Style::default().fg(Color::Gray));
             f.render_widget(footer, chunks[2]);
@@ -74,27 +229,35 @@ pub async fn run_host_game() -> Result<(), GameError> {
         if event::poll(Duration::from_millis(100)).map_err(|e| GameError::Other(e.to_string()))? {
             if let Event::Key(key) = event::read().map_err(|e| GameError::Other(e.to_string()))? {
                 if key.kind == KeyEventKind::Press {
+                    if input_phase == 1 && handle_avatar_key(&mut avatar, key.code) {
+                        continue;
+                    }
                     match key.code {
                         KeyCode::Char(c) => {
-                            let current = if input_phase == 0 { &mut room_name } else { &mut host_name };
+                            let current = match input_phase {
+                                0 => &mut room_name,
+                                1 => &mut host_name,
+                                _ => &mut room_password,
+                            };
                             if current.len() < 20 {
                                 current.push(c);
                             }
                         }
                         KeyCode::Backspace => {
-                            let current = if input_phase == 0 { &mut room_name } else { &mut host_name };
+                            let current = match input_phase {
+                                0 => &mut room_name,
+                                1 => &mut host_name,
+                                _ => &mut room_password,
+                            };
                             current.pop();
                         }
-                        KeyCode::Enter => {
-                            let current = if input_phase == 0 { &room_name } else { &host_name };
-                            if !current.is_empty() {
-                                if input_phase == 0 {
-                                    input_phase = 1;
-                                } else {
-                                    break;
-                                }
-                            }
-                        }
+                        KeyCode::Enter => match input_phase {
+                            0 if !room_name.is_empty() => input_phase = 1,
+                            1 if !host_name.is_empty() => input_phase = 2,
+                            // the password is optional
+                            2 => break,
+                            _ => {}
+                        },
                         KeyCode::Esc => {
                             disable_raw_mode().ok();
                             execute!(io::stdout(), LeaveAlternateScreen).ok();
@@ -108,24 +271,73 @@ pub async fn run_host_game() -> Result<(), GameError> {
     }
 
     // Create host lobby
-    let mut lobby = Host::<LobbyHostState>::new(room_name.clone(), host_name.clone()).await
+    save_profile(&mut identity, &host_name, avatar);
+    let mut lobby = Host::<LobbyHostState>::new(room_name.clone(), &identity).await
         .map_err(|e| GameError::Other(e.to_string()))?;
-    lobby.listen("/ip4/0.0.0.0/tcp/0")
+    let transports = crate::network::swarm::transports_from_env()
+        .map_err(GameError::Other)?;
+    lobby.listen_on_transports(&transports)
         .map_err(|e| GameError::Other(e.to_string()))?;
+    lobby.set_password(Some(room_password));
+    // players outside the LAN reach us through the relay when one is configured
+    if let Some(relay) = crate::network::swarm::relay_from_env() {
+        lobby.listen_via_relay(relay)
+            .map_err(|e| GameError::Other(e.to_string()))?;
+    }
 
-    let mut listening_addr = None;
+    // web players come in through the gateway, it dials the first address we listen on
+    let mut gateway = match crate::network::Gateway::address_from_env() {
+        Some(address) => Some(crate::network::Gateway::bind(&address).await
+            .map_err(|e| GameError::Other(format!("Could not open the gateway on {address}: {e}")))?),
+        None => None,
+    };
+    let gateway_addr = gateway.as_ref().and_then(|g| g.local_addr().ok()).map(|a| format!("ws://{a}"));
+    let free_seats = gateway.as_ref().map(crate::network::Gateway::free_seats);
+
+    // one address to show per transport
+    let mut listening_addrs: Vec<(TransportKind, String)> = Vec::new();
 
     let mut should_start_game = false;
+    let mut hostless = false;
+    let mut chat_ui = ChatUIState::new();
+    let mut settings_ui = SettingsUIState::new();
+    // identity being typed in to add to the invite list
+    let mut invite_input: Option<String> = None;
+    let mut selected_player = 0;
+    let mut lobby_notice: Option<String> = None;
 
     // Lobby loop
     loop {
+        if lobby.poll_countdown() {
+            should_start_game = true;
+            break;
+        }
+        // players may have left since the last frame
+        selected_player = selected_player.min(lobby.get_lobby_players().len().saturating_sub(1));
+        if let Some(free_seats) = &free_seats {
+            free_seats.set(lobby.state().free_seats());
+        }
+
         // Poll for network events (non-blocking)
         tokio::select! {
             Some(event) = lobby.next_event() => {
                 use crate::network::HostEvent;
                 match event {
                     HostEvent::Listening { address } => {
-                        listening_addr = Some(address.to_string());
+                        if crate::network::gateway::can_dial(&address)
+                            && let Some(gateway) = gateway.take()
+                        {
+                            gateway.spawn(address.clone());
+                        }
+                        if let Some(transport) = TransportKind::of_address(&address) {
+                            let relayed = crate::network::swarm::is_relayed(&address);
+                            match listening_addrs.iter_mut().find(|(t, _)| *t == transport) {
+                                // the relayed address works from anywhere, prefer it over local ones
+                                Some(entry) if relayed => entry.1 = address.to_string(),
+                                Some(_) => {}
+                                None => listening_addrs.push((transport, address.to_string())),
+                            }
+                        }
                     }
                     HostEvent::PlayerJoined { .. } => {
                         // Players list updated automatically
@@ -133,6 +345,10 @@ pub async fn run_host_game() -> Result<(), GameError> {
                     HostEvent::PlayerLeft { .. } => {
                         // Players list updated automatically
                     }
+                    HostEvent::ReadyChanged { .. } => {}
+                    HostEvent::ChatReceived { .. } => {
+                        chat_ui.on_message_received();
+                    }
                 }
             }
             _ = sleep(Duration::from_millis(50)) => {
@@ -143,11 +359,13 @@ pub async fn run_host_game() -> Result<(), GameError> {
         // Render
         terminal.draw(|f| {
             let area = f.area();
+            // room code line plus one line per transport
+            let addr_lines = 1 + listening_addrs.len().max(1) as u16 + gateway_addr.is_some() as u16;
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
                 .constraints([
                     Constraint::Length(3),
-                    Constraint::Length(3),
+                    Constraint::Length(addr_lines + 2),
                     Constraint::Min(1),
                     Constraint::Length(3),
                 ])
@@ -161,11 +379,16 @@ pub async fn run_host_game() -> Result<(), GameError> {
             f.render_widget(title, chunks[0]);
 
             // Address
-            let addr_text = if let Some(addr) = &listening_addr {
-                format!("Address: {}", addr)
-            } else {
-                "Starting server...".to_string()
-            };
+            let mut addr_text = vec![Line::from(format!("Room code: {} (same network only, share an address below otherwise)", lobby.room_code()))];
+            if listening_addrs.is_empty() {
+                addr_text.push(Line::from("Starting server..."));
+            }
+            for (transport, address) in &listening_addrs {
+                addr_text.push(Line::from(format!("{}: {}", transport, address)));
+            }
+            if let Some(gateway_addr) = &gateway_addr {
+                addr_text.push(Line::from(format!("Web players: {}", gateway_addr)));
+            }
             let addr = Paragraph::new(addr_text)
                 .style(Style::default().fg(Color::Green))
                 .alignment(Alignment::Center)
@@ -176,21 +399,71 @@ pub async fn run_host_game() -> Result<(), GameError> {
             let players = lobby.get_lobby_players();
             let player_items: Vec<ListItem> = players
                 .iter()
-                .map(|p| ListItem::new(format!("• {}", p.name)))
+                .enumerate()
+                .map(|(i, p)| {
+                    let marker = if i == selected_player { ">" } else { "•" };
+                    let ready = if p.id == 0 { "host" } else if p.ready { "ready" } else { "not ready" };
+                    let latency = lobby.player_latency(p.id);
+                    let style = if i == selected_player {
+                        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+                    } else {
+                        latency_style(latency)
+                    };
+                    ListItem::new(Line::from(vec![
+                        Span::raw(format!("{} ", marker)),
+                        avatar_span(p.avatar),
+                        Span::raw(format!(" {} ({}){}", p.name, ready, latency_label(latency))),
+                    ]))
+                    .style(style)
+                })
                 .collect();
+            let players_title = format!("Players in Lobby ({}/{})", players.len(), lobby.state().max_seats());
             let player_list = List::new(player_items)
-                .block(Block::default().borders(Borders::ALL).title("Players in Lobby"));
-            f.render_widget(player_list, chunks[2]);
+                .block(Block::default().borders(Borders::ALL).title(players_title));
+            let players_area = if settings_ui.open || chat_ui.open {
+                let split = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .split(chunks[2]);
+                if settings_ui.open {
+                    render_settings_panel(f, split[1], &settings_ui);
+                } else {
+                    render_chat_panel(f, split[1], lobby.chat(), &chat_ui);
+                }
+                split[0]
+            } else {
+                chunks[2]
+            };
+            f.render_widget(player_list, players_area);
 
             // Footer
-            let footer_text = if lobby.get_lobby_players().len() >= 2 {
-                "Press S to start game, Esc to quit"
+            let fair_dealing = if lobby.is_fair_dealing() { "on" } else { "off" };
+            let mode = if hostless { "hostless" } else { "hosted" };
+            let invite_only = if lobby.is_invite_only() { "on" } else { "off" };
+            let controls = if settings_ui.open {
+                settings_ui.footer_hint().to_string()
+            } else if invite_input.is_some() {
+                "Paste a player identity  Enter: Invite  Esc: Cancel".to_string()
             } else {
-                "Press Esc to quit (need at least 2 players to start)"
+                format!(
+                    "S: Start  ↑/↓: Select  K: Kick  B: Ban  O: Settings  F: Fair dealing ({})  P: Mode ({})  I: Invite-only ({})  A: Invite  Esc: Quit | {}",
+                    fair_dealing, mode, invite_only, chat_ui.footer_hint()
+                )
             };
-            let footer = Paragraph::new(footer_text)
-                .alignment(Alignment::Center)
-                .style(Style::default().fg(Color::Gray));
+            let (status, status_color) = if let Some(input) = &invite_input {
+                (format!("Invite: {}_", input), Color::Yellow)
+            } else if let Some(remaining) = lobby.countdown_remaining() {
+                (format!("Starting in {}... (S to cancel)", remaining), Color::Yellow)
+            } else if let Some(notice) = &lobby_notice {
+                (notice.clone(), Color::Red)
+            } else {
+                (format!("Waiting for everyone to ready up | {}", lobby.settings().summary()), Color::Gray)
+            };
+            let footer = Paragraph::new(vec![
+                Line::from(Span::styled(controls, Style::default().fg(Color::Gray))),
+                Line::from(Span::styled(status, Style::default().fg(status_color))),
+            ])
+                .alignment(Alignment::Center);
             f.render_widget(footer, chunks[3]);
         }).map_err(|e| GameError::Other(e.to_string()))?;
 
@@ -198,15 +471,91 @@ pub async fn run_host_game() -> Result<(), GameError> {
         if event::poll(Duration::from_millis(10)).map_err(|e| GameError::Other(e.to_string()))? {
             if let Event::Key(key) = event::read().map_err(|e| GameError::Other(e.to_string()))? {
                 if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Esc => break,
-                        KeyCode::Char('s') | KeyCode::Char('S') => {
-                            if lobby.get_lobby_players().len() >= 2 {
-                                should_start_game = true;
-                                break;
+                    match settings_ui.handle_key(key.code) {
+                        SettingsKeyResult::Apply(settings) => {
+                            lobby.set_settings(settings);
+                            lobby_notice = None;
+                            continue;
+                        }
+                        SettingsKeyResult::Handled => continue,
+                        SettingsKeyResult::Ignored => {}
+                    }
+                    if let Some(input) = invite_input.as_mut() {
+                        match key.code {
+                            KeyCode::Char(c) => input.push(c),
+                            KeyCode::Backspace => {
+                                input.pop();
+                            }
+                            KeyCode::Enter => {
+                                lobby_notice = match input.trim().parse::<libp2p::PeerId>() {
+                                    Ok(peer) => {
+                                        lobby.invite(peer);
+                                        None
+                                    }
+                                    Err(_) => Some("That is not a valid player identity".to_string()),
+                                };
+                                invite_input = None;
                             }
+                            KeyCode::Esc => invite_input = None,
+                            _ => {}
                         }
-                        _ => {}
+                        continue;
+                    }
+                    match chat_ui.handle_key(key.code) {
+                        ChatKeyResult::Send(text) => {
+                            let players: Vec<(usize, String)> = lobby.get_lobby_players()
+                                .into_iter()
+                                .map(|p| (p.id, p.name))
+                                .collect();
+                            send_host_chat(&mut lobby, &mut chat_ui, &text, &players);
+                        }
+                        ChatKeyResult::Handled => {}
+                        ChatKeyResult::Ignored => match key.code {
+                            KeyCode::Esc => break,
+                            KeyCode::Char('s') | KeyCode::Char('S') => {
+                                if lobby.countdown_remaining().is_some() {
+                                    lobby.cancel_countdown();
+                                } else {
+                                    lobby_notice = lobby.start_countdown().err();
+                                }
+                            }
+                            KeyCode::Up => {
+                                selected_player = selected_player.saturating_sub(1);
+                            }
+                            KeyCode::Down => {
+                                if selected_player + 1 < lobby.get_lobby_players().len() {
+                                    selected_player += 1;
+                                }
+                            }
+                            KeyCode::Char('k') | KeyCode::Char('K') | KeyCode::Char('b') | KeyCode::Char('B') => {
+                                let ban = matches!(key.code, KeyCode::Char('b') | KeyCode::Char('B'));
+                                let players = lobby.get_lobby_players();
+                                lobby_notice = match players.get(selected_player) {
+                                    Some(p) if p.id == 0 => Some("You can't kick yourself".to_string()),
+                                    Some(p) => lobby.kick_player(p.id, ban).err(),
+                                    None => None,
+                                };
+                                selected_player = selected_player.min(lobby.get_lobby_players().len().saturating_sub(1));
+                            }
+                            KeyCode::Char('f') | KeyCode::Char('F') => {
+                                let enabled = !lobby.is_fair_dealing();
+                                lobby.set_fair_dealing(enabled);
+                            }
+                            KeyCode::Char('p') | KeyCode::Char('P') => {
+                                hostless = !hostless;
+                            }
+                            KeyCode::Char('o') | KeyCode::Char('O') => {
+                                settings_ui.open(lobby.settings());
+                            }
+                            KeyCode::Char('i') | KeyCode::Char('I') => {
+                                let enabled = !lobby.is_invite_only();
+                                lobby.set_invite_only(enabled);
+                            }
+                            KeyCode::Char('a') | KeyCode::Char('A') => {
+                                invite_input = Some(String::new());
+                            }
+                            _ => {}
+                        },
                     }
                 }
             }
@@ -214,18 +563,30 @@ pub async fn run_host_game() -> Result<(), GameError> {
     }
 
     if should_start_game {
+        // web players can't join a game in progress
+        if let Some(free_seats) = &free_seats {
+            free_seats.set(0);
+        }
         let players = lobby.get_lobby_players();
         let player_names: Vec<String> = players.iter().map(|p| p.name.clone()).collect();
 
-        let config = crate::engine::GameConfig {
-            player_names,
-            seed: None,
-            card_distribution: None,
-            round_count: 3,
-        };
+        let mut config = lobby.settings().to_config(player_names);
+        // with fair dealing the seed comes from everyone's entropy instead of the host alone
+        if let Some(fair_dealing) = lobby.state.fair_dealing() {
+            config.seed = Some(fair_dealing.seed());
+        }
 
         crate::log::host(format!("Starting game with config: {:?}", config));
 
+        if hostless {
+            let peer = crate::network::lobby_to_hostless_host(lobby, config)?;
+            crate::log::host("Started hostless game");
+            let result = run_hostless_game(&mut terminal, peer).await;
+            disable_raw_mode().ok();
+            execute!(io::stdout(), LeaveAlternateScreen).ok();
+            return result;
+        }
+
         let game = crate::engine::Game::new(config)?;
         let mut game_host = crate::network::lobby_to_game_host(lobby, game);
 
@@ -266,6 +627,9 @@ pub async fn run_host_game() -> Result<(), GameError> {
                             crate::log::host("Game ended".to_string());
                             break;
                         }
+                        GameHostEvent::ChatReceived { .. } => {
+                            ui_state.chat.on_message_received();
+                        }
                     }
                 }
                 _ = sleep(Duration::from_millis(16)) => {
@@ -274,17 +638,31 @@ pub async fn run_host_game() -> Result<(), GameError> {
             }
 
             // Render game UI
+            ui_state.track_turn_progress(&game_host.get_game_status());
             let has_drink_tray = ui_state.drink_tray_activated.get(&game_host.get_player_id()).copied().unwrap_or(false);
             let max_selections = if has_drink_tray { 2 } else { 1 };
             terminal.draw(|f| {
-                super::game_ui::render_game_ui(f, &game_host, &ui_state, submitted, max_selections);
+                super::game_ui::render_game_ui(f, &game_host, &ui_state, submitted, max_selections, Some(game_host.chat()));
             }).map_err(|e| GameError::Other(e.to_string()))?;
 
             // Handle input (non-blocking poll)
-            if !submitted && event::poll(Duration::from_millis(10)).map_err(|e| GameError::Other(e.to_string()))? {
+            if event::poll(Duration::from_millis(10)).map_err(|e| GameError::Other(e.to_string()))? {
                 if let Event::Key(key) = event::read().map_err(|e| GameError::Other(e.to_string()))? {
                     if key.kind == KeyEventKind::Press {
                         use super::input::{handle_game_input, InputAction};
+                        match ui_state.chat.handle_key(key.code) {
+                            ChatKeyResult::Send(text) => {
+                                let players: Vec<(usize, String)> = game_host.get_players_public()
+                                    .into_iter()
+                                    .map(|p| (p.id, p.name))
+                                    .collect();
+                                send_host_chat(&mut game_host, &mut ui_state.chat, &text, &players);
+                                continue;
+                            }
+                            ChatKeyResult::Handled => continue,
+                            ChatKeyResult::Ignored if submitted => continue,
+                            ChatKeyResult::Ignored => {}
+                        }
                         match handle_game_input(key.code, &mut game_host, &mut ui_state, max_selections) {
                             InputAction::Quit => break,
                             InputAction::SubmitTurn => {
@@ -316,7 +694,7 @@ pub async fn run_host_game() -> Result<(), GameError> {
         let score_data = game_host.get_score_breakdowns();
         loop {
             terminal.draw(|f| {
-                super::render_score_breakdown_data(f, score_data.clone());
+                super::render_score_breakdown_data(f, score_data.clone(), None);
             }).ok();
 
             if event::poll(Duration::from_millis(100)).ok().unwrap_or(false) {
@@ -342,25 +720,48 @@ pub async fn run_join_game() -> Result<(), GameError> {
     let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend).map_err(|e| GameError::Other(e.to_string()))?;
 
-    let mut player_name = String::new();
+    let mut identity = load_identity();
+    let mut player_name = identity.profile.name.clone();
+    let mut avatar = identity.profile.avatar;
     let mut host_address = String::new();
     let mut input_phase = 0;
+    let mut recent_ui = RecentHostsUIState::new(RecentHosts::load());
+    let mut probe = match ReachabilityProbe::new(recent_ui.addresses()).await {
+        Ok(probe) => Some(probe),
+        Err(e) => {
+            crate::log::debug("recent_hosts", format!("Could not check recent rooms: {e}"));
+            None
+        }
+    };
 
     // Input phase
     loop {
+        tokio::select! {
+            Some((address, reachability)) = async {
+                match probe.as_mut() {
+                    Some(probe) => probe.next_result().await,
+                    None => None,
+                }
+            } => {
+                recent_ui.reachability.insert(address, reachability);
+            }
+            _ = sleep(Duration::from_millis(50)) => {}
+        }
+
         terminal.draw(|f| {
             let area = f.area();
+            let show_recent = input_phase == 0 && !recent_ui.hosts.list().is_empty();
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
-                .constraints([
-                    Constraint::Length(3),
-                    Constraint::Length(3),
-                    Constraint::Min(1),
-                ])
+                .constraints(if show_recent {
+                    vec![Constraint::Length(3), Constraint::Length(3), Constraint::Min(3), Constraint::Length(2)]
+                } else {
+                    vec![Constraint::Length(3), Constraint::Length(3), Constraint::Min(1)]
+                })
                 .split(area);
 
             let prompt = if input_phase == 0 {
-                "Enter host address (e.g., /ip4/127.0.0.1/tcp/12345):"
+                "Enter room code (same network only, e.g., BOBA-7Q4K) or host address (e.g., /ip4/127.0.0.1/tcp/12345):"
             } else {
                 "Enter your name:"
             };
@@ -369,11 +770,10 @@ pub async fn run_join_game() -> Result<(), GameError> {
                 .block(Block::default().borders(Borders::ALL));
             f.render_widget(prompt_para, chunks[0]);
 
-            let current_input = if input_phase == 0 { &host_address } else { &player_name };
-            let input_display = if current_input.is_empty() {
-                "_".to_string()
+            let input_display = if input_phase == 0 {
+                Line::from(format!("{}_", host_address))
             } else {
-                format!("{}_", current_input)
+                name_input_line(avatar, &player_name)
             };
             let input = Paragraph::new(input_display)
                 .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
@@ -381,15 +781,43 @@ pub async fn run_join_game() -> Result<(), GameError> {
                 .block(Block::default().borders(Borders::ALL));
             f.render_widget(input, chunks[1]);
 
-            let footer = Paragraph::new("Press Enter to continue, Backspace to delete, Esc to cancel")
+            if show_recent {
+                render_recent_hosts_panel(f, chunks[2], &recent_ui);
+            }
+
+            let mut footer_text = vec![Line::from("Press Enter to continue, Backspace to delete, Esc to cancel")];
+            if input_phase == 1 {
+                footer_text.push(Line::from(AVATAR_KEYS_HINT));
+            } else if show_recent {
+                footer_text = vec![Line::from(if recent_ui.selected.is_some() {
+                    recent_ui.footer_hint().to_string()
+                } else {
+                    format!("Enter: Continue  Esc: Cancel  {}", recent_ui.footer_hint())
+                })];
+            }
+            let footer = Paragraph::new(footer_text)
                 .alignment(Alignment::Center)
                 .style(Style::default().fg(Color::Gray));
-            f.render_widget(footer, chunks[2]);
+            f.render_widget(footer, chunks[chunks.len() - 1]);
         }).map_err(|e| GameError::Other(e.to_string()))?;
 
-        if event::poll(Duration::from_millis(100)).map_err(|e| GameError::Other(e.to_string()))? {
+        if event::poll(Duration::from_millis(10)).map_err(|e| GameError::Other(e.to_string()))? {
             if let Event::Key(key) = event::read().map_err(|e| GameError::Other(e.to_string()))? {
                 if key.kind == KeyEventKind::Press {
+                    if input_phase == 1 && handle_avatar_key(&mut avatar, key.code) {
+                        continue;
+                    }
+                    if input_phase == 0 {
+                        match recent_ui.handle_key(key.code) {
+                            RecentHostsKeyResult::Ignored => {}
+                            RecentHostsKeyResult::Handled => continue,
+                            RecentHostsKeyResult::Join(address) => {
+                                host_address = address;
+                                input_phase = 1;
+                                continue;
+                            }
+                        }
+                    }
                     match key.code {
                         KeyCode::Char(c) => {
                             let current = if input_phase == 0 { &mut host_address } else { &mut player_name };
@@ -423,14 +851,27 @@ pub async fn run_join_game() -> Result<(), GameError> {
         }
     }
 
+    // the lobby dials the host itself, don't leave a probe connection open next to it
+    drop(probe);
+
     // Create client lobby and connect
-    let mut lobby = Client::<LobbyClientState>::new(player_name.clone()).await
+    save_profile(&mut identity, &player_name, avatar);
+    let mut lobby = Client::<LobbyClientState>::new(&identity).await
         .map_err(|e| GameError::Other(e.to_string()))?;
-    lobby.connect(&host_address)
+    lobby.join(&host_address)
         .map_err(|e| GameError::Other(e.to_string()))?;
 
-    let mut status = "Connecting...".to_string();
+    let mut status = match lobby.searching_room_code() {
+        Some(code) => format!("Looking for room {} on the local network... (codes don't reach other networks, ask for the host address)", code),
+        None => "Connecting...".to_string(),
+    };
     let mut connected = false;
+    // whether this attempt made it into the recent rooms yet
+    let mut join_recorded = false;
+    let mut chat_ui = ChatUIState::new();
+    // Some while the player is typing the room password
+    let mut password_input: Option<String> = None;
+    let mut hostless_start: Option<(Vec<crate::network::HostlessSeat>, crate::engine::GameConfig)> = None;
     let mut game_starting_data: Option<(Vec<std::collections::HashMap<crate::engine::models::CardKind, usize>>, Vec<crate::engine::models::PlayerPublic>, crate::engine::state::GameStatus)> = None;
 
     loop {
@@ -442,11 +883,30 @@ pub async fn run_join_game() -> Result<(), GameError> {
                     ClientEvent::JoinedLobby { player_id, .. } => {
                         status = format!("Connected! Your ID: {}", player_id);
                         connected = true;
+                        recent_ui.record(&host_address, lobby.room_name(), JoinOutcome::Joined);
+                        join_recorded = true;
                     }
                     ClientEvent::JoinRejected { reason } => {
                         status = format!("Rejected: {}", reason);
+                        recent_ui.record(&host_address, lobby.room_name(), JoinOutcome::Rejected(reason));
+                        join_recorded = true;
+                    }
+                    ClientEvent::PasswordRequired { reason } => {
+                        status = reason.unwrap_or_else(|| "This room needs a password".to_string());
+                        password_input = Some(String::new());
+                    }
+                    ClientEvent::Kicked { reason } => {
+                        status = reason;
+                        connected = false;
                     }
                     ClientEvent::LobbyUpdated { .. } => {}
+                    ClientEvent::SettingsUpdated { .. } => {
+                        status = "Host changed the game settings, ready up again".to_string();
+                    }
+                    ClientEvent::HostlessStarting { seats, config } => {
+                        hostless_start = Some((seats, config));
+                        break;
+                    }
                     ClientEvent::GameStarting { all_hands, players_public, game_status } => {
                         game_starting_data = Some((all_hands, players_public, game_status));
                         break;
@@ -458,6 +918,9 @@ pub async fn run_join_game() -> Result<(), GameError> {
                     ClientEvent::Error { message } => {
                         status = format!("Error: {}", message);
                     }
+                    ClientEvent::ChatReceived { .. } => {
+                        chat_ui.on_message_received();
+                    }
                 }
             }
             _ = sleep(Duration::from_millis(50)) => {}
@@ -484,24 +947,62 @@ pub async fn run_join_game() -> Result<(), GameError> {
             f.render_widget(title, chunks[0]);
 
             // Status
-            let status_para = Paragraph::new(status.clone())
+            let status_text = match (&password_input, lobby.countdown()) {
+                (Some(input), _) => format!("{} | Password: {}_", status, "*".repeat(input.chars().count())),
+                (None, Some(remaining)) if connected => format!("{} | Starting in {}...", status, remaining),
+                _ => status.clone(),
+            };
+            let status_para = Paragraph::new(status_text)
                 .style(Style::default().fg(if connected { Color::Green } else { Color::Yellow }))
                 .alignment(Alignment::Center)
-                .block(Block::default().borders(Borders::ALL).title("Status"));
+                .block(Block::default().borders(Borders::ALL).title(if lobby.is_fair_dealing() {
+                    "Status - Fair dealing on"
+                } else {
+                    "Status"
+                }));
             f.render_widget(status_para, chunks[1]);
 
             // Players
             let players = lobby.get_lobby_players();
             let player_items: Vec<ListItem> = players
                 .iter()
-                .map(|p| ListItem::new(format!("• {}", p.name)))
+                .map(|p| {
+                    let ready = if p.id == 0 { "host" } else if p.ready { "ready" } else { "not ready" };
+                    let latency = lobby.player_latency(p.id);
+                    ListItem::new(Line::from(vec![
+                        Span::raw("• "),
+                        avatar_span(p.avatar),
+                        Span::raw(format!(" {} ({}){}", p.name, ready, latency_label(latency))),
+                    ]))
+                    .style(latency_style(latency))
+                })
                 .collect();
+            let players_title = format!("Players in Lobby - {}", lobby.settings().summary());
             let player_list = List::new(player_items)
-                .block(Block::default().borders(Borders::ALL).title("Players in Lobby"));
-            f.render_widget(player_list, chunks[2]);
+                .block(Block::default().borders(Borders::ALL).title(players_title));
+            let players_area = if chat_ui.open {
+                let split = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .split(chunks[2]);
+                render_chat_panel(f, split[1], lobby.chat(), &chat_ui);
+                split[0]
+            } else {
+                chunks[2]
+            };
+            f.render_widget(player_list, players_area);
 
             // Footer
-            let footer = Paragraph::new("Press Esc to quit")
+            let controls = if password_input.is_some() {
+                "Enter: Join  Esc: Cancel".to_string()
+            } else {
+                let ready_hint = if lobby.is_ready() { "R: Not ready" } else { "R: Ready" };
+                format!("{}  Esc: Quit | {}", ready_hint, chat_ui.footer_hint())
+            };
+            let footer = Paragraph::new(vec![
+                Line::from(controls),
+                Line::from(format!("Your identity: {}", lobby.local_peer_id())),
+            ])
                 .alignment(Alignment::Center)
                 .style(Style::default().fg(Color::Gray));
             f.render_widget(footer, chunks[3]);
@@ -510,15 +1011,56 @@ pub async fn run_join_game() -> Result<(), GameError> {
         // Handle input
         if event::poll(Duration::from_millis(10)).map_err(|e| GameError::Other(e.to_string()))? {
             if let Event::Key(key) = event::read().map_err(|e| GameError::Other(e.to_string()))? {
-                if key.kind == KeyEventKind::Press && key.code == KeyCode::Esc {
-                    break;
+                if key.kind == KeyEventKind::Press {
+                    if let Some(input) = password_input.as_mut() {
+                        match key.code {
+                            KeyCode::Char(c) => input.push(c),
+                            KeyCode::Backspace => {
+                                input.pop();
+                            }
+                            KeyCode::Enter => {
+                                if let Some(password) = password_input.take() {
+                                    status = "Checking password...".to_string();
+                                    lobby.submit_password(password);
+                                }
+                            }
+                            KeyCode::Esc => password_input = None,
+                            _ => {}
+                        }
+                        continue;
+                    }
+                    match chat_ui.handle_key(key.code) {
+                        ChatKeyResult::Send(text) => send_client_chat(&mut lobby, &mut chat_ui, &text),
+                        ChatKeyResult::Handled => {}
+                        ChatKeyResult::Ignored => match key.code {
+                            KeyCode::Esc => break,
+                            KeyCode::Char('r') | KeyCode::Char('R') if connected => {
+                                let ready = !lobby.is_ready();
+                                lobby.set_ready(ready);
+                            }
+                            _ => {}
+                        },
+                    }
                 }
             }
         }
     }
 
+    if !join_recorded {
+        recent_ui.record(&host_address, "", JoinOutcome::Unreachable);
+    }
+
+    if let Some((seats, config)) = hostless_start {
+        let peer = crate::network::lobby_to_hostless_client(lobby, seats, config)?;
+        crate::log::client("Transitioned to hostless game");
+        let result = run_hostless_game(&mut terminal, peer).await;
+        disable_raw_mode().ok();
+        execute!(io::stdout(), LeaveAlternateScreen).ok();
+        return result;
+    }
+
     if let Some((all_hands, players_public, game_status)) = game_starting_data {
-        let player_id = lobby.get_player_id().unwrap();
+        let player_id = lobby.game_seat().unwrap();
         let initial_hand = all_hands.get(player_id).cloned().unwrap_or_default();
 
         let mut game_client = crate::network::lobby_to_game_client(
@@ -542,12 +1084,12 @@ pub async fn run_join_game() -> Result<(), GameError> {
                     match event {
                         GameClientEvent::GameUpdated { game_status } => {
                             crate::log::client(format!("Game updated: {:?}", game_status));
-                            // Reset for next turn
-                            submitted = false;
+                            // Reset for next turn, unless a resync says the host already has our pick
+                            submitted = game_client.state().turn_submitted;
                             ui_state.clear_selections();
                             ui_state.reset_for_new_turn();
                         }
-                        GameClientEvent::GameEnded { final_scores, reason } => {
+                        GameClientEvent::GameEnded { final_scores, reason, fairness } => {
                             crate::log::client(format!("Game ended: {:?}, scores: {:?}", reason, final_scores));
                             // display score screen
                             let score_data: Vec<(String, crate::engine::ScoreBreakdown)> = final_scores.iter()
@@ -556,7 +1098,7 @@ pub async fn run_join_game() -> Result<(), GameError> {
 
                             loop {
                                 terminal.draw(|f| {
-                                    super::render_score_breakdown_data(f, score_data.clone());
+                                    super::render_score_breakdown_data(f, score_data.clone(), fairness.as_ref());
                                 }).ok();
 
                                 if event::poll(Duration::from_millis(100)).ok().unwrap_or(false) {
@@ -573,6 +1115,24 @@ pub async fn run_join_game() -> Result<(), GameError> {
                             crate::log::client("Disconnected from host".to_string());
                             break;
                         }
+                        GameClientEvent::TurnProgress { submitted } => {
+                            crate::log::client(format!("Picked so far: {:?}", submitted));
+                        }
+                        GameClientEvent::RequestAccepted { .. } => {}
+                        GameClientEvent::RequestRejected { request, reason } => {
+                            use crate::network::GameRequestKind;
+                            match request {
+                                GameRequestKind::SubmitTurn => submitted = false,
+                                GameRequestKind::ActivateDrinkTray => {
+                                    ui_state.drink_tray_activated.remove(&game_client.get_player_id());
+                                }
+                                GameRequestKind::Sync => {}
+                            }
+                            ui_state.error_message = Some(reason);
+                        }
+                        GameClientEvent::ChatReceived { .. } => {
+                            ui_state.chat.on_message_received();
+                        }
                     }
                 }
                 _ = sleep(Duration::from_millis(16)) => {
@@ -581,17 +1141,27 @@ pub async fn run_join_game() -> Result<(), GameError> {
             }
 
             // Render game UI
+            ui_state.track_turn_progress(&game_client.get_game_status());
             let has_drink_tray = ui_state.drink_tray_activated.get(&game_client.get_player_id()).copied().unwrap_or(false);
             let max_selections = if has_drink_tray { 2 } else { 1 };
             terminal.draw(|f| {
-                crate::tui::render_game_ui(f, &game_client, &ui_state, submitted, max_selections);
+                crate::tui::render_game_ui(f, &game_client, &ui_state, submitted, max_selections, Some(game_client.chat()));
             }).map_err(|e| GameError::Other(e.to_string()))?;
 
             // Handle input (non-blocking poll)
-            if !submitted && event::poll(Duration::from_millis(10)).map_err(|e| GameError::Other(e.to_string()))? {
+            if event::poll(Duration::from_millis(10)).map_err(|e| GameError::Other(e.to_string()))? {
                 if let Event::Key(key) = event::read().map_err(|e| GameError::Other(e.to_string()))? {
                     if key.kind == KeyEventKind::Press {
                         use super::input::{handle_game_input, InputAction};
+                        match ui_state.chat.handle_key(key.code) {
+                            ChatKeyResult::Send(text) => {
+                                send_client_chat(&mut game_client, &mut ui_state.chat, &text);
+                                continue;
+                            }
+                            ChatKeyResult::Handled => continue,
+                            ChatKeyResult::Ignored if submitted => continue,
+                            ChatKeyResult::Ignored => {}
+                        }
                         match handle_game_input(key.code, &mut game_client, &mut ui_state, max_selections) {
                             InputAction::Quit => break,
                             InputAction::SubmitTurn => {
@@ -0,0 +1,60 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+use crate::network::{Avatar, Identity, ProfileColor};
+
+/// Terminal color for a profile color
+pub fn profile_color(color: ProfileColor) -> Color {
+    match color {
+        ProfileColor::Yellow => Color::Yellow,
+        ProfileColor::Red => Color::Red,
+        ProfileColor::Green => Color::Green,
+        ProfileColor::Blue => Color::Blue,
+        ProfileColor::Magenta => Color::Magenta,
+        ProfileColor::Cyan => Color::Cyan,
+        ProfileColor::White => Color::White,
+    }
+}
+
+/// The avatar glyph in the player's color, to go in front of their name
+pub fn avatar_span(avatar: Avatar) -> Span<'static> {
+    Span::styled(avatar.glyph.to_string(), Style::default().fg(profile_color(avatar.color)))
+}
+
+/// Name entry with the avatar in front, so the player sees what others will
+pub fn name_input_line(avatar: Avatar, name: &str) -> Line<'static> {
+    Line::from(vec![avatar_span(avatar), Span::raw(format!(" {}_", name))])
+}
+
+pub const AVATAR_KEYS_HINT: &str = "Tab: avatar, Left/Right: color";
+
+/// Tab cycles the glyph and Left/Right the color while the name is typed,
+/// returns false for any other key
+pub fn handle_avatar_key(avatar: &mut Avatar, code: KeyCode) -> bool {
+    match code {
+        KeyCode::Tab => *avatar = avatar.next_glyph(),
+        KeyCode::Right => avatar.color = avatar.color.next(),
+        KeyCode::Left => avatar.color = avatar.color.previous(),
+        _ => return false,
+    }
+    true
+}
+
+/// The saved identity, or a throwaway one if it can't be read or created
+pub fn load_identity() -> Identity {
+    Identity::load().unwrap_or_else(|e| {
+        crate::log::debug("identity", format!("Using a temporary identity: {e}"));
+        Identity::generate()
+    })
+}
+
+/// Keep the name and avatar for next time
+pub fn save_profile(identity: &mut Identity, name: &str, avatar: Avatar) {
+    identity.profile.name = name.to_string();
+    identity.profile.avatar = avatar;
+    if let Err(e) = identity.save_profile() {
+        crate::log::debug("identity", format!("Could not save profile: {e}"));
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use crossterm::event::KeyCode;
+use crate::network::{JoinOutcome, Reachability, RecentHost, RecentHosts};
+
+/// Recent rooms under the address input on the join screen
+pub struct RecentHostsUIState {
+    pub hosts: RecentHosts,
+    pub reachability: HashMap<String, Reachability>,
+    // highlighted row, None while the player types an address
+    pub selected: Option<usize>,
+}
+
+pub enum RecentHostsKeyResult {
+    // nothing is selected, the key goes to the address input
+    Ignored,
+    Handled,
+    Join(String),
+}
+
+impl RecentHostsUIState {
+    /// Every room starts out as being checked
+    pub fn new(hosts: RecentHosts) -> Self {
+        let reachability = hosts
+            .list()
+            .into_iter()
+            .map(|host| (host.address.clone(), Reachability::Checking))
+            .collect();
+        Self { hosts, reachability, selected: None }
+    }
+
+    pub fn addresses(&self) -> Vec<String> {
+        self.hosts.list().into_iter().map(|host| host.address.clone()).collect()
+    }
+
+    pub fn selected_host(&self) -> Option<&RecentHost> {
+        self.selected.and_then(|i| self.hosts.list().get(i).copied())
+    }
+
+    /// Down moves from the input into the list and Up back out of it.
+    /// With a room selected Enter joins it, F marks it as a favorite and
+    /// D forgets it.
+    pub fn handle_key(&mut self, key_code: KeyCode) -> RecentHostsKeyResult {
+        let count = self.hosts.list().len();
+        let Some(selected) = self.selected else {
+            if key_code == KeyCode::Down && count > 0 {
+                self.selected = Some(0);
+                return RecentHostsKeyResult::Handled;
+            }
+            return RecentHostsKeyResult::Ignored;
+        };
+        let Some(address) = self.selected_host().map(|host| host.address.clone()) else {
+            self.selected = None;
+            return RecentHostsKeyResult::Ignored;
+        };
+
+        match key_code {
+            KeyCode::Up => self.selected = selected.checked_sub(1),
+            KeyCode::Down if selected + 1 < count => self.selected = Some(selected + 1),
+            KeyCode::Esc => self.selected = None,
+            KeyCode::Enter => return RecentHostsKeyResult::Join(address),
+            KeyCode::Char('f') | KeyCode::Char('F') => {
+                log_save_error(self.hosts.toggle_favorite(&address));
+                // keep the same room highlighted now that it moved
+                self.selected = self.hosts.list().iter().position(|host| host.address == address);
+            }
+            KeyCode::Char('d') | KeyCode::Char('D') | KeyCode::Delete => {
+                log_save_error(self.hosts.remove(&address));
+                self.reachability.remove(&address);
+                self.selected = match count - 1 {
+                    0 => None,
+                    left => Some(selected.min(left - 1)),
+                };
+            }
+            _ => {}
+        }
+        RecentHostsKeyResult::Handled
+    }
+
+    /// Remember how joining `address` went
+    pub fn record(&mut self, address: &str, name: &str, outcome: JoinOutcome) {
+        log_save_error(self.hosts.record(address, name, outcome));
+    }
+
+    pub fn footer_hint(&self) -> &'static str {
+        if self.selected.is_some() {
+            "↑/↓: Select  Enter: Join  F: Favorite  D: Forget  Esc: Back to typing"
+        } else if self.hosts.list().is_empty() {
+            ""
+        } else {
+            "↓: Recent rooms"
+        }
+    }
+}
+
+// the list is a convenience, failing to save it shouldn't stop anyone from playing
+fn log_save_error(result: Result<(), String>) {
+    if let Err(e) = result {
+        crate::log::debug("recent_hosts", format!("Could not save recent rooms: {e}"));
+    }
+}
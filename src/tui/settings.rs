@@ -0,0 +1,108 @@
+use crossterm::event::KeyCode;
+use crate::engine::{GameSettings, GameVariant};
+
+/// Rows on the settings screen, variants follow the fixed rows
+pub const FIXED_ROWS: usize = 3;
+pub const ROW_ROUNDS: usize = 0;
+pub const ROW_PRESET: usize = 1;
+pub const ROW_SEED: usize = 2;
+
+/// Host-side settings screen, edits a draft that is only applied on Enter
+pub struct SettingsUIState {
+    pub open: bool,
+    pub selected: usize,
+    pub draft: GameSettings,
+    pub seed_input: String,
+}
+
+pub enum SettingsKeyResult {
+    // screen is closed, let the lobby handle the key
+    Ignored,
+    Handled,
+    Apply(GameSettings),
+}
+
+impl SettingsUIState {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            selected: 0,
+            draft: GameSettings::default(),
+            seed_input: String::new(),
+        }
+    }
+
+    /// Open the screen with a copy of the current settings
+    pub fn open(&mut self, current: &GameSettings) {
+        self.open = true;
+        self.selected = 0;
+        self.draft = current.clone();
+        self.seed_input = current.seed.map(|s| s.to_string()).unwrap_or_default();
+    }
+
+    pub fn row_count(&self) -> usize {
+        FIXED_ROWS + GameVariant::ALL.len()
+    }
+
+    pub fn handle_key(&mut self, key_code: KeyCode) -> SettingsKeyResult {
+        if !self.open {
+            return SettingsKeyResult::Ignored;
+        }
+
+        match key_code {
+            KeyCode::Esc => {
+                self.open = false;
+            }
+            KeyCode::Enter => {
+                self.open = false;
+                self.draft.seed = self.seed_input.parse().ok();
+                return SettingsKeyResult::Apply(self.draft.clone());
+            }
+            KeyCode::Up => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            KeyCode::Down if self.selected + 1 < self.row_count() => {
+                self.selected += 1;
+            }
+            KeyCode::Left | KeyCode::Right | KeyCode::Char(' ') => self.change_selected(key_code),
+            // anything longer than 19 digits would not fit in a u64
+            KeyCode::Char(c) if self.selected == ROW_SEED && c.is_ascii_digit() && self.seed_input.len() < 19 => {
+                self.seed_input.push(c);
+            }
+            KeyCode::Backspace if self.selected == ROW_SEED => {
+                self.seed_input.pop();
+            }
+            _ => {}
+        }
+        SettingsKeyResult::Handled
+    }
+
+    fn change_selected(&mut self, key_code: KeyCode) {
+        match self.selected {
+            ROW_ROUNDS => {
+                let rounds = &mut self.draft.round_count;
+                *rounds = match key_code {
+                    KeyCode::Left => rounds.saturating_sub(1),
+                    _ => *rounds + 1,
+                }
+                .clamp(GameSettings::MIN_ROUNDS, GameSettings::MAX_ROUNDS);
+            }
+            ROW_PRESET => {
+                self.draft.preset = self.draft.preset.next();
+            }
+            ROW_SEED if key_code == KeyCode::Char(' ') => {
+                self.seed_input.clear();
+            }
+            ROW_SEED => {}
+            row => {
+                if let Some(variant) = GameVariant::ALL.get(row - FIXED_ROWS) {
+                    self.draft.toggle_variant(*variant);
+                }
+            }
+        }
+    }
+
+    pub fn footer_hint(&self) -> &'static str {
+        "↑/↓: Select  ←/→/Space: Change  0-9: Seed  Enter: Apply  Esc: Cancel"
+    }
+}
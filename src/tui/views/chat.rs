@@ -0,0 +1,65 @@
+use crate::network::ChatLog;
+use crate::tui::chat::ChatUIState;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+pub fn render_chat_panel(
+    f: &mut Frame,
+    area: Rect,
+    chat: &ChatLog,
+    chat_ui: &ChatUIState,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(3),    // History
+            Constraint::Length(3), // Input
+        ])
+        .split(area);
+
+    let mut lines: Vec<Line> = Vec::new();
+    for message in chat.messages() {
+        let name_style = if chat.is_muted(message.player_id) {
+            Style::default().fg(Color::DarkGray)
+        } else {
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("[{}] ", message.time_label()), Style::default().fg(Color::Gray)),
+            Span::styled(format!("{}: ", message.player_name), name_style),
+            Span::raw(message.text.clone()),
+        ]));
+    }
+
+    // keep the newest messages visible
+    let visible = chunks[0].height.saturating_sub(2) as usize;
+    let skip = lines.len().saturating_sub(visible);
+    let history = Paragraph::new(lines.into_iter().skip(skip).collect::<Vec<_>>())
+        .block(Block::default().borders(Borders::ALL).title("Chat"))
+        .wrap(Wrap { trim: false });
+    f.render_widget(history, chunks[0]);
+
+    let input_text = if chat_ui.typing {
+        format!("> {}_", chat_ui.input)
+    } else if let Some(notice) = &chat_ui.notice {
+        notice.clone()
+    } else {
+        "Press T to type".to_string()
+    };
+    let input_style = if chat_ui.typing {
+        Style::default().fg(Color::Yellow)
+    } else if chat_ui.notice.is_some() {
+        Style::default().fg(Color::Red)
+    } else {
+        Style::default().fg(Color::Gray)
+    };
+    let input = Paragraph::new(input_text)
+        .style(input_style)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(input, chunks[1]);
+}
@@ -1,6 +1,5 @@
 use std::collections::HashMap;
-use crate::engine::CardKind;
-use crate::tui::GameInterface;
+use crate::engine::{CardKind, GameInterface};
 use super::card_details::render_card_details;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
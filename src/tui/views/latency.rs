@@ -0,0 +1,15 @@
+use crate::network::PlayerLatency;
+use ratatui::style::{Color, Style};
+
+/// Suffix for a player's name, empty until we have a measurement
+pub fn latency_label(latency: Option<PlayerLatency>) -> String {
+    latency.map(|latency| format!(" - {latency}")).unwrap_or_default()
+}
+
+/// Unstable connections stand out in red
+pub fn latency_style(latency: Option<PlayerLatency>) -> Style {
+    match latency {
+        Some(latency) if latency.unstable => Style::default().fg(Color::Red),
+        _ => Style::default(),
+    }
+}
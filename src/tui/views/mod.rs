@@ -3,8 +3,16 @@ pub mod hand;
 pub mod my_cards;
 pub mod player_cards;
 pub mod lobby;
+pub mod chat;
+pub mod settings;
+pub mod latency;
+pub mod recent_hosts;
 
 pub use hand::render_hand;
 pub use my_cards::render_my_cards;
 pub use player_cards::render_player_cards;
+pub use chat::render_chat_panel;
+pub use settings::render_settings_panel;
+pub use latency::{latency_label, latency_style};
+pub use recent_hosts::render_recent_hosts_panel;
 
@@ -1,4 +1,6 @@
-use crate::tui::GameInterface;
+use crate::engine::GameInterface;
+use crate::network::PlayerLatencies;
+use super::{latency_label, latency_style};
 use ratatui::{
     layout::Rect,
     style::{Color, Modifier, Style},
@@ -7,7 +9,7 @@ use ratatui::{
     Frame,
 };
 
-pub fn render_player_cards<G: GameInterface>(
+pub fn render_player_cards<G: GameInterface + PlayerLatencies>(
     f: &mut Frame,
     game: &G,
     viewing_player_id: usize,
@@ -28,12 +30,13 @@ pub fn render_player_cards<G: GameInterface>(
     // Player list
     let mut player_items = Vec::new();
     for (idx, player) in players_public.iter().enumerate() {
+        let latency = game.player_latency(player.id);
         let style = if idx == player_list_index {
             Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
         } else {
-            Style::default()
+            latency_style(latency)
         };
-        player_items.push(ListItem::new(Span::styled(player.name.clone(), style)));
+        player_items.push(ListItem::new(Span::styled(format!("{}{}", player.name, latency_label(latency)), style)));
     }
     
     let player_list = List::new(player_items)
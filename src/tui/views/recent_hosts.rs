@@ -0,0 +1,61 @@
+use crate::network::{recent_hosts::unix_now, Reachability};
+use crate::tui::recent_hosts::RecentHostsUIState;
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+/// Rough age of a timestamp, e.g. "5 min ago"
+pub fn last_seen_label(last_seen: u64, now: u64) -> String {
+    let secs = now.saturating_sub(last_seen);
+    match secs {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{} min ago", secs / 60),
+        3600..86400 => format!("{} h ago", secs / 3600),
+        _ => format!("{} days ago", secs / 86400),
+    }
+}
+
+fn reachability_style(reachability: Reachability) -> Style {
+    match reachability {
+        Reachability::Checking => Style::default().fg(Color::Gray),
+        Reachability::Reachable => Style::default().fg(Color::Green),
+        Reachability::Unreachable => Style::default().fg(Color::Red),
+    }
+}
+
+/// Render the rooms joined before, favorites on top
+pub fn render_recent_hosts_panel(f: &mut Frame, area: Rect, recent_ui: &RecentHostsUIState) {
+    let now = unix_now();
+    let lines: Vec<Line> = recent_ui
+        .hosts
+        .list()
+        .into_iter()
+        .enumerate()
+        .map(|(i, host)| {
+            let selected = recent_ui.selected == Some(i);
+            let style = if selected {
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let name = if host.name.is_empty() { host.address.as_str() } else { host.name.as_str() };
+            let reachability = recent_ui.reachability.get(&host.address).copied().unwrap_or(Reachability::Checking);
+            Line::from(vec![
+                Span::styled(format!("{} {} {}", if selected { ">" } else { " " }, if host.favorite { "★" } else { " " }, name), style),
+                Span::styled(format!("  {}", reachability), reachability_style(reachability)),
+                Span::styled(
+                    format!("  {}, {}  {}", host.outcome, last_seen_label(host.last_seen, now), host.address),
+                    Style::default().fg(Color::Gray),
+                ),
+            ])
+        })
+        .collect();
+
+    let panel = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("Recent Rooms"));
+    f.render_widget(panel, area);
+}
@@ -0,0 +1,49 @@
+use crate::engine::GameVariant;
+use crate::tui::settings::{SettingsUIState, FIXED_ROWS, ROW_PRESET, ROW_ROUNDS, ROW_SEED};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+/// Render the host's game settings editor
+pub fn render_settings_panel(f: &mut Frame, area: Rect, settings_ui: &SettingsUIState) {
+    let draft = &settings_ui.draft;
+    let seed = if settings_ui.seed_input.is_empty() {
+        "random".to_string()
+    } else {
+        settings_ui.seed_input.clone()
+    };
+
+    let mut rows = vec![
+        (ROW_ROUNDS, "Rounds", format!("< {} >", draft.round_count)),
+        (ROW_PRESET, "Deck", format!("< {} >", draft.preset)),
+        (ROW_SEED, "Seed", seed),
+    ];
+    for (i, variant) in GameVariant::ALL.iter().enumerate() {
+        let checked = if draft.has_variant(*variant) { "[x]" } else { "[ ]" };
+        rows.push((FIXED_ROWS + i, variant.name(), checked.to_string()));
+    }
+
+    let lines: Vec<Line> = rows
+        .into_iter()
+        .map(|(row, label, value)| {
+            let selected = row == settings_ui.selected;
+            let style = if selected {
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            Line::from(vec![
+                Span::styled(format!("{} {:<16}", if selected { ">" } else { " " }, label), style),
+                Span::styled(value, style),
+            ])
+        })
+        .collect();
+
+    let panel = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("Game Settings"));
+    f.render_widget(panel, area);
+}
@@ -0,0 +1,174 @@
+mod common;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use boba_go::bot::protocol::{format_cards, parse_cards, BotLine, EngineLine};
+use boba_go::bot::{parse_bot_seats, play_game, ExternalBot};
+use boba_go::engine::{CardKind, GameSettings};
+use common::TempDir;
+
+const FIRST_CARD_BOT: &str = concat!("sh ", env!("CARGO_MANIFEST_DIR"), "/bots/first_card.sh");
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+// a bot script in a directory of its own, gone once the guard is dropped
+fn script(name: &str, body: &str) -> (TempDir, String) {
+    let dir = TempDir::new(name);
+    let path = dir.path().join("bot.sh");
+    std::fs::write(&path, body).unwrap();
+    let command = format!("sh {}", path.display());
+    (dir, command)
+}
+
+fn one_round() -> GameSettings {
+    GameSettings { round_count: 1, seed: Some(3), ..GameSettings::default() }
+}
+
+#[test]
+fn protocol_lines_read_back() {
+    let hand = HashMap::from([(CardKind::ThaiTea, 1), (CardKind::TapiocaPearl, 2)]);
+    assert_eq!(format_cards(&hand), "TapiocaPearl:2 ThaiTea:1");
+    assert_eq!(parse_cards(&format_cards(&hand)).unwrap(), hand);
+    assert_eq!(EngineLine::Go { picks: 2, millis: 500 }.to_string(), "go 2 500");
+
+    assert_eq!("pick Matcha MangoTea".parse::<BotLine>().unwrap(), BotLine::Pick(vec![CardKind::Matcha, CardKind::MangoTea]));
+    assert_eq!("tray".parse::<BotLine>().unwrap(), BotLine::DrinkTray);
+    assert!("pick Coffee".parse::<BotLine>().is_err());
+}
+
+#[test]
+fn bot_seats_are_counted_from_one() {
+    assert_eq!(
+        parse_bot_seats("2=./bot --fast; 3=python3 b.py").unwrap(),
+        [(1, "./bot --fast".to_string()), (2, "python3 b.py".to_string())]
+    );
+    assert!(parse_bot_seats("0=./bot").is_err());
+    assert!(parse_bot_seats("2=./a;2=./b").is_err());
+    assert!(parse_bot_seats("./bot").is_err());
+}
+
+#[test]
+fn bots_play_a_simulated_game() {
+    let mut bots: Vec<ExternalBot> = (0..3).map(|_| ExternalBot::spawn(FIRST_CARD_BOT, TIMEOUT).unwrap()).collect();
+    assert_eq!(bots[0].name(), "First Card");
+
+    let scores = play_game(one_round().to_config(Vec::new()), &mut bots).unwrap();
+    assert_eq!(scores.len(), 3);
+}
+
+#[test]
+fn illegal_and_slow_bots_get_picked_for() {
+    let (_illegal_dir, illegal) = script("illegal-bot", "while read -r command rest; do\n  case \"$command\" in\n    boba) echo ready;;\n    go) echo \"pick Coffee\";;\n  esac\ndone\n");
+    let (_silent_dir, silent) = script("silent-bot", "while read -r command rest; do\n  case \"$command\" in\n    boba) echo ready;;\n  esac\ndone\n");
+    let mut bots = vec![
+        ExternalBot::spawn(&illegal, TIMEOUT).unwrap(),
+        ExternalBot::spawn(&silent, Duration::from_millis(50)).unwrap(),
+    ];
+
+    let config = one_round().to_config(Vec::new());
+    let scores = play_game(config.clone(), &mut bots).unwrap();
+
+    // falling back always plays the first cards in hand, so it plays like the sample bot
+    let mut reference = vec![
+        ExternalBot::spawn(FIRST_CARD_BOT, TIMEOUT).unwrap(),
+        ExternalBot::spawn(FIRST_CARD_BOT, TIMEOUT).unwrap(),
+    ];
+    assert_eq!(scores, play_game(config, &mut reference).unwrap());
+}
+
+#[test]
+fn bot_that_will_not_start_is_an_error() {
+    assert!(ExternalBot::spawn("boba-go-no-such-bot", TIMEOUT).is_err());
+    let (_mute_dir, mute) = script("mute-bot", "cat > /dev/null\n");
+    assert!(ExternalBot::spawn(&mute, Duration::from_millis(100)).is_err());
+}
+
+// seat `bot` in a hosted one round game, answering every pick with the first card
+#[cfg(feature = "network")]
+async fn play_hosted(bot: ExternalBot) -> Vec<(usize, f32)> {
+    use boba_go::bot::play_hosted_game;
+    use boba_go::engine::{Game, GameInterface};
+    use boba_go::network::swarm::build_memory_swarm;
+    use boba_go::network::{lobby_to_game_host, GameHostEvent};
+    use common::{first_card, lobby_host, GameNet, LobbyNet, Net};
+    use libp2p::identity::Keypair;
+
+    let (host, address) = lobby_host().await;
+    let swarm = build_memory_swarm(Keypair::generate_ed25519()).unwrap();
+    let player = tokio::spawn(async move { play_hosted_game(bot, swarm, &address, None).await });
+
+    let mut net: LobbyNet = Net::new(host, Vec::new());
+    net.run_until("the bot to ready up", |net| net.host.state().can_start()).await;
+
+    let names = net.host.get_lobby_players().into_iter().map(|p| p.name).collect();
+    let game = Game::new(one_round().to_config(names)).unwrap();
+    let mut game_host = lobby_to_game_host(net.host, game);
+    game_host.broadcast_game_update();
+    let mut net: GameNet = Net::new(game_host, Vec::new());
+
+    loop {
+        let (selected, remaining) = first_card(&net.host.get_hand());
+        GameInterface::submit_turn(&mut net.host, selected, remaining).unwrap();
+        net.run_until("the bot's pick", |net| {
+            net.host_events.iter().any(|e| matches!(e, GameHostEvent::AllPlayersSubmitted))
+        })
+        .await;
+        net.host_events.clear();
+        if let Some(GameHostEvent::GameEnded { .. }) = net.host.process_turn().unwrap() {
+            break;
+        }
+    }
+
+    tokio::time::timeout(common::TIMEOUT, async {
+        loop {
+            if player.is_finished() {
+                break player.await.unwrap();
+            }
+            net.step().await;
+        }
+    })
+    .await
+    .expect("the bot to see the game end")
+    .unwrap()
+}
+
+#[cfg(feature = "network")]
+#[tokio::test]
+async fn bot_plays_a_hosted_game() {
+    let bot = ExternalBot::spawn(FIRST_CARD_BOT, TIMEOUT).unwrap();
+    assert_eq!(play_hosted(bot).await.len(), 2);
+}
+
+#[cfg(feature = "network")]
+#[tokio::test]
+async fn hosted_game_keeps_running_while_the_bot_thinks() {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use std::time::Instant;
+
+    // the sample bot, taking its time over the first pick
+    let (_slow_dir, slow) = script(
+        "slow-bot",
+        &format!("while read -r line; do\n  case \"$line\" in go*) [ -z \"$slept\" ] && sleep 0.4 && slept=1;; esac\n  echo \"$line\"\ndone | {FIRST_CARD_BOT}\n"),
+    );
+    let bot = ExternalBot::spawn(&slow, TIMEOUT).unwrap();
+
+    // a task that only gets to run while nothing blocks the runtime, keeping
+    // the longest it had to wait
+    let longest_wait = Arc::new(AtomicU64::new(0));
+    let ticker = tokio::spawn({
+        let longest_wait = longest_wait.clone();
+        async move {
+            loop {
+                let started = Instant::now();
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                longest_wait.fetch_max(started.elapsed().as_millis() as u64, Ordering::Relaxed);
+            }
+        }
+    });
+    assert_eq!(play_hosted(bot).await.len(), 2);
+    ticker.abort();
+
+    let longest_wait = longest_wait.load(Ordering::Relaxed);
+    assert!(longest_wait < 200, "the runtime stalled for {longest_wait} ms");
+}
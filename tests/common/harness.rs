@@ -0,0 +1,359 @@
+//! In-memory network harness: one host and any number of clients on libp2p's
+//! memory transport, all driven from the test's tokio runtime.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use boba_go::engine::models::CardKind;
+use boba_go::engine::Game;
+use boba_go::network::swarm::build_memory_swarm;
+use boba_go::network::{
+    lobby_to_game_client, lobby_to_game_host, lobby_to_hostless_client, lobby_to_hostless_host, Client,
+    ClientEvent, GameClientEvent, GameClientState, GameHostEvent, GameHostState, Host, HostEvent,
+    HostlessGameState, LobbyClientState, LobbyHostState, Peer, PeerEvent,
+};
+use boba_go::engine::GameInterface;
+use libp2p::futures::future::select_all;
+use libp2p::identity::Keypair;
+
+/// How long a scripted step may take before the test fails
+pub const TIMEOUT: Duration = Duration::from_secs(20);
+
+pub const ROOM_NAME: &str = "Test Room";
+pub const HOST_NAME: &str = "Host";
+
+/// Anything with an event loop the harness can drive
+pub trait Node {
+    type Event: Debug;
+
+    fn next(&mut self) -> impl Future<Output = Option<Self::Event>>;
+}
+
+impl Node for Host<LobbyHostState> {
+    type Event = HostEvent;
+
+    fn next(&mut self) -> impl Future<Output = Option<HostEvent>> {
+        self.next_event()
+    }
+}
+
+impl Node for Client<LobbyClientState> {
+    type Event = ClientEvent;
+
+    fn next(&mut self) -> impl Future<Output = Option<ClientEvent>> {
+        self.next_event()
+    }
+}
+
+impl Node for Host<GameHostState> {
+    type Event = GameHostEvent;
+
+    fn next(&mut self) -> impl Future<Output = Option<GameHostEvent>> {
+        self.next_event()
+    }
+}
+
+impl Node for Client<GameClientState> {
+    type Event = GameClientEvent;
+
+    fn next(&mut self) -> impl Future<Output = Option<GameClientEvent>> {
+        self.next_event()
+    }
+}
+
+impl Node for Peer<HostlessGameState> {
+    type Event = PeerEvent;
+
+    fn next(&mut self) -> impl Future<Output = Option<PeerEvent>> {
+        self.next_event()
+    }
+}
+
+enum Polled<H, C> {
+    Host(Option<H>),
+    Client(usize, Option<C>),
+    Idle,
+}
+
+type Polling<'a, H, C> = Pin<Box<dyn Future<Output = Polled<H, C>> + 'a>>;
+
+/// A host and its clients with every event they produced so far.
+/// Disconnected clients stay in place as `None` so indices keep matching.
+pub struct Net<H: Node, C: Node> {
+    pub host: H,
+    pub clients: Vec<Option<C>>,
+    pub host_events: Vec<H::Event>,
+    pub client_events: Vec<Vec<C::Event>>,
+}
+
+pub type LobbyNet = Net<Host<LobbyHostState>, Client<LobbyClientState>>;
+pub type GameNet = Net<Host<GameHostState>, Client<GameClientState>>;
+pub type HostlessNet = Net<Peer<HostlessGameState>, Peer<HostlessGameState>>;
+
+impl<H: Node, C: Node> Net<H, C> {
+    pub fn new(host: H, clients: Vec<C>) -> Self {
+        let client_events = clients.iter().map(|_| Vec::new()).collect();
+        Self {
+            host,
+            clients: clients.into_iter().map(Some).collect(),
+            host_events: Vec::new(),
+            client_events,
+        }
+    }
+
+    pub fn client(&mut self, index: usize) -> &mut C {
+        self.clients[index].as_mut().expect("client is disconnected")
+    }
+
+    /// Drop a client, closing its connection to the host
+    pub fn disconnect(&mut self, index: usize) {
+        self.clients[index] = None;
+    }
+
+    pub fn connected(&self) -> impl Iterator<Item = (usize, &C)> {
+        self.clients.iter().enumerate().filter_map(|(i, c)| c.as_ref().map(|c| (i, c)))
+    }
+
+    /// Wait for the next event from anyone, or a short idle tick
+    pub async fn step(&mut self) {
+        let mut pending: Vec<Polling<'_, H::Event, C::Event>> = Vec::new();
+        let host = &mut self.host;
+        pending.push(Box::pin(async move { Polled::Host(host.next().await) }));
+        for (index, client) in self.clients.iter_mut().enumerate() {
+            if let Some(client) = client {
+                pending.push(Box::pin(async move { Polled::Client(index, client.next().await) }));
+            }
+        }
+        pending.push(Box::pin(async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Polled::Idle
+        }));
+
+        let (polled, _, rest) = select_all(pending).await;
+        drop(rest);
+        match polled {
+            Polled::Host(Some(event)) => self.host_events.push(event),
+            Polled::Client(index, Some(event)) => self.client_events[index].push(event),
+            Polled::Host(None) | Polled::Client(_, None) | Polled::Idle => {}
+        }
+    }
+
+    /// Drive every node until `done` holds, failing the test after `TIMEOUT`
+    pub async fn run_until(&mut self, what: &str, mut done: impl FnMut(&Self) -> bool) {
+        let deadline = tokio::time::Instant::now() + TIMEOUT;
+        while !done(self) {
+            if tokio::time::Instant::now() > deadline {
+                panic!(
+                    "timed out waiting for {what}\nhost events: {:?}\nclient events: {:?}",
+                    self.host_events, self.client_events
+                );
+            }
+            self.step().await;
+        }
+    }
+
+    /// Keep driving for a while, for asserting that something does not happen
+    pub async fn run_for(&mut self, duration: Duration) {
+        let deadline = tokio::time::Instant::now() + duration;
+        while tokio::time::Instant::now() < deadline {
+            self.step().await;
+        }
+    }
+}
+
+/// A lobby host listening on a fresh memory address
+pub async fn lobby_host() -> (Host<LobbyHostState>, String) {
+    let swarm = build_memory_swarm(Keypair::generate_ed25519()).expect("memory swarm");
+    let mut host = Host::<LobbyHostState>::with_swarm(swarm, ROOM_NAME.to_string(), HOST_NAME.to_string())
+        .expect("lobby host");
+    host.listen("/memory/0").expect("listen");
+
+    let mut net: LobbyNet = Net::new(host, Vec::new());
+    net.run_until("host to listen", |net| {
+        net.host_events.iter().any(|e| matches!(e, HostEvent::Listening { .. }))
+    })
+    .await;
+    let address = net
+        .host_events
+        .iter()
+        .find_map(|e| match e {
+            HostEvent::Listening { address } => Some(address.to_string()),
+            _ => None,
+        })
+        .unwrap();
+    (net.host, address)
+}
+
+/// A client that dials `address` straight away, without waiting for the answer
+pub fn lobby_client(name: &str, address: &str) -> Client<LobbyClientState> {
+    let swarm = build_memory_swarm(Keypair::generate_ed25519()).expect("memory swarm");
+    let mut client = Client::<LobbyClientState>::with_swarm(swarm, name.to_string()).expect("lobby client");
+    client.join(address).expect("join");
+    client
+}
+
+pub fn player_name(index: usize) -> String {
+    format!("Player {}", index + 1)
+}
+
+pub fn joined(events: &[ClientEvent]) -> bool {
+    events.iter().any(|e| matches!(e, ClientEvent::JoinedLobby { .. }))
+}
+
+/// Host plus `players` clients, all joined and seeing the full player list
+pub async fn lobby(players: usize) -> LobbyNet {
+    let (host, address) = lobby_host().await;
+    join_lobby(host, &address, players).await
+}
+
+/// Like `lobby`, but the host runs the room the way `boba_server` does, without a seat
+pub async fn dedicated_lobby(players: usize) -> LobbyNet {
+    let (mut host, address) = lobby_host().await;
+    *host.state_mut() = LobbyHostState::dedicated(ROOM_NAME.to_string(), HOST_NAME.to_string());
+    join_lobby(host, &address, players).await
+}
+
+async fn join_lobby(host: Host<LobbyHostState>, address: &str, players: usize) -> LobbyNet {
+    let seats = players + usize::from(host.state().host_plays());
+    let clients = (0..players).map(|i| lobby_client(&player_name(i), address)).collect();
+    let mut net = Net::new(host, clients);
+
+    net.run_until("everyone to join", |net| {
+        net.client_events.iter().all(|events| joined(events))
+            && net.connected().all(|(_, c)| c.get_lobby_players().len() == seats)
+    })
+    .await;
+    net
+}
+
+/// Ready everyone up and move host and clients into the game, the way the TUI does
+pub async fn start_game(mut net: LobbyNet) -> GameNet {
+    let indices: Vec<usize> = net.connected().map(|(i, _)| i).collect();
+    for &i in &indices {
+        net.client(i).set_ready(true);
+    }
+    net.run_until("everyone to be ready", |net| net.host.state().can_start()).await;
+
+    let names = net.host.get_lobby_players().into_iter().map(|p| p.name).collect();
+    let mut config = net.host.settings().to_config(names);
+    if let Some(fair_dealing) = net.host.state().fair_dealing() {
+        config.seed = Some(fair_dealing.seed());
+    }
+    let game = Game::new(config).expect("game");
+    let mut game_host = lobby_to_game_host(net.host, game);
+    game_host.broadcast_game_update();
+
+    let mut starting: Net<Host<GameHostState>, Client<LobbyClientState>> =
+        Net::new(game_host, net.clients.into_iter().flatten().collect());
+    starting
+        .run_until("clients to see the game start", |net| {
+            net.client_events
+                .iter()
+                .all(|events| events.iter().any(|e| matches!(e, ClientEvent::GameStarting { .. })))
+        })
+        .await;
+
+    let mut clients = Vec::new();
+    for (client, events) in starting.clients.into_iter().flatten().zip(starting.client_events) {
+        let Some(ClientEvent::GameStarting { all_hands, players_public, game_status }) = events
+            .into_iter()
+            .find(|e| matches!(e, ClientEvent::GameStarting { .. }))
+        else {
+            unreachable!()
+        };
+        let seat = client.game_seat().expect("seated");
+        let hand = all_hands[seat].clone();
+        clients.push(lobby_to_game_client(client, seat, hand, players_public, game_status));
+    }
+    Net::new(starting.host, clients)
+}
+
+/// Ready everyone up and start a hostless game, the lobby host becomes a peer
+pub async fn start_hostless(mut net: LobbyNet) -> HostlessNet {
+    let indices: Vec<usize> = net.connected().map(|(i, _)| i).collect();
+    for &i in &indices {
+        net.client(i).set_ready(true);
+    }
+    net.run_until("everyone to be ready", |net| net.host.state().can_start()).await;
+
+    let names = net.host.get_lobby_players().into_iter().map(|p| p.name).collect();
+    let config = net.host.settings().to_config(names);
+    let host_peer = lobby_to_hostless_host(net.host, config).expect("hostless host");
+
+    let mut starting: Net<Peer<HostlessGameState>, Client<LobbyClientState>> =
+        Net::new(host_peer, net.clients.into_iter().flatten().collect());
+    starting
+        .run_until("clients to see the game start", |net| {
+            net.client_events
+                .iter()
+                .all(|events| events.iter().any(|e| matches!(e, ClientEvent::HostlessStarting { .. })))
+        })
+        .await;
+
+    let mut peers = Vec::new();
+    for (client, events) in starting.clients.into_iter().flatten().zip(starting.client_events) {
+        let Some(ClientEvent::HostlessStarting { seats, config }) = events
+            .into_iter()
+            .find(|e| matches!(e, ClientEvent::HostlessStarting { .. }))
+        else {
+            unreachable!()
+        };
+        peers.push(lobby_to_hostless_client(client, seats, config).expect("hostless client"));
+    }
+    Net::new(starting.host, peers)
+}
+
+/// Pick the first card in a hand, the smallest possible legal move
+pub fn first_card(hand: &HashMap<CardKind, usize>) -> (HashMap<CardKind, usize>, HashMap<CardKind, usize>) {
+    let card = *hand.iter().filter(|(_, count)| **count > 0).map(|(card, _)| card).min().expect("empty hand");
+    let mut remaining = hand.clone();
+    *remaining.get_mut(&card).unwrap() -= 1;
+    remaining.retain(|_, count| *count > 0);
+    (HashMap::from([(card, 1)]), remaining)
+}
+
+pub fn game_ended(events: &[GameClientEvent]) -> bool {
+    events.iter().any(|e| matches!(e, GameClientEvent::GameEnded { .. }))
+}
+
+/// Everyone plays their first card until the game is over, host included if it has a seat
+pub async fn play_to_end(net: &mut GameNet) {
+    loop {
+        if net.host.state().local_player_id.is_some() {
+            let (selected, remaining) = first_card(&net.host.get_hand());
+            GameInterface::submit_turn(&mut net.host, selected, remaining).expect("host turn");
+        }
+        let indices: Vec<usize> = net.connected().map(|(i, _)| i).collect();
+        for i in indices {
+            let client = net.client(i);
+            let (selected, remaining) = first_card(&client.get_hand());
+            GameInterface::submit_turn(client, selected, remaining).expect("client turn");
+        }
+
+        let status = net.host.get_game_status();
+        let turn = (status.round, status.turn);
+        net.run_until("every turn to arrive", |net| {
+            net.host_events.iter().any(|e| matches!(e, GameHostEvent::AllPlayersSubmitted))
+        })
+        .await;
+        net.host_events.clear();
+
+        if let Some(GameHostEvent::GameEnded { .. }) = net.host.process_turn().expect("turn") {
+            net.run_until("clients to see the game end", |net| {
+                net.client_events.iter().all(|events| game_ended(events))
+            })
+            .await;
+            return;
+        }
+        net.run_until("clients to get the next hand", |net| {
+            net.connected().all(|(_, c)| {
+                let status = c.get_game_status();
+                (status.round, status.turn) != turn
+            })
+        })
+        .await;
+    }
+}
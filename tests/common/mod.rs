@@ -0,0 +1,35 @@
+//! Shared test helpers. The network harness needs the `network` feature,
+//! the rest works in headless builds too.
+
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "network")]
+mod harness;
+#[cfg(feature = "network")]
+#[allow(unused_imports)]
+pub use harness::*;
+
+/// A directory of our own under the system temp dir, emptied first and
+/// removed again once the guard is dropped
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("boba-go-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("temp dir");
+        Self(dir)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
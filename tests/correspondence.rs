@@ -0,0 +1,158 @@
+#![cfg(feature = "network")]
+
+mod common;
+
+use std::collections::HashMap;
+
+use boba_go::engine::{CardKind, Game, GameSettings};
+use boba_go::network::correspondence::{Pick, SignedPick, TURN_FILE_EXTENSION};
+use boba_go::network::{CorrespondenceGame, Identity, TurnFile};
+use boba_go::engine::GameInterface;
+use common::TempDir;
+
+const PLAYERS: [&str; 3] = ["Ana", "Bo", "Cy"];
+
+fn new_file(identities: &[Identity]) -> TurnFile {
+    let settings = GameSettings { seed: Some(7), ..GameSettings::default() };
+    let seats = identities.iter().map(|identity| (identity.profile.name.clone(), identity.peer_id())).collect();
+    TurnFile::new(settings, seats).unwrap()
+}
+
+fn identities() -> Vec<Identity> {
+    PLAYERS
+        .iter()
+        .map(|name| {
+            let mut identity = Identity::generate();
+            identity.profile.name = name.to_string();
+            identity
+        })
+        .collect()
+}
+
+// the first card in hand, the rest stays in it
+fn first_card(hand: &HashMap<CardKind, usize>) -> (HashMap<CardKind, usize>, HashMap<CardKind, usize>) {
+    let card = *hand.keys().min().unwrap();
+    let mut remaining = hand.clone();
+    *remaining.get_mut(&card).unwrap() -= 1;
+    remaining.retain(|_, count| *count > 0);
+    (HashMap::from([(card, 1)]), remaining)
+}
+
+fn pick_first_card(file: TurnFile, identity: &Identity) -> TurnFile {
+    let seat = file.seat_for(identity).expect("a seat");
+    let mut game = CorrespondenceGame::new(file, seat, identity).unwrap();
+    let (selected, remaining) = first_card(&game.get_hand());
+    game.submit_turn(selected, remaining).unwrap();
+    game.file().clone()
+}
+
+#[test]
+fn passing_the_file_around_plays_the_same_game_as_the_engine() {
+    let identities = identities();
+    let dir = TempDir::new("sequential");
+    let path = dir.path().join(format!("lunch.{TURN_FILE_EXTENSION}"));
+    new_file(&identities).save(&path).unwrap();
+
+    for _ in 0..2 {
+        for identity in &identities {
+            let file = TurnFile::load(&path).unwrap();
+            pick_first_card(file, identity).save(&path).unwrap();
+        }
+    }
+
+    let file = TurnFile::load(&path).unwrap();
+    assert_eq!(file.turns.len(), 2);
+    assert!(file.picks.is_empty());
+
+    // the same picks made straight on the engine
+    let settings = GameSettings { seed: Some(7), ..GameSettings::default() };
+    let mut game = Game::new(settings.to_config(PLAYERS.iter().map(|p| p.to_string()).collect())).unwrap();
+    for _ in 0..2 {
+        let submissions = (0..PLAYERS.len())
+            .map(|seat| {
+                game.mark_player_selected(seat).unwrap();
+                Some(first_card(game.get_player_hand(seat).unwrap()))
+            })
+            .collect();
+        game.process_turn(submissions).unwrap();
+    }
+    assert_eq!(file.replay().unwrap().state_hash(), game.state_hash());
+}
+
+#[test]
+fn picks_sent_back_as_separate_files_are_collected() {
+    let identities = identities();
+    let dir = TempDir::new("parallel");
+    let start = new_file(&identities);
+    start.save(&dir.path().join(format!("game.{TURN_FILE_EXTENSION}"))).unwrap();
+
+    // everyone picks on their own copy and mails it back
+    for identity in &identities[1..] {
+        let name = format!("{}.{TURN_FILE_EXTENSION}", identity.profile.name);
+        pick_first_card(start.clone(), identity).save(&dir.path().join(name)).unwrap();
+    }
+    std::fs::write(dir.path().join("notes.txt"), "not a turn file").unwrap();
+
+    let mut file = TurnFile::load(&dir.path().join(format!("game.{TURN_FILE_EXTENSION}"))).unwrap();
+    assert_eq!(file.collect_from(dir.path()), 2);
+    assert_eq!(file.waiting_on(), ["Ana"]);
+
+    let file = pick_first_card(file, &identities[0]);
+    assert_eq!(file.turns.len(), 1);
+    assert_eq!(file.waiting_on().len(), PLAYERS.len());
+}
+
+#[test]
+fn seats_belong_to_the_identities_named_at_creation() {
+    let identities = identities();
+    let file = new_file(&identities);
+    assert_eq!(file.seat_for(&identities[1]), Some(1));
+
+    // someone else under the same name gets no seat, even one nobody has picked for yet
+    let mut impostor = Identity::generate();
+    impostor.profile.name = "Ana".to_string();
+    assert_eq!(file.seat_for(&impostor), None);
+    assert!(CorrespondenceGame::new(file.clone(), 0, &impostor).is_err());
+
+    // a pick signed by anyone but the seat's identity is refused
+    let mut forged = pick_first_card(file, &identities[0]);
+    forged.picks[0].public_key = impostor.keypair.public().encode_protobuf();
+    assert!(forged.verify().is_err());
+    let mut copy = new_file(&identities);
+    assert!(copy.add_pick(forged.picks[0].clone()).is_err());
+
+    let twice = vec![("Ana".to_string(), identities[0].peer_id()), ("Bo".to_string(), identities[0].peer_id())];
+    assert!(TurnFile::new(GameSettings::default(), twice).is_err());
+}
+
+#[test]
+fn tampered_files_are_refused() {
+    let identities = identities();
+    let mut file = pick_first_card(new_file(&identities), &identities[0]);
+    file.picks[0].pick.selected[0].1 += 1;
+    assert!(file.verify().is_err());
+
+    let mut other_game = new_file(&identities[..2]);
+    assert!(other_game.merge(&pick_first_card(new_file(&identities), &identities[0])).is_err());
+}
+
+#[test]
+fn signed_picks_of_cards_not_in_hand_are_refused() {
+    let identities = identities();
+    let mut file = new_file(&identities);
+    let game = file.replay().unwrap();
+    let hand = game.get_player_hand(0).unwrap();
+    let missing = *CardKind::ALL.iter().find(|card| !hand.contains_key(card)).expect("a card not in hand");
+    let mut remaining: Vec<(CardKind, usize)> = hand.iter().map(|(card, count)| (*card, *count)).collect();
+    remaining.sort();
+    let pick = Pick { seat: 0, turn: 0, drink_tray: false, selected: vec![(missing, 1)], remaining };
+    let signed = SignedPick::sign(&file.game_id(), pick, &identities[0].keypair).unwrap();
+    assert!(file.add_pick(signed).is_err());
+    assert!(!file.has_picked(0));
+
+    // the seat can still pick, and the turn goes ahead
+    for identity in &identities {
+        file = pick_first_card(file, identity);
+    }
+    assert_eq!(file.turns.len(), 1);
+}
@@ -0,0 +1,125 @@
+use std::time::Instant;
+
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use boba_go::engine::constants;
+use boba_go::engine::env::{Action, Env, ACTION_COUNT, MAX_PLAYERS, OBSERVATION_SIZE};
+use boba_go::engine::{CardKind, GameSettings};
+
+fn env(players: usize) -> Env {
+    Env::new(players, GameSettings { seed: Some(9), ..GameSettings::default() }).unwrap()
+}
+
+// plays random legal actions to the end, returning the rewards each player collected
+fn self_play(env: &mut Env, rng: &mut ChaCha8Rng) -> Vec<f32> {
+    let mut totals = vec![0.0; env.num_players()];
+    while !env.is_done() {
+        for player in 0..env.num_players() {
+            while env.needs_action(player) {
+                let mask = env.legal_actions(player);
+                let legal: Vec<usize> = (0..ACTION_COUNT).filter(|i| mask[*i]).collect();
+                let action = Action::from_index(*legal.choose(rng).unwrap()).unwrap();
+                let step = env.step(player, action).unwrap();
+                for (total, reward) in totals.iter_mut().zip(&step.rewards) {
+                    *total += reward;
+                }
+            }
+        }
+    }
+    totals
+}
+
+#[test]
+fn sizes_fit_every_table() {
+    assert_eq!(MAX_PLAYERS, constants::max_players());
+    assert_eq!(env(MAX_PLAYERS).observation(MAX_PLAYERS - 1).unwrap().len(), OBSERVATION_SIZE);
+    assert_eq!(env(3).observation(3), None);
+    assert!(Env::new(MAX_PLAYERS + 1, GameSettings::default()).is_err());
+    for index in 0..ACTION_COUNT {
+        assert_eq!(Action::from_index(index).unwrap().index(), index);
+    }
+    assert_eq!(Action::from_index(ACTION_COUNT), None);
+    for (index, card) in CardKind::ALL.iter().enumerate() {
+        assert_eq!(card.index(), index);
+        assert_eq!(Action::Pick(*card).index(), index);
+    }
+}
+
+#[test]
+fn mask_follows_the_hand() {
+    let mut env = env(3);
+    let hand = env.game().get_player_hand(0).unwrap().clone();
+    let mask = env.legal_actions(0);
+    for card in CardKind::ALL {
+        assert_eq!(mask[Action::Pick(*card).index()], hand.contains_key(card));
+    }
+    // nobody has a Drink Tray on the table yet
+    assert!(!mask[Action::ActivateDrinkTray.index()]);
+    assert!(env.step(0, Action::ActivateDrinkTray).is_err());
+
+    let missing = CardKind::ALL.iter().find(|card| !hand.contains_key(card));
+    if let Some(card) = missing {
+        assert!(env.step(0, Action::Pick(*card)).is_err());
+    }
+
+    let card = *hand.keys().next().unwrap();
+    let step = env.step(0, Action::Pick(card)).unwrap();
+    assert!(!step.turn_resolved);
+    assert!(!env.needs_action(0));
+    assert!(env.legal_actions(0).iter().all(|legal| !legal));
+}
+
+#[test]
+fn reset_deals_the_same_game_for_the_same_seed() {
+    let mut env = env(4);
+    env.reset(42).unwrap();
+    let first = env.observation(2).unwrap();
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    self_play(&mut env, &mut rng);
+
+    env.reset(42).unwrap();
+    assert!(!env.is_done());
+    assert_eq!(env.scores(), [0.0; 4]);
+    assert_eq!(env.observation(2).unwrap(), first);
+}
+
+#[test]
+fn rewards_add_up_to_the_final_score() {
+    let mut env = env(5);
+    let mut rng = ChaCha8Rng::seed_from_u64(7);
+    for seed in 0..20 {
+        env.reset(seed).unwrap();
+        let totals = self_play(&mut env, &mut rng);
+        for (player, total) in totals.iter().enumerate() {
+            let (score, _) = env.game().calculate_player_score(player).unwrap();
+            assert!((total - score).abs() < 1e-3, "player {player}: rewards {total}, score {score}");
+        }
+    }
+}
+
+#[test]
+fn history_is_not_kept_for_self_play() {
+    let mut env = env(3);
+    let mut rng = ChaCha8Rng::seed_from_u64(3);
+    self_play(&mut env, &mut rng);
+    assert!(env.game().history().is_empty());
+}
+
+// self-play is meant for training, which wants thousands of games a second
+#[test]
+#[ignore = "timing, run with cargo test --release -- --ignored"]
+fn self_play_runs_thousands_of_games_a_second() {
+    const GAMES: u64 = 2000;
+    let mut env = env(4);
+    let mut rng = ChaCha8Rng::seed_from_u64(5);
+    let started = Instant::now();
+    for seed in 0..GAMES {
+        env.reset(seed).unwrap();
+        self_play(&mut env, &mut rng);
+    }
+    let per_second = GAMES as f64 / started.elapsed().as_secs_f64();
+    println!("{per_second:.0} games a second");
+    assert!(per_second >= 1000.0, "only {per_second:.0} games a second");
+}
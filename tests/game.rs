@@ -0,0 +1,334 @@
+#![cfg(feature = "network")]
+
+mod common;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use boba_go::engine::fairness::FairnessReport;
+use boba_go::engine::{CardKind, PlayerTurnState};
+use boba_go::network::{GameClientEvent, GameEndReason, GameHostEvent, PeerEvent};
+use boba_go::engine::GameInterface;
+use common::{dedicated_lobby, first_card, game_ended, lobby, play_to_end, player_name, start_game, start_hostless, HOST_NAME};
+
+#[tokio::test]
+async fn every_player_gets_their_own_seat() {
+    let mut net = start_game(lobby(2).await).await;
+
+    assert_eq!(net.host.get_player_id(), 0);
+    for i in 0..2 {
+        let client = net.client(i);
+        let seat = client.get_player_id();
+        let players = client.get_players_public();
+        assert_eq!(players.len(), 3);
+        assert_eq!(players[seat].name, player_name(i));
+        assert_eq!(players[0].name, HOST_NAME);
+        assert!(!client.get_hand().is_empty());
+    }
+}
+
+#[tokio::test]
+async fn full_game_reaches_the_same_scores_everywhere() {
+    let mut net = start_game(lobby(2).await).await;
+    play_to_end(&mut net).await;
+
+    assert!(net.host.get_game_status().is_game_over);
+    let host_scores: Vec<(usize, f32)> = net
+        .host
+        .get_players_public()
+        .iter()
+        .map(|p| (p.id, net.host.state().game.calculate_player_score(p.id).unwrap().0))
+        .collect();
+
+    for events in &net.client_events {
+        let Some(GameClientEvent::GameEnded { final_scores, reason, .. }) =
+            events.iter().find(|e| matches!(e, GameClientEvent::GameEnded { .. }))
+        else {
+            unreachable!()
+        };
+        assert!(matches!(reason, GameEndReason::Completed));
+        let scores: Vec<(usize, f32)> = final_scores.iter().map(|(id, score, _, _)| (*id, *score)).collect();
+        assert_eq!(scores, host_scores);
+    }
+}
+
+#[tokio::test]
+async fn disconnect_ends_the_game_for_everyone() {
+    let mut net = start_game(lobby(2).await).await;
+    let seat = net.client(0).get_player_id();
+
+    net.disconnect(0);
+    net.run_until("the remaining player to see the game end", |net| game_ended(&net.client_events[1])).await;
+
+    assert!(net.host_events.iter().any(|e| matches!(
+        e,
+        GameHostEvent::GameEnded { reason: GameEndReason::PlayerDisconnected { player_id }, .. } if *player_id == seat
+    )));
+}
+
+#[tokio::test]
+async fn hostless_game_reaches_the_same_scores_everywhere() {
+    let mut net = start_hostless(lobby(2).await).await;
+
+    while !net.host.get_game_status().is_game_over {
+        let turns = net.host_events.len();
+        let (selected, remaining) = first_card(&net.host.get_hand());
+        GameInterface::submit_turn(&mut net.host, selected, remaining).unwrap();
+        for i in 0..2 {
+            let peer = net.client(i);
+            let (selected, remaining) = first_card(&peer.get_hand());
+            GameInterface::submit_turn(peer, selected, remaining).unwrap();
+        }
+        net.run_until("every peer to resolve the turn", |net| {
+            let resolved = |events: &[PeerEvent]| {
+                events.iter().filter(|e| matches!(e, PeerEvent::TurnResolved | PeerEvent::GameOver)).count()
+            };
+            resolved(&net.host_events[turns..]) == 1 && net.client_events.iter().all(|events| resolved(events) == resolved(&net.host_events))
+        })
+        .await;
+        assert!(!net.host_events.iter().any(|e| matches!(e, PeerEvent::Aborted { .. })));
+    }
+
+    // same cards on the table means the same scores
+    let tables = |players: Vec<boba_go::engine::models::PlayerPublic>| {
+        players.into_iter().map(|p| (p.name, p.public_cards)).collect::<Vec<_>>()
+    };
+    let host_tables = tables(net.host.get_players_public());
+    for i in 0..2 {
+        assert!(net.client(i).get_game_status().is_game_over);
+        assert_eq!(tables(net.client(i).get_players_public()), host_tables);
+    }
+}
+
+#[tokio::test]
+async fn hostless_peers_only_take_messages_for_the_next_turn() {
+    let net = start_hostless(lobby(2).await).await;
+    let state = net.host.state();
+    let (round, turn) = state.current_turn();
+    assert!(state.accepts_turn(round, turn));
+    assert!(state.accepts_turn(round, turn + 1));
+    assert!(state.accepts_turn(round + 1, 1));
+    assert!(!state.accepts_turn(round, turn + 2));
+    assert!(!state.accepts_turn(round + 2, 1));
+    assert!(!state.accepts_turn(round - 1, turn));
+}
+
+#[tokio::test]
+async fn hostless_game_gives_up_on_a_peer_that_never_reveals() {
+    let mut net = start_hostless(lobby(2).await).await;
+    net.host.state_mut().reveal_timeout = Duration::from_millis(300);
+    net.client(0).state_mut().reveal_timeout = Duration::from_millis(300);
+
+    // commits like everyone else, then stops answering without leaving
+    let staller = net.client(1);
+    let seat = staller.get_player_id();
+    let name = staller.state().player_name(seat);
+    let (selected, remaining) = first_card(&staller.get_hand());
+    GameInterface::submit_turn(staller, selected, remaining).unwrap();
+    let committed = |events: &[PeerEvent]| events.iter().any(|e| matches!(e, PeerEvent::PlayerCommitted { player_id } if *player_id == seat));
+    net.run_until("the others to see the commitment", |net| committed(&net.host_events) && committed(&net.client_events[0]))
+        .await;
+    let _stalled = net.clients[1].take();
+
+    let (selected, remaining) = first_card(&net.host.get_hand());
+    GameInterface::submit_turn(&mut net.host, selected, remaining).unwrap();
+    let peer = net.client(0);
+    let (selected, remaining) = first_card(&peer.get_hand());
+    GameInterface::submit_turn(peer, selected, remaining).unwrap();
+
+    let gave_up = |events: &[PeerEvent]| {
+        events.iter().any(|e| matches!(e, PeerEvent::Aborted { reason } if reason.contains(&name)))
+    };
+    net.run_until("the table to give up on the staller", |net| gave_up(&net.host_events) && gave_up(&net.client_events[0]))
+        .await;
+}
+
+fn updates(events: &[GameClientEvent]) -> usize {
+    events.iter().filter(|e| matches!(e, GameClientEvent::GameUpdated { .. })).count()
+}
+
+#[tokio::test]
+async fn client_resyncs_on_request() {
+    let mut net = start_game(lobby(2).await).await;
+    let seat = net.client(0).get_player_id();
+    let dealt = net.host.state().game.get_player_hand(seat).unwrap().clone();
+
+    // pretend we lost track of the game
+    net.client(0).state_mut().hand.clear();
+    net.client(0).request_sync();
+    net.run_until("the resync", |net| updates(&net.client_events[0]) == 1).await;
+
+    assert_eq!(net.client(0).get_hand(), dealt);
+    let version = net.host.state().state_version;
+    assert_eq!(net.client(0).state().state_version, version);
+}
+
+#[tokio::test]
+async fn version_gap_triggers_a_resync() {
+    let mut net = start_game(lobby(2).await).await;
+    // as if the first update never arrived
+    net.client(0).state_mut().state_version = 0;
+
+    let (selected, remaining) = first_card(&net.host.get_hand());
+    GameInterface::submit_turn(&mut net.host, selected, remaining).unwrap();
+    for i in 0..2 {
+        let client = net.client(i);
+        let (selected, remaining) = first_card(&client.get_hand());
+        GameInterface::submit_turn(client, selected, remaining).unwrap();
+    }
+    net.run_until("every turn to arrive", |net| {
+        net.host_events.iter().any(|e| matches!(e, GameHostEvent::AllPlayersSubmitted))
+    })
+    .await;
+    net.host.process_turn().unwrap();
+
+    net.run_until("the update and the resync", |net| updates(&net.client_events[0]) == 2).await;
+    net.run_until("the other player's update", |net| updates(&net.client_events[1]) == 1).await;
+    net.run_for(Duration::from_millis(300)).await;
+    assert_eq!(updates(&net.client_events[1]), 1);
+}
+
+#[tokio::test]
+async fn resync_after_the_end_returns_the_result() {
+    let mut net = start_game(lobby(2).await).await;
+    play_to_end(&mut net).await;
+
+    net.client(0).request_sync();
+    net.run_until("the final result again", |net| {
+        net.client_events[0].iter().filter(|e| matches!(e, GameClientEvent::GameEnded { .. })).count() == 2
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn other_players_see_who_has_picked() {
+    let mut net = start_game(lobby(2).await).await;
+    let seat = net.client(0).get_player_id();
+
+    let (selected, remaining) = first_card(&net.client(0).get_hand());
+    GameInterface::submit_turn(net.client(0), selected, remaining).unwrap();
+    net.run_until("the turn progress", |net| {
+        net.client_events[1]
+            .iter()
+            .any(|e| matches!(e, GameClientEvent::TurnProgress { submitted } if submitted == &vec![seat]))
+    })
+    .await;
+
+    let status = net.client(1).get_game_status();
+    assert!(matches!(status.player_turn_states[seat], PlayerTurnState::Selected));
+    assert!(matches!(status.player_turn_states[0], PlayerTurnState::NotSelected));
+}
+
+fn nonzero(cards: &HashMap<CardKind, usize>) -> HashMap<CardKind, usize> {
+    cards.iter().filter(|(_, count)| **count > 0).map(|(card, count)| (*card, *count)).collect()
+}
+
+#[tokio::test]
+async fn later_updates_are_deltas_that_match_the_host() {
+    let mut net = start_game(lobby(2).await).await;
+    for turn in 1..=2 {
+        let (selected, remaining) = first_card(&net.host.get_hand());
+        GameInterface::submit_turn(&mut net.host, selected, remaining).unwrap();
+        for i in 0..2 {
+            let client = net.client(i);
+            let (selected, remaining) = first_card(&client.get_hand());
+            GameInterface::submit_turn(client, selected, remaining).unwrap();
+        }
+        net.run_until("every turn to arrive", |net| {
+            net.host_events.iter().filter(|e| matches!(e, GameHostEvent::AllPlayersSubmitted)).count() == turn
+        })
+        .await;
+        net.host.process_turn().unwrap();
+        net.run_until("the update", |net| (0..2).all(|i| updates(&net.client_events[i]) == turn)).await;
+    }
+
+    // both players acknowledged the first update by submitting against it
+    assert!(net.host.state().delta_base().is_some());
+    let host_players = net.host.get_players_public();
+    for i in 0..2 {
+        let seat = net.client(i).get_player_id();
+        let host_hand = net.host.state().game.get_player_hand(seat).unwrap().clone();
+        assert_eq!(nonzero(&net.client(i).get_hand()), nonzero(&host_hand));
+        for (player, host_player) in net.client(i).get_players_public().iter().zip(&host_players) {
+            assert_eq!(nonzero(&player.public_cards), nonzero(&host_player.public_cards));
+        }
+    }
+}
+
+// what every client concluded about the deal once the game was over
+fn fairness_reports(net: &common::GameNet) -> Vec<FairnessReport> {
+    net.client_events
+        .iter()
+        .map(|events| {
+            events
+                .iter()
+                .find_map(|e| match e {
+                    GameClientEvent::GameEnded { fairness, .. } => fairness.clone(),
+                    _ => None,
+                })
+                .expect("fairness report")
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn fair_dealing_checks_out_when_turned_on_after_joining() {
+    let mut net = lobby(2).await;
+    // the clients only send their entropy once they see the host's commitment
+    net.host.set_fair_dealing(true);
+    net.run_until("clients to see the commitment", |net| net.connected().all(|(_, c)| c.is_fair_dealing())).await;
+
+    let mut net = start_game(net).await;
+    play_to_end(&mut net).await;
+    for report in fairness_reports(&net) {
+        assert!(report.is_fair(), "{:?}", report.mismatches);
+    }
+}
+
+#[tokio::test]
+async fn seed_is_committed_before_the_deal() {
+    let mut net = lobby(2).await;
+    net.host.set_fair_dealing(true);
+    net.run_until("everyone's entropy to come in", |net| net.host.state().seed_commitment().is_some()).await;
+    net.run_until("clients to hold the seed commitment", |net| {
+        let seed_commitment = net.host.state().seed_commitment();
+        net.connected().all(|(_, c)| c.fairness_commitments().2 == seed_commitment)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn fair_dealing_checks_out_on_a_dedicated_server() {
+    // nobody sits at seat 0 for the host, so seats and lobby ids differ
+    let mut net = dedicated_lobby(2).await;
+    net.host.set_fair_dealing(true);
+    net.run_until("clients to see the commitment", |net| net.connected().all(|(_, c)| c.is_fair_dealing())).await;
+
+    let mut net = start_game(net).await;
+    play_to_end(&mut net).await;
+    for report in fairness_reports(&net) {
+        assert!(report.is_fair(), "{:?}", report.mismatches);
+    }
+}
+
+#[tokio::test]
+async fn fair_dealing_checks_out_after_a_kick() {
+    let mut net = lobby(3).await;
+    net.host.set_fair_dealing(true);
+    net.run_until("clients to see the commitment", |net| net.connected().all(|(_, c)| c.is_fair_dealing())).await;
+
+    // leaves a gap in the lobby ids
+    let kicked = net.client(0).get_player_id().unwrap();
+    net.host.kick_player(kicked, false).unwrap();
+    net.disconnect(0);
+    net.run_until("the others to see the kick", |net| net.connected().all(|(_, c)| c.get_lobby_players().len() == 3))
+        .await;
+
+    let mut net = start_game(net).await;
+    play_to_end(&mut net).await;
+    let reports = fairness_reports(&net);
+    assert_eq!(reports.len(), 2);
+    for report in reports {
+        assert!(report.is_fair(), "{:?}", report.mismatches);
+    }
+}
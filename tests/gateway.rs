@@ -0,0 +1,191 @@
+#![cfg(feature = "network")]
+
+mod common;
+
+use std::collections::HashMap;
+
+use boba_go::engine::Game;
+use boba_go::network::gateway::{GatewayEvent, GatewayRequest};
+use boba_go::network::{lobby_to_game_host, Gateway, GameHostEvent, HostEvent};
+use boba_go::engine::GameInterface;
+use common::{first_card, lobby_host, GameNet, LobbyNet, Net, Node, TIMEOUT};
+use libp2p::futures::{SinkExt, StreamExt};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+/// A player on the other side of the gateway, speaking JSON
+struct WebPlayer {
+    ws: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    events: Vec<GatewayEvent>,
+}
+
+impl WebPlayer {
+    async fn send(&mut self, request: GatewayRequest) {
+        let text = serde_json::to_string(&request).unwrap();
+        self.ws.send(Message::text(text)).await.expect("send");
+    }
+
+    /// Drive the network until `done` holds for what came over the socket
+    async fn run_until<H: Node, C: Node>(
+        &mut self,
+        net: &mut Net<H, C>,
+        what: &str,
+        mut done: impl FnMut(&[GatewayEvent], &Net<H, C>) -> bool,
+    ) {
+        let deadline = tokio::time::Instant::now() + TIMEOUT;
+        while !done(&self.events, net) {
+            if tokio::time::Instant::now() > deadline {
+                panic!("timed out waiting for {what}\ngateway events: {:?}", self.events);
+            }
+            tokio::select! {
+                message = self.ws.next() => {
+                    if let Some(Ok(Message::Text(text))) = message {
+                        self.events.push(serde_json::from_str(&text).expect("gateway event"));
+                    }
+                }
+                _ = net.step() => {}
+            }
+        }
+    }
+}
+
+/// Host with a gateway in front of it and one web player connected to the gateway
+async fn gateway_lobby() -> (LobbyNet, WebPlayer) {
+    let (host, address) = lobby_host().await;
+    let gateway = Gateway::bind("127.0.0.1:0").await.expect("bind").with_memory_transport();
+    let url = format!("ws://{}", gateway.local_addr().unwrap());
+    gateway.spawn(address.parse().unwrap());
+
+    (Net::new(host, Vec::new()), connect(&url).await)
+}
+
+async fn connect(url: &str) -> WebPlayer {
+    let (ws, _) = connect_async(url).await.expect("connect");
+    WebPlayer { ws, events: Vec::new() }
+}
+
+async fn join(net: &mut LobbyNet, player: &mut WebPlayer) {
+    player.send(GatewayRequest::Join { name: "Browser".to_string(), password: None }).await;
+    player
+        .run_until(net, "the web player to join", |events, _| {
+            events.iter().any(|e| matches!(e, GatewayEvent::Lobby { .. }))
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn web_player_joins_the_lobby() {
+    let (mut net, mut player) = gateway_lobby().await;
+    join(&mut net, &mut player).await;
+
+    assert!(matches!(&player.events[0], GatewayEvent::Joined { player_id: 1, players } if players.len() == 2));
+    assert!(net
+        .host_events
+        .iter()
+        .any(|e| matches!(e, HostEvent::PlayerJoined { player_name, .. } if player_name == "Browser")));
+
+    player.send(GatewayRequest::SetReady { ready: true }).await;
+    player
+        .run_until(&mut net, "the host to see the web player ready", |_, net| {
+            net.host.state().can_start()
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn requests_out_of_order_are_errors() {
+    let (mut net, mut player) = gateway_lobby().await;
+    player.send(GatewayRequest::ActivateDrinkTray).await;
+    player
+        .run_until(&mut net, "an error", |events, _| !events.is_empty())
+        .await;
+    assert!(matches!(&player.events[0], GatewayEvent::Error { message } if message == "Send a join request first"));
+
+    // the session is still usable afterwards
+    join(&mut net, &mut player).await;
+    player.events.clear();
+    player.send(GatewayRequest::SubmitTurn { selected: HashMap::new() }).await;
+    player
+        .run_until(&mut net, "an error", |events, _| {
+            events.iter().any(|e| matches!(e, GatewayEvent::Error { .. }))
+        })
+        .await;
+}
+
+fn errors(events: &[GatewayEvent]) -> usize {
+    events.iter().filter(|e| matches!(e, GatewayEvent::Error { .. })).count()
+}
+
+#[tokio::test]
+async fn web_player_plays_a_turn() {
+    let (mut net, mut player) = gateway_lobby().await;
+    join(&mut net, &mut player).await;
+    player.send(GatewayRequest::SetReady { ready: true }).await;
+    player.run_until(&mut net, "everyone to be ready", |_, net| net.host.state().can_start()).await;
+
+    let names = net.host.get_lobby_players().into_iter().map(|p| p.name).collect();
+    let game = Game::new(net.host.settings().to_config(names)).expect("game");
+    let mut game_host = lobby_to_game_host(net.host, game);
+    game_host.broadcast_game_update();
+    let mut net: GameNet = Net::new(game_host, Vec::new());
+
+    player
+        .run_until(&mut net, "the first hand", |events, _| {
+            events.iter().any(|e| matches!(e, GatewayEvent::GameState { .. }))
+        })
+        .await;
+    let Some(GatewayEvent::GameState { player_id, hand, status, .. }) =
+        player.events.iter().rev().find(|e| matches!(e, GatewayEvent::GameState { .. })).cloned()
+    else {
+        unreachable!()
+    };
+    assert_eq!(player_id, 1);
+
+    // one card a turn, neither none nor the whole hand
+    for selected in [HashMap::new(), hand.clone()] {
+        let refused = errors(&player.events);
+        player.send(GatewayRequest::SubmitTurn { selected }).await;
+        player
+            .run_until(&mut net, "the pick to be refused", |events, _| errors(events) > refused)
+            .await;
+    }
+    let (selected, _) = first_card(&hand);
+    player.send(GatewayRequest::SubmitTurn { selected }).await;
+
+    let (selected, remaining) = first_card(&net.host.get_hand());
+    GameInterface::submit_turn(&mut net.host, selected, remaining).expect("host turn");
+    player
+        .run_until(&mut net, "both turns to arrive", |_, net| {
+            net.host_events.iter().any(|e| matches!(e, GameHostEvent::AllPlayersSubmitted))
+        })
+        .await;
+    net.host.process_turn().expect("turn");
+
+    player
+        .run_until(&mut net, "the next hand", |events, _| {
+            events.iter().any(|e| {
+                matches!(e, GatewayEvent::GameState { status: next, .. } if next.turn != status.turn)
+            })
+        })
+        .await;
+}
+
+#[tokio::test]
+async fn connections_beyond_the_free_seats_are_turned_away() {
+    let (host, address) = lobby_host().await;
+    let gateway = Gateway::bind("127.0.0.1:0").await.expect("bind").with_memory_transport();
+    let url = format!("ws://{}", gateway.local_addr().unwrap());
+    gateway.free_seats().set(1);
+    gateway.spawn(address.parse().unwrap());
+
+    let mut first = connect(&url).await;
+    let mut second = connect(&url).await;
+    let mut net = Net::new(host, Vec::new());
+    second
+        .run_until(&mut net, "the second player to be turned away", |events, _| {
+            events.iter().any(|e| matches!(e, GatewayEvent::Disconnected { reason } if reason.contains("full")))
+        })
+        .await;
+    join(&mut net, &mut first).await;
+}
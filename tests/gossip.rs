@@ -0,0 +1,131 @@
+#![cfg(feature = "network")]
+
+mod common;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use boba_go::engine::GameInterface;
+use boba_go::network::chat::{ChatGossip, ChatLog, ChatMessage};
+use boba_go::network::gossip::{check_chat_gossip, check_host_gossip, HostGossip};
+use boba_go::network::swarm::build_memory_swarm;
+use boba_go::network::behaviour::BobaGoBehaviourEvent;
+use boba_go::network::{wire, GameClientEvent, GameEndReason, GameHostEvent, GameHostMessage};
+use common::{game_ended, lobby, play_to_end, player_name, start_game, HOST_NAME};
+use libp2p::futures::StreamExt;
+use libp2p::gossipsub::{self, IdentTopic, MessageAcceptance};
+use libp2p::identity::Keypair;
+use libp2p::swarm::SwarmEvent;
+use libp2p::{Multiaddr, PeerId};
+
+// a peer that connects to the host and, once it can, publishes a fake end of game
+fn spawn_spoofer(host_address: Multiaddr) {
+    let mut swarm = build_memory_swarm(Keypair::generate_ed25519()).unwrap();
+    let topic = IdentTopic::new("boba-go-lobby");
+    swarm.behaviour_mut().gossipsub.subscribe(&topic).unwrap();
+    swarm.dial(host_address).unwrap();
+
+    let fake = HostGossip {
+        seq: u64::MAX,
+        message: GameHostMessage::GameEnded {
+            final_scores: vec![],
+            reason: GameEndReason::Completed,
+            fairness: None,
+        },
+    };
+    let data = wire::encode(&fake).unwrap();
+
+    tokio::spawn(async move {
+        loop {
+            match swarm.select_next_some().await {
+                SwarmEvent::ConnectionEstablished { peer_id, .. } => {
+                    swarm.behaviour_mut().gossipsub.add_explicit_peer(&peer_id);
+                }
+                SwarmEvent::Behaviour(BobaGoBehaviourEvent::Gossipsub(gossipsub::Event::Subscribed { .. })) => {
+                    swarm.behaviour_mut().gossipsub.publish(topic.clone(), data.clone()).ok();
+                }
+                _ => {}
+            }
+        }
+    });
+}
+
+#[tokio::test]
+async fn spoofed_host_messages_are_not_passed_on() {
+    let mut net = start_game(lobby(2).await).await;
+    spawn_spoofer(net.host.listeners()[0].clone());
+
+    net.run_for(Duration::from_secs(2)).await;
+    assert!(net.client_events.iter().all(|events| !game_ended(events)));
+
+    // the real host is still believed
+    play_to_end(&mut net).await;
+}
+
+#[test]
+fn host_messages_are_accepted_once_in_order() {
+    let host = PeerId::random();
+    let mut last_seq = None;
+
+    assert!(matches!(check_host_gossip(Some(host), Some(host), 1, &mut last_seq), MessageAcceptance::Accept));
+    assert!(matches!(check_host_gossip(Some(host), Some(host), 1, &mut last_seq), MessageAcceptance::Ignore));
+    assert!(matches!(check_host_gossip(Some(host), Some(host), 3, &mut last_seq), MessageAcceptance::Accept));
+    assert!(matches!(check_host_gossip(Some(host), Some(host), 2, &mut last_seq), MessageAcceptance::Ignore));
+    assert_eq!(last_seq, Some(3));
+}
+
+#[test]
+fn host_messages_from_anyone_else_are_rejected() {
+    let host = PeerId::random();
+    let mut last_seq = None;
+
+    assert!(matches!(check_host_gossip(Some(PeerId::random()), Some(host), 1, &mut last_seq), MessageAcceptance::Reject));
+    assert!(matches!(check_host_gossip(None, Some(host), 1, &mut last_seq), MessageAcceptance::Reject));
+    // before we know the host nothing can be checked
+    assert!(matches!(check_host_gossip(Some(host), None, 1, &mut last_seq), MessageAcceptance::Ignore));
+    assert_eq!(last_seq, None);
+}
+
+fn chat_message(player_id: usize, player_name: &str) -> ChatGossip {
+    ChatGossip::Message(ChatMessage { player_id, player_name: player_name.to_string(), timestamp: 0, text: "hi".to_string() })
+}
+
+#[test]
+fn chat_has_to_come_from_the_player_it_claims() {
+    let (host, bob) = (PeerId::random(), PeerId::random());
+    let mut chat = ChatLog::new(Some(0), HOST_NAME.to_string());
+    chat.set_senders(HashMap::from([(bob, (2, "Bob".to_string()))]));
+    let check = |gossip: &ChatGossip, source: PeerId, chat: &ChatLog| check_chat_gossip(gossip, Some(source), Some(host), chat);
+
+    assert!(matches!(check(&chat_message(2, "Bob"), bob, &chat), MessageAcceptance::Accept));
+    assert!(matches!(check(&chat_message(0, "Bob"), bob, &chat), MessageAcceptance::Reject));
+    assert!(matches!(check(&chat_message(0, HOST_NAME), host, &chat), MessageAcceptance::Accept));
+    // someone who joined after our last lobby update
+    assert!(matches!(check(&chat_message(3, "Carol"), PeerId::random(), &chat), MessageAcceptance::Ignore));
+
+    // the name is the one we know the peer by, whatever the message says
+    let shown = chat.handle_gossip(chat_message(2, HOST_NAME), Some(bob), false).unwrap();
+    assert_eq!((shown.player_id, shown.player_name.as_str()), (2, "Bob"));
+
+    chat.set_muted(2, true);
+    assert!(matches!(check(&chat_message(2, "Bob"), bob, &chat), MessageAcceptance::Reject));
+    assert!(chat.handle_gossip(chat_message(2, "Bob"), Some(bob), false).is_none());
+}
+
+#[tokio::test]
+async fn chat_in_game_shows_the_senders_seat() {
+    let mut net = start_game(lobby(2).await).await;
+    let seat = GameInterface::get_player_id(net.client(1));
+    net.client(1).send_chat("hello").unwrap();
+
+    net.run_until("the chat to arrive", |net| {
+        net.host_events.iter().any(|e| matches!(e, GameHostEvent::ChatReceived { .. }))
+            && net.client_events[0].iter().any(|e| matches!(e, GameClientEvent::ChatReceived { .. }))
+    })
+    .await;
+    let Some(GameHostEvent::ChatReceived { message }) = net.host_events.iter().find(|e| matches!(e, GameHostEvent::ChatReceived { .. })) else {
+        unreachable!()
+    };
+    assert_eq!((message.player_id, message.player_name.clone()), (seat, player_name(1)));
+}
+
@@ -0,0 +1,73 @@
+#![cfg(feature = "network")]
+
+mod common;
+
+use boba_go::network::identity;
+use boba_go::network::{Avatar, Identity, ProfileColor};
+use common::{lobby_client, lobby_host, Net, TempDir};
+
+#[test]
+fn identity_is_the_same_across_launches() {
+    let dir = TempDir::new("identity");
+    let first = Identity::load_or_create(dir.path()).unwrap();
+    let second = Identity::load_or_create(dir.path()).unwrap();
+    assert_eq!(first.peer_id(), second.peer_id());
+
+    let other_dir = TempDir::new("other");
+    let other = Identity::load_or_create(other_dir.path()).unwrap();
+    assert_ne!(first.peer_id(), other.peer_id());
+}
+
+#[test]
+fn key_file_keeps_the_same_key() {
+    let dir = TempDir::new("relay");
+    let path = dir.path().join("relay.key");
+    let first = identity::load_or_create_key(&path).unwrap();
+    let second = identity::load_or_create_key(&path).unwrap();
+    assert_eq!(first.public(), second.public());
+}
+
+#[test]
+fn profiles_have_their_own_identity() {
+    let dir = TempDir::new("profiles");
+    let default = Identity::load_or_create(dir.path()).unwrap();
+    let second = Identity::load_or_create(&identity::profile_dir(dir.path(), "second")).unwrap();
+    assert_ne!(default.peer_id(), second.peer_id());
+
+    assert!(identity::set_profile("../elsewhere").is_err());
+}
+
+#[test]
+fn profile_is_saved_and_loaded() {
+    let dir = TempDir::new("profile");
+    let mut identity = Identity::load_or_create(dir.path()).unwrap();
+    assert!(identity.profile.name.is_empty());
+
+    identity.profile.name = "Taro".to_string();
+    identity.profile.avatar = Avatar { glyph: '♪', color: ProfileColor::Cyan };
+    identity.save_profile().unwrap();
+
+    let loaded = Identity::load_or_create(dir.path()).unwrap();
+    assert_eq!(loaded.profile, identity.profile);
+}
+
+#[tokio::test]
+async fn avatars_reach_the_other_players() {
+    let (host, address) = lobby_host().await;
+    let avatar = Avatar { glyph: '★', color: ProfileColor::Magenta };
+    let mut guest = lobby_client("Guest", &address);
+    guest.set_avatar(avatar);
+    let mut net = Net::new(host, vec![guest, lobby_client("Other", &address)]);
+
+    net.run_until("everyone to see the guest", |net| {
+        net.connected().all(|(_, c)| c.get_lobby_players().len() == 3)
+    })
+    .await;
+
+    for (_, client) in net.connected() {
+        let guest = client.get_lobby_players().into_iter().find(|p| p.name == "Guest").unwrap();
+        assert_eq!(guest.avatar, avatar);
+    }
+    let host_view = net.host.get_lobby_players().into_iter().find(|p| p.name == "Guest").unwrap();
+    assert_eq!(host_view.avatar, avatar);
+}
@@ -0,0 +1,94 @@
+#![cfg(feature = "network")]
+
+mod common;
+
+use std::time::Duration;
+
+use boba_go::network::latency::{LatencyTracker, MAX_MISSED_PINGS};
+use boba_go::engine::GameInterface;
+use boba_go::network::PlayerLatencies;
+use common::{lobby, start_game};
+use libp2p::swarm::ConnectionId;
+use libp2p::{ping, PeerId};
+
+#[tokio::test]
+async fn everyone_sees_everyones_latency_in_the_lobby() {
+    let mut net = lobby(2).await;
+    let ids: Vec<usize> = (0..2).map(|i| net.client(i).get_player_id().unwrap()).collect();
+
+    net.run_until("every latency to be reported", |net| {
+        net.connected().all(|(_, client)| ids.iter().all(|id| client.player_latency(*id).is_some()))
+    })
+    .await;
+
+    for id in &ids {
+        let latency = net.host.player_latency(*id).unwrap();
+        assert!(latency.rtt_ms.is_some());
+        assert!(!latency.unstable);
+    }
+    // nobody measures the host against itself
+    assert!(net.host.player_latency(0).is_none());
+}
+
+#[tokio::test]
+async fn latency_carries_over_into_the_game() {
+    let mut net = start_game(lobby(2).await).await;
+    let seats: Vec<usize> = (0..2).map(|i| net.client(i).get_player_id()).collect();
+
+    net.run_until("the game to report latency", |net| {
+        net.connected().all(|(_, client)| seats.iter().all(|seat| PlayerLatencies::player_latency(client, *seat).is_some()))
+    })
+    .await;
+    assert!(PlayerLatencies::player_latency(&net.host, seats[0]).is_some());
+}
+
+fn ping(peer: PeerId, result: Result<Duration, ping::Failure>) -> ping::Event {
+    ping::Event { peer, connection: ConnectionId::new_unchecked(0), result }
+}
+
+#[test]
+fn missed_pings_flag_the_connection_before_giving_up() {
+    let peer = PeerId::random();
+    let mut tracker = LatencyTracker::new();
+    assert!(tracker.latency(&peer).is_none());
+
+    assert!(!tracker.record(&ping(peer, Ok(Duration::from_millis(42)))));
+    let latency = tracker.latency(&peer).unwrap();
+    assert_eq!(latency.rtt_ms, Some(42));
+    assert_eq!(latency.to_string(), "42 ms");
+
+    assert!(!tracker.record(&ping(peer, Err(ping::Failure::Timeout))));
+    let latency = tracker.latency(&peer).unwrap();
+    assert!(latency.unstable);
+    assert_eq!(latency.to_string(), "connection unstable");
+
+    // an answer clears it again
+    tracker.record(&ping(peer, Ok(Duration::from_millis(50))));
+    assert!(!tracker.latency(&peer).unwrap().unstable);
+
+    for missed in 1..=MAX_MISSED_PINGS {
+        assert_eq!(tracker.record(&ping(peer, Err(ping::Failure::Timeout))), missed == MAX_MISSED_PINGS);
+    }
+}
+
+#[test]
+fn peers_without_ping_are_not_unstable() {
+    let peer = PeerId::random();
+    let mut tracker = LatencyTracker::new();
+    tracker.record(&ping(peer, Err(ping::Failure::Unsupported)));
+    assert!(!tracker.latency(&peer).unwrap().unstable);
+}
+
+#[test]
+fn instability_is_reported_right_away() {
+    let peer = PeerId::random();
+    let mut tracker = LatencyTracker::new();
+    tracker.record(&ping(peer, Ok(Duration::from_millis(10))));
+    assert!(tracker.report_due());
+
+    tracker.record(&ping(peer, Ok(Duration::from_millis(12))));
+    assert!(!tracker.report_due());
+
+    tracker.record(&ping(peer, Err(ping::Failure::Timeout)));
+    assert!(tracker.report_due());
+}
@@ -0,0 +1,123 @@
+#![cfg(feature = "network")]
+
+mod common;
+
+use boba_go::network::{parse_room_code, ClientEvent, HostEvent};
+use common::{lobby, lobby_client, lobby_host, player_name, Net, HOST_NAME};
+
+#[test]
+fn room_codes_are_read_forgivingly() {
+    assert_eq!(parse_room_code(" boba-7q4k ").as_deref(), Some("BOBA-7Q4K"));
+    assert_eq!(parse_room_code("7QOL").as_deref(), Some("BOBA-7Q01"));
+    assert_eq!(parse_room_code("/ip4/127.0.0.1/tcp/4001"), None);
+    // only ASCII, however the low byte of a character reads
+    assert_eq!(parse_room_code("BOBA-7Q4Ł"), None);
+}
+
+#[tokio::test]
+async fn players_join_and_see_each_other() {
+    let net = lobby(2).await;
+
+    let joined: Vec<&str> = net
+        .host_events
+        .iter()
+        .filter_map(|e| match e {
+            HostEvent::PlayerJoined { player_name, .. } => Some(player_name.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(joined, ["Player 1", "Player 2"]);
+
+    for (_, client) in net.connected() {
+        let names: Vec<String> = client.get_lobby_players().into_iter().map(|p| p.name).collect();
+        assert_eq!(names, [HOST_NAME.to_string(), player_name(0), player_name(1)]);
+    }
+}
+
+#[tokio::test]
+async fn duplicate_name_is_rejected() {
+    let mut net = lobby(1).await;
+    let address = net.host.listeners()[0].to_string();
+    net.clients.push(Some(lobby_client(&player_name(0), &address)));
+    net.client_events.push(Vec::new());
+
+    net.run_until("the second join to be answered", |net| !net.client_events[1].is_empty()).await;
+    assert!(matches!(
+        &net.client_events[1][0],
+        ClientEvent::JoinRejected { reason } if reason == "Name already taken"
+    ));
+    assert_eq!(net.host.get_lobby_players().len(), 2);
+}
+
+#[tokio::test]
+async fn password_is_required_to_join() {
+    let (mut host, address) = lobby_host().await;
+    host.set_password(Some("tapioca".to_string()));
+    let mut net = Net::new(host, vec![lobby_client("Guest", &address)]);
+
+    net.run_until("the password challenge", |net| {
+        net.client_events[0].iter().any(|e| matches!(e, ClientEvent::PasswordRequired { .. }))
+    })
+    .await;
+    net.client(0).submit_password("tapioca".to_string());
+    net.run_until("the guest to join", |net| common::joined(&net.client_events[0])).await;
+
+    assert_eq!(net.host.get_lobby_players().len(), 2);
+}
+
+#[tokio::test]
+async fn ready_flags_reach_the_host() {
+    let mut net = lobby(2).await;
+    assert!(!net.host.state().can_start());
+
+    net.client(0).set_ready(true);
+    net.client(1).set_ready(true);
+    net.run_until("both players to be ready", |net| net.host.state().can_start()).await;
+
+    let ready: Vec<usize> = net
+        .host_events
+        .iter()
+        .filter_map(|e| match e {
+            HostEvent::ReadyChanged { player_id, ready: true } => Some(*player_id),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(ready.len(), 2);
+}
+
+#[tokio::test]
+async fn kicked_player_is_told_and_removed() {
+    let mut net = lobby(2).await;
+    let player_id = net.client(0).get_player_id().unwrap();
+
+    net.host.kick_player(player_id, false).unwrap();
+    net.run_until("the kick to arrive", |net| {
+        net.client_events[0].iter().any(|e| matches!(e, ClientEvent::Kicked { .. }))
+    })
+    .await;
+    net.run_until("the other player to see it", |net| {
+        net.clients[1].as_ref().unwrap().get_lobby_players().len() == 2
+    })
+    .await;
+
+    assert_eq!(net.host.get_lobby_players().len(), 2);
+}
+
+#[tokio::test]
+async fn leaving_player_is_removed_for_everyone() {
+    let mut net = lobby(2).await;
+    let leaving = net.client(0).local_peer_id();
+
+    net.disconnect(0);
+    net.run_until("the host to notice", |net| {
+        net.host_events.iter().any(|e| matches!(e, HostEvent::PlayerLeft { peer_id } if *peer_id == leaving))
+    })
+    .await;
+    net.run_until("the other player to see it", |net| {
+        net.clients[1].as_ref().unwrap().get_lobby_players().len() == 2
+    })
+    .await;
+
+    let names: Vec<String> = net.host.get_lobby_players().into_iter().map(|p| p.name).collect();
+    assert_eq!(names, [HOST_NAME.to_string(), player_name(1)]);
+}
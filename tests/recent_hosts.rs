@@ -0,0 +1,82 @@
+#![cfg(feature = "network")]
+
+mod common;
+
+use std::collections::HashMap;
+
+use boba_go::network::recent_hosts::MAX_RECENT_HOSTS;
+use boba_go::network::swarm::build_memory_swarm;
+use boba_go::network::{JoinOutcome, Reachability, ReachabilityProbe, RecentHosts};
+use common::{lobby, lobby_host, TempDir, ROOM_NAME, TIMEOUT};
+use libp2p::identity::Keypair;
+
+#[test]
+fn recent_hosts_are_saved_with_favorites_first() {
+    let dir = TempDir::new("recent");
+    let mut hosts = RecentHosts::load_from(dir.path());
+    hosts.record("BOBA-7Q4K", "Lunch", JoinOutcome::Joined).unwrap();
+    hosts.record("/memory/1", "", JoinOutcome::Unreachable).unwrap();
+    hosts.toggle_favorite("BOBA-7Q4K").unwrap();
+    // a later attempt without an answer keeps the name we know
+    hosts.record("BOBA-7Q4K", "", JoinOutcome::Rejected("Game already started".to_string())).unwrap();
+
+    let loaded = RecentHosts::load_from(dir.path());
+    let list = loaded.list();
+    assert_eq!(list.len(), 2);
+    assert_eq!(list[0].address, "BOBA-7Q4K");
+    assert_eq!(list[0].name, "Lunch");
+    assert!(list[0].favorite);
+    assert_eq!(list[0].outcome, JoinOutcome::Rejected("Game already started".to_string()));
+    assert_eq!(list[1].address, "/memory/1");
+}
+
+#[test]
+fn only_the_latest_rooms_are_kept_besides_favorites() {
+    let dir = TempDir::new("trim");
+    let mut hosts = RecentHosts::load_from(dir.path());
+    hosts.record("favorite", "", JoinOutcome::Joined).unwrap();
+    hosts.toggle_favorite("favorite").unwrap();
+    for i in 0..MAX_RECENT_HOSTS + 3 {
+        hosts.record(&format!("/memory/{i}"), "", JoinOutcome::Joined).unwrap();
+    }
+
+    assert_eq!(hosts.list().len(), MAX_RECENT_HOSTS + 1);
+    assert!(hosts.get("favorite").is_some());
+}
+
+#[tokio::test]
+async fn joining_tells_the_client_the_room_name() {
+    let mut net = lobby(1).await;
+    assert_eq!(net.client(0).room_name(), ROOM_NAME);
+}
+
+#[tokio::test]
+async fn probe_tells_live_rooms_from_dead_ones() {
+    let (mut host, address) = lobby_host().await;
+    let dead = "/memory/4242".to_string();
+    let swarm = build_memory_swarm(Keypair::generate_ed25519()).expect("memory swarm");
+    let mut probe = ReachabilityProbe::with_swarm(swarm, [address.clone(), dead.clone(), "not an address".to_string()]);
+
+    let mut results = HashMap::new();
+    tokio::time::timeout(TIMEOUT, async {
+        loop {
+            tokio::select! {
+                result = probe.next_result() => match result {
+                    Some((target, reachability)) => {
+                        results.insert(target, reachability);
+                    }
+                    None => break,
+                },
+                _ = host.next_event() => {}
+            }
+        }
+    })
+    .await
+    .expect("probe to finish");
+
+    assert_eq!(results[&address], Reachability::Reachable);
+    assert_eq!(results[&dead], Reachability::Unreachable);
+    assert_eq!(results["not an address"], Reachability::Unreachable);
+    // the probe never joined
+    assert_eq!(host.get_lobby_players().len(), 1);
+}
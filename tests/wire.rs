@@ -0,0 +1,125 @@
+#![cfg(feature = "network")]
+
+use std::collections::HashMap;
+
+use boba_go::engine::{CardKind, Game, GameConfig};
+use boba_go::network::game::delta::{delta_since, CardsDelta, Snapshot};
+use boba_go::network::wire::{self, WIRE_FORMAT};
+use boba_go::network::{GameClientMessage, GameHostMessage, GameRequestKind};
+
+fn cards(counts: &[(CardKind, usize)]) -> HashMap<CardKind, usize> {
+    counts.iter().copied().collect()
+}
+
+#[test]
+fn messages_survive_the_round_trip() {
+    let message = GameClientMessage::SubmitTurn {
+        selected_cards: cards(&[(CardKind::ThaiTea, 1)]),
+        remaining_hand: cards(&[(CardKind::MangoTea, 2), (CardKind::ThaiTea, 1)]),
+        state_version: 7,
+    };
+    let data = wire::encode(&message).unwrap();
+    assert_eq!(data[0], WIRE_FORMAT);
+
+    let Some(GameClientMessage::SubmitTurn { selected_cards, remaining_hand, state_version }) = wire::decode(&data) else {
+        panic!("not decoded as a turn submission");
+    };
+    assert_eq!(selected_cards, cards(&[(CardKind::ThaiTea, 1)]));
+    assert_eq!(remaining_hand, cards(&[(CardKind::MangoTea, 2), (CardKind::ThaiTea, 1)]));
+    assert_eq!(state_version, 7);
+}
+
+#[test]
+fn other_formats_and_messages_are_not_misread() {
+    let message = GameHostMessage::Accepted { request: GameRequestKind::Sync };
+    let mut data = wire::encode(&message).unwrap();
+    assert!(wire::decode::<GameClientMessage>(&data).is_none());
+
+    data[0] = WIRE_FORMAT + 1;
+    assert!(wire::decode::<GameHostMessage>(&data).is_none());
+    assert!(wire::decode::<GameHostMessage>(br#"{"Accepted":{"request":"Sync"}}"#).is_none());
+}
+
+#[test]
+fn card_deltas_only_carry_what_changed() {
+    let old = cards(&[(CardKind::ThaiTea, 2), (CardKind::MangoTea, 1)]);
+    let new = cards(&[(CardKind::ThaiTea, 2), (CardKind::Matcha, 1)]);
+
+    let delta = CardsDelta::since([&old], &new);
+    let mut changed = delta.0.clone();
+    changed.sort();
+    let mut expected = vec![(CardKind::MangoTea, 0), (CardKind::Matcha, 1)];
+    expected.sort();
+    assert_eq!(changed, expected);
+
+    let mut applied = old.clone();
+    delta.apply(&mut applied);
+    assert_eq!(applied, new);
+    assert!(CardsDelta::since([&new], &new).is_empty());
+}
+
+#[test]
+fn a_delta_applies_on_top_of_any_version_since_its_base() {
+    // a card that changed and then changed back still has to be sent,
+    // someone on the middle version has the wrong count otherwise
+    let first = cards(&[(CardKind::ThaiTea, 1)]);
+    let middle = cards(&[(CardKind::ThaiTea, 2)]);
+    let last = cards(&[(CardKind::ThaiTea, 1)]);
+
+    let delta = CardsDelta::since([&first, &middle], &last);
+    for start in [&first, &middle] {
+        let mut applied = start.clone();
+        delta.apply(&mut applied);
+        assert_eq!(applied, last);
+    }
+}
+
+#[test]
+fn game_deltas_rebuild_the_next_state() {
+    let mut game = Game::new(GameConfig {
+        player_names: vec!["A".to_string(), "B".to_string(), "C".to_string()],
+        seed: Some(7),
+        ..GameConfig::default()
+    })
+    .unwrap();
+    let before = Snapshot::of(&game);
+
+    let submissions = (0..3)
+        .map(|player_id| {
+            let hand = game.get_player_hand(player_id).unwrap().clone();
+            let card = *hand.iter().find(|(_, count)| **count > 0).unwrap().0;
+            let mut remaining = hand.clone();
+            *remaining.get_mut(&card).unwrap() -= 1;
+            game.mark_player_selected(player_id).unwrap();
+            Some((cards(&[(card, 1)]), remaining))
+        })
+        .collect();
+    game.process_turn(submissions).unwrap();
+    let after = Snapshot::of(&game);
+
+    let (hands, players) = delta_since([&before].into_iter(), &after);
+    for (seat, hand) in before.hands.iter().enumerate() {
+        let mut applied = hand.clone();
+        if let Some((_, delta)) = hands.iter().find(|(id, _)| *id == seat) {
+            delta.apply(&mut applied);
+        }
+        applied.retain(|_, count| *count > 0);
+        let mut expected = after.hands[seat].clone();
+        expected.retain(|_, count| *count > 0);
+        assert_eq!(applied, expected);
+    }
+
+    let mut players_public = before.players_public.clone();
+    for delta in &players {
+        delta.apply(&mut players_public);
+    }
+    for (applied, expected) in players_public.iter().zip(&after.players_public) {
+        assert_eq!(applied.id, expected.id);
+        let nonzero = |cards: &HashMap<CardKind, usize>| {
+            let mut cards = cards.clone();
+            cards.retain(|_, count| *count > 0);
+            cards
+        };
+        assert_eq!(nonzero(&applied.public_cards), nonzero(&expected.public_cards));
+    }
+}